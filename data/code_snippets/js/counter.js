@@ -0,0 +1,12 @@
+class Counter {
+  #count = 0;
+
+  increment(step = 1) {
+    this.#count += step;
+    return this;
+  }
+
+  get value() {
+    return this.#count;
+  }
+}
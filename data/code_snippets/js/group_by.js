@@ -0,0 +1,7 @@
+const groupBy = (items, key) =>
+  items.reduce((groups, item) => {
+    (groups[item[key]] ||= []).push(item);
+    return groups;
+  }, {});
+
+console.log(groupBy([{ type: "a" }, { type: "b" }], "type"));
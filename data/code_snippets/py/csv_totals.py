@@ -0,0 +1,10 @@
+import csv
+
+with open("sales.csv", newline="") as f:
+    totals = {}
+    for row in csv.DictReader(f):
+        region = row["region"]
+        totals[region] = totals.get(region, 0.0) + float(row["amount"])
+
+for region, total in sorted(totals.items()):
+    print(f"{region:<10} {total:>8.2f}")
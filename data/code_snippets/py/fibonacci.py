@@ -0,0 +1,8 @@
+def fibonacci(limit):
+    a, b = 0, 1
+    while a < limit:
+        yield a
+        a, b = b, a + b
+
+
+print(list(fibonacci(100)))
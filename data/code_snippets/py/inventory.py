@@ -0,0 +1,11 @@
+class Inventory:
+    def __init__(self):
+        self.items = {}
+
+    def add(self, name, quantity=1):
+        self.items[name] = self.items.get(name, 0) + quantity
+
+    def remove(self, name):
+        if name not in self.items:
+            raise KeyError(f"no {name!r} in stock")
+        del self.items[name]
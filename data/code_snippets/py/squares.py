@@ -0,0 +1,3 @@
+squares = [n * n for n in range(10) if n % 2 == 0]
+lookup = {n: str(n) for n in squares}
+print(squares, lookup.get(16, "missing"))
@@ -0,0 +1,10 @@
+fn read_config(path: &Path) -> Result<Config, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let config: Config = serde_json::from_str(&contents)?;
+
+    if config.name.is_empty() {
+        return Err("config needs a name".into());
+    }
+
+    Ok(config)
+}
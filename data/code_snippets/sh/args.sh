@@ -0,0 +1,8 @@
+while [ $# -gt 0 ]; do
+    case "$1" in
+        -v|--verbose) verbose=1 ;;
+        -o|--output) output="$2"; shift ;;
+        *) echo "unknown option: $1" >&2; exit 1 ;;
+    esac
+    shift
+done
@@ -0,0 +1,6 @@
+backup() {
+	src="$1"
+	dest="${2:-$HOME/backups}"
+	mkdir -p "$dest"
+	tar -czf "$dest/$(date +%F).tar.gz" "$src"
+}
@@ -0,0 +1,8 @@
+#!/bin/sh
+set -eu
+
+for file in *.log; do
+	if [ -s "$file" ]; then
+		gzip -9 "$file"
+	fi
+done
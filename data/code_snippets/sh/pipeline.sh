@@ -0,0 +1 @@
+grep -rn "TODO" src/ | cut -d: -f1 | sort | uniq -c | sort -rn | head -n 10
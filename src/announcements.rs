@@ -0,0 +1,93 @@
+use gettextrs::gettext;
+use gtk::gio;
+use gtk::prelude::*;
+use i18n_format::i18n_fmt;
+
+// Whether lessons speak up on their own, for people following them with a screen reader
+pub fn screen_reader_mode() -> bool {
+    gio::Settings::new("io.github.nacho.mecalin").boolean("screen-reader-mode")
+}
+
+// Sends a message to the screen reader, if the screen reader mode is on
+pub fn announce(
+    accessible: &impl IsA<gtk::Accessible>,
+    message: &str,
+    priority: gtk::AccessibleAnnouncementPriority,
+) {
+    if screen_reader_mode() {
+        accessible.announce(message, priority);
+    }
+}
+
+// The finger names used by the keyboard layouts, as they should be spoken
+pub fn finger_name(finger: &str) -> Option<String> {
+    let name = match finger {
+        "left_pinky" => gettext("left pinky"),
+        "left_ring" => gettext("left ring finger"),
+        "left_middle" => gettext("left middle finger"),
+        "left_index" => gettext("left index finger"),
+        "left_thumb" => gettext("left thumb"),
+        "right_thumb" => gettext("right thumb"),
+        "right_index" => gettext("right index finger"),
+        "right_middle" => gettext("right middle finger"),
+        "right_ring" => gettext("right ring finger"),
+        "right_pinky" => gettext("right pinky"),
+        "both_thumbs" => gettext("either thumb"),
+        _ => return None,
+    };
+    Some(name)
+}
+
+// Screen readers skip blanks and most punctuation when reading a single character,
+// so those get a name of their own
+pub fn key_name(ch: char) -> String {
+    match ch {
+        ' ' => gettext("space"),
+        '\t' => gettext("tab"),
+        '\n' => gettext("enter"),
+        '.' => gettext("period"),
+        ',' => gettext("comma"),
+        ';' => gettext("semicolon"),
+        ':' => gettext("colon"),
+        '\'' => gettext("apostrophe"),
+        '"' => gettext("quotation mark"),
+        '-' => gettext("hyphen"),
+        '?' => gettext("question mark"),
+        '!' => gettext("exclamation mark"),
+        ch if ch.is_uppercase() => {
+            let lowercase = ch.to_lowercase().to_string();
+            // Translators: an uppercase letter to type, so it's clear Shift is needed
+            i18n_fmt! { i18n_fmt("capital {}", lowercase) }
+        }
+        ch => ch.to_string(),
+    }
+}
+
+pub fn next_key_message(ch: char, finger: Option<&str>) -> String {
+    let key = key_name(ch);
+    match finger.and_then(finger_name) {
+        // Translators: the key to type next and the finger to type it with, e.g. "f, left index finger"
+        Some(finger) => i18n_fmt! { i18n_fmt("{}, {}", key, finger) },
+        None => key,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_key_message() {
+        assert_eq!(
+            next_key_message('f', Some("left_index")),
+            "f, left index finger"
+        );
+        assert_eq!(
+            next_key_message(' ', Some("both_thumbs")),
+            "space, either thumb"
+        );
+        assert_eq!(next_key_message('J', None), "capital j");
+        assert_eq!(next_key_message('.', Some("unknown")), "period");
+        assert_eq!(finger_name("right_pinky").as_deref(), Some("right pinky"));
+    }
+}
@@ -0,0 +1,258 @@
+use std::error::Error;
+use std::path::Path;
+use std::str::FromStr;
+
+use gio::prelude::*;
+use serde_json::json;
+
+use crate::code_snippets;
+use crate::course::{Course, LessonsData, BUNDLED_LANGUAGES};
+use crate::course_validator::{self, Severity};
+use crate::custom_word_lists;
+use crate::keyboard_widget::KeyboardLayout;
+use crate::layout_validator;
+use crate::quotes;
+use crate::text_generation::{self, Language};
+use crate::typing_test_utils::GeneratedTestDifficulty;
+
+const SCHEMA_ID: &str = "io.github.nacho.mecalin";
+
+const USAGE: &str = "Usage:
+  mecalin                                   Start the application
+  mecalin generate [--lang CODE] [--difficulty TYPE] [--seed N] [--words FILE] [--top N]
+                                            Print a generated speed test text, where TYPE is
+                                            simple, advanced, quotes, code-rs, code-py,
+                                            code-js, code-sh or custom, which takes its
+                                            words from FILE, one per line. --top uses only
+                                            the N most common words
+  mecalin validate-course [FILE...]         Check course files, or every bundled course
+  mecalin validate-layout [FILE...]         Check keyboard layout files, or every bundled layout
+  mecalin stats export                      Print lesson progress as JSON
+  mecalin help                              Show this help";
+
+const EXIT_OK: i32 = 0;
+const EXIT_FAILURE: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+// Runs a headless subcommand and returns its exit code, or None when the
+// arguments are meant for the GTK application
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+
+    let code = match command.as_str() {
+        "generate" => generate(rest),
+        // Kept for compatibility with the original flag
+        "validate-course" | "--validate-course" => validate_courses(rest),
+        "validate-layout" => validate_layouts(rest),
+        "stats" => stats(rest),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            EXIT_OK
+        }
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+fn usage_error(message: &str) -> i32 {
+    eprintln!("{message}\n\n{USAGE}");
+    EXIT_USAGE
+}
+
+fn generate(args: &[String]) -> i32 {
+    let mut language = Language::default();
+    let mut difficulty = GeneratedTestDifficulty::Simple;
+    let mut seed = text_generation::random_seed();
+    let mut words_file = None;
+    let mut vocabulary = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--lang", Some(code)) => match Language::from_str(code) {
+                Ok(parsed) => language = parsed,
+                Err(_) => return usage_error(&format!("Unknown language: {code}")),
+            },
+            ("--difficulty", Some(value)) => {
+                match GeneratedTestDifficulty::from_settings_string(value) {
+                    Some(parsed) => difficulty = parsed,
+                    None => return usage_error(&format!("Unknown difficulty: {value}")),
+                }
+            }
+            ("--seed", Some(value)) => match value.parse() {
+                Ok(parsed) => seed = parsed,
+                Err(_) => return usage_error(&format!("Invalid seed: {value}")),
+            },
+            ("--words", Some(path)) => words_file = Some(path),
+            ("--top", Some(value)) => match value.parse() {
+                Ok(parsed) if parsed > 0 => vocabulary = Some(parsed),
+                _ => return usage_error(&format!("Invalid word count: {value}")),
+            },
+            _ => return usage_error(&format!("Invalid argument: {arg}")),
+        }
+    }
+
+    // The seed and attribution go to stderr so the text can be piped on its own
+    eprintln!("Seed: {seed}");
+    let text = match difficulty {
+        GeneratedTestDifficulty::Simple => text_generation::simple(language, vocabulary, seed),
+        GeneratedTestDifficulty::Advanced => text_generation::advanced(language, vocabulary, seed),
+        GeneratedTestDifficulty::Quotes => {
+            let quote_text = quotes::quotes(language, seed);
+            eprintln!("Sources: {}", quote_text.attribution());
+            quote_text.text
+        }
+        GeneratedTestDifficulty::Code(programming_language) => {
+            code_snippets::code(programming_language, seed)
+        }
+        GeneratedTestDifficulty::Custom => {
+            let Some(path) = words_file else {
+                return usage_error("Custom texts need a word list: --words FILE");
+            };
+            let words = match std::fs::read_to_string(path) {
+                Ok(contents) => custom_word_lists::parse_word_list(&contents),
+                Err(e) => {
+                    eprintln!("{path}: {e}");
+                    return EXIT_FAILURE;
+                }
+            };
+            if words.is_empty() {
+                eprintln!("{path}: the word list has no words");
+                return EXIT_FAILURE;
+            }
+            text_generation::custom(&words, vocabulary, seed)
+        }
+    };
+    println!("{text}");
+
+    EXIT_OK
+}
+
+// Prints every issue prefixed with where it was found, and returns whether any of them is an error
+fn report<T: std::fmt::Display>(
+    source: &str,
+    issues: &[T],
+    severity: impl Fn(&T) -> Severity,
+) -> bool {
+    for issue in issues {
+        println!("{source}: {issue}");
+    }
+
+    issues
+        .iter()
+        .any(|issue| severity(issue) == Severity::Error)
+}
+
+fn validate_courses(files: &[String]) -> i32 {
+    let mut failed = false;
+
+    if files.is_empty() {
+        for language in BUNDLED_LANGUAGES {
+            let result = LessonsData::bundled(language)
+                .map_err(Box::<dyn Error>::from)
+                .and_then(|data| {
+                    let layout = KeyboardLayout::load_from_json(language)?;
+                    Ok(course_validator::validate_lessons(&data, &layout))
+                });
+            failed |= report_result(&format!("bundled course {language}"), result, |issue| {
+                issue.severity()
+            });
+        }
+    }
+
+    for file in files {
+        let path = Path::new(file);
+        let result = course_validator::layout_for_course_file(path)
+            .and_then(|layout| course_validator::validate_course_file(path, &layout));
+        failed |= report_result(file, result, |issue| issue.severity());
+    }
+
+    if failed {
+        EXIT_FAILURE
+    } else {
+        EXIT_OK
+    }
+}
+
+fn validate_layouts(files: &[String]) -> i32 {
+    let mut failed = false;
+
+    if files.is_empty() {
+        for language in BUNDLED_LANGUAGES {
+            let result = KeyboardLayout::load_from_json(language)
+                .map(|layout| layout_validator::validate_layout(&layout));
+            failed |= report_result(&format!("bundled layout {language}"), result, |issue| {
+                issue.severity()
+            });
+        }
+    }
+
+    for file in files {
+        let result = layout_validator::validate_layout_file(Path::new(file));
+        failed |= report_result(file, result, |issue| issue.severity());
+    }
+
+    if failed {
+        EXIT_FAILURE
+    } else {
+        EXIT_OK
+    }
+}
+
+// Files that can't be read or parsed count as failures too
+fn report_result<T: std::fmt::Display>(
+    source: &str,
+    result: Result<Vec<T>, Box<dyn Error>>,
+    severity: impl Fn(&T) -> Severity,
+) -> bool {
+    match result {
+        Ok(issues) => report(source, &issues, severity),
+        Err(e) => {
+            eprintln!("{source}: error: {e}");
+            true
+        }
+    }
+}
+
+fn stats(args: &[String]) -> i32 {
+    match args {
+        [command] if command == "export" => stats_export(),
+        _ => usage_error("Expected: mecalin stats export"),
+    }
+}
+
+fn stats_export() -> i32 {
+    // gio::Settings::new() aborts when the schema isn't installed, so check first
+    let schema_installed = gio::SettingsSchemaSource::default()
+        .and_then(|source| source.lookup(SCHEMA_ID, true))
+        .is_some();
+    if !schema_installed {
+        eprintln!("Settings schema {SCHEMA_ID} is not installed");
+        return EXIT_FAILURE;
+    }
+
+    let settings = gio::Settings::new(SCHEMA_ID);
+    let language = crate::utils::language_from_locale();
+    let course = Course::new_with_language(language).unwrap_or_default();
+    let current_lesson = settings.uint("current-lesson");
+
+    let stats = json!({
+        "language": language,
+        "lessons": course.get_lessons().len(),
+        "current_lesson": current_lesson,
+        "current_lesson_title": course.get_lesson(current_lesson).map(|lesson| lesson.title.clone()),
+        "current_step": settings.uint("current-step"),
+    });
+
+    match serde_json::to_string_pretty(&stats) {
+        Ok(json) => {
+            println!("{json}");
+            EXIT_OK
+        }
+        Err(e) => {
+            eprintln!("Failed to export stats: {e}");
+            EXIT_FAILURE
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use include_dir::{include_dir, Dir};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use strum_macros::{Display as EnumDisplay, EnumIter, EnumMessage, EnumString};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::text_generation::CHUNK_GRAPHEME_COUNT;
+
+// Snippets live in data/code_snippets/{code}/, one file per snippet
+static EMBEDDED_SNIPPET_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/data/code_snippets");
+
+// All languages here MUST have a corresponding directory in data/code_snippets/{code}
+#[derive(
+    Clone, Copy, Debug, Default, EnumDisplay, EnumString, EnumIter, EnumMessage, PartialEq,
+)]
+pub enum ProgrammingLanguage {
+    #[default]
+    #[strum(message = "Rust", to_string = "rs")]
+    Rust,
+    #[strum(message = "Python", to_string = "py")]
+    Python,
+    #[strum(message = "JavaScript", to_string = "js")]
+    JavaScript,
+    #[strum(message = "Shell", to_string = "sh")]
+    Shell,
+}
+
+fn snippets_from_language(language: ProgrammingLanguage) -> Vec<&'static str> {
+    let dir = EMBEDDED_SNIPPET_DIR
+        .get_dir(language.to_string())
+        .unwrap_or_else(|| panic!("snippet directory for \"{}\" exists", language));
+
+    let mut snippets: Vec<&'static str> = dir
+        .files()
+        .map(|file| {
+            file.contents_utf8()
+                .expect("file has valid utf8 contents")
+                .trim_end()
+        })
+        .collect();
+    // Directory order isn't guaranteed, and seeded texts have to be the same everywhere
+    snippets.sort_unstable();
+
+    snippets
+}
+
+// Whole snippets in random order, separated by an empty line
+pub fn code(language: ProgrammingLanguage, seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut snippets = snippets_from_language(language);
+    snippets.shuffle(&mut rng);
+
+    let mut text = String::new();
+    for snippet in snippets {
+        if text.graphemes(true).count() >= CHUNK_GRAPHEME_COUNT {
+            break;
+        }
+
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(snippet);
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_bundled_snippets() {
+        for language in ProgrammingLanguage::iter() {
+            let snippets = snippets_from_language(language);
+            assert!(!snippets.is_empty(), "{language}");
+
+            for snippet in snippets {
+                assert!(!snippet.is_empty(), "{language}");
+                // Typed text starts at the first column, so only lines can be indented
+                assert!(!snippet.starts_with(char::is_whitespace), "{snippet}");
+                assert!(!snippet.contains('\r'), "{snippet}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_code_is_seeded() {
+        for language in ProgrammingLanguage::iter() {
+            let text = code(language, 5);
+            assert_eq!(text, code(language, 5));
+            assert!(!text.ends_with(char::is_whitespace));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LessonStep {
@@ -26,9 +27,35 @@ pub struct Lesson {
     pub introduction: bool,
 }
 
-#[derive(Serialize, Deserialize)]
-struct LessonsData {
-    lessons: Vec<Lesson>,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LessonsData {
+    pub lessons: Vec<Lesson>,
+}
+
+impl LessonsData {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn bundled(language: &str) -> Result<Self, serde_json::Error> {
+        Self::from_json(bundled_lessons_json(language))
+    }
+}
+
+// Languages that ship both a course in data/lessons/{code}.json and a keyboard
+// layout in data/keyboard_layouts/{code}.json
+pub const BUNDLED_LANGUAGES: &[&str] = &["us", "es", "fr", "gl", "it", "pl", "pt"];
+
+fn bundled_lessons_json(language: &str) -> &'static str {
+    match language {
+        "es" => include_str!("../data/lessons/es.json"),
+        "fr" => include_str!("../data/lessons/fr.json"),
+        "gl" => include_str!("../data/lessons/gl.json"),
+        "it" => include_str!("../data/lessons/it.json"),
+        "pl" => include_str!("../data/lessons/pl.json"),
+        "pt" => include_str!("../data/lessons/pt.json"),
+        _ => include_str!("../data/lessons/us.json"),
+    }
 }
 
 #[derive(Debug, Clone, glib::Boxed)]
@@ -39,16 +66,7 @@ pub struct Course {
 
 impl Course {
     pub fn new_with_language(language: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let lessons_json = match language {
-            "es" => include_str!("../data/lessons/es.json"),
-            "fr" => include_str!("../data/lessons/fr.json"),
-            "gl" => include_str!("../data/lessons/gl.json"),
-            "it" => include_str!("../data/lessons/it.json"),
-            "pl" => include_str!("../data/lessons/pl.json"),
-            "pt" => include_str!("../data/lessons/pt.json"),
-            _ => include_str!("../data/lessons/us.json"),
-        };
-        let lessons_data: LessonsData = serde_json::from_str(lessons_json)?;
+        let lessons_data = LessonsData::bundled(language)?;
         Ok(Self {
             lessons: lessons_data.lessons,
         })
@@ -67,6 +85,18 @@ impl Course {
             .iter()
             .find(|lesson| lesson.id == current_id + 1)
     }
+
+    // Every character typed in the exercises of a lesson and the lessons before it
+    pub fn learned_characters(&self, lesson_id: u32) -> HashSet<char> {
+        self.lessons
+            .iter()
+            .filter(|lesson| lesson.id <= lesson_id)
+            .flat_map(|lesson| &lesson.steps)
+            .filter(|step| !step.introduction)
+            .flat_map(|step| step.text.chars())
+            .filter(|ch| !ch.is_whitespace() && !ch.is_control())
+            .collect()
+    }
 }
 
 impl Default for Course {
@@ -144,6 +174,17 @@ mod tests {
         assert!(next_lesson.is_none());
     }
 
+    #[test]
+    fn test_learned_characters() {
+        let course = Course::new_with_language("us").unwrap();
+        let first_lesson: HashSet<char> = "asdkl;".chars().collect();
+        assert_eq!(course.learned_characters(1), first_lesson);
+
+        let second_lesson = course.learned_characters(2);
+        assert!(second_lesson.is_superset(&first_lesson));
+        assert!(second_lesson.contains(&'g') && !second_lesson.contains(&'q'));
+    }
+
     #[test]
     fn test_default_repetitions() {
         assert_eq!(default_repetitions(), 1);
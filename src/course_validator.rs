@@ -0,0 +1,375 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use crate::course::LessonsData;
+use crate::keyboard_widget::KeyboardLayout;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    DuplicateLessonId {
+        lesson: u32,
+    },
+    NonContiguousLessonId {
+        expected: u32,
+        found: u32,
+    },
+    DuplicateStepId {
+        lesson: u32,
+        step: u32,
+    },
+    NonContiguousStepId {
+        lesson: u32,
+        expected: u32,
+        found: u32,
+    },
+    EmptyStepText {
+        lesson: u32,
+        step: u32,
+    },
+    UnproducibleCharacter {
+        lesson: u32,
+        step: u32,
+        character: char,
+    },
+    KeyUsedBeforeIntroduction {
+        lesson: u32,
+        step: u32,
+        character: char,
+        introduced_in: u32,
+    },
+}
+
+impl ValidationIssue {
+    // Errors break navigation through the course, warnings only affect the learning experience
+    pub fn severity(&self) -> Severity {
+        match self {
+            ValidationIssue::DuplicateLessonId { .. }
+            | ValidationIssue::NonContiguousLessonId { .. }
+            | ValidationIssue::DuplicateStepId { .. }
+            | ValidationIssue::NonContiguousStepId { .. }
+            | ValidationIssue::EmptyStepText { .. } => Severity::Error,
+            ValidationIssue::UnproducibleCharacter { .. }
+            | ValidationIssue::KeyUsedBeforeIntroduction { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        match self {
+            ValidationIssue::DuplicateLessonId { lesson } => {
+                write!(f, "{prefix}: lesson {lesson}: duplicate lesson id")
+            }
+            ValidationIssue::NonContiguousLessonId { expected, found } => write!(
+                f,
+                "{prefix}: lesson {found}: expected lesson id {expected}, lesson ids must be contiguous"
+            ),
+            ValidationIssue::DuplicateStepId { lesson, step } => {
+                write!(f, "{prefix}: lesson {lesson}, step {step}: duplicate step id")
+            }
+            ValidationIssue::NonContiguousStepId {
+                lesson,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{prefix}: lesson {lesson}, step {found}: expected step id {expected}, step ids must be contiguous"
+            ),
+            ValidationIssue::EmptyStepText { lesson, step } => write!(
+                f,
+                "{prefix}: lesson {lesson}, step {step}: practice step has no text"
+            ),
+            ValidationIssue::UnproducibleCharacter {
+                lesson,
+                step,
+                character,
+            } => write!(
+                f,
+                "{prefix}: lesson {lesson}, step {step}: {character:?} can't be typed on this keyboard layout"
+            ),
+            ValidationIssue::KeyUsedBeforeIntroduction {
+                lesson,
+                step,
+                character,
+                introduced_in,
+            } => write!(
+                f,
+                "{prefix}: lesson {lesson}, step {step}: {character:?} is used before lesson {introduced_in} introduces it"
+            ),
+        }
+    }
+}
+
+pub fn validate_lessons(data: &LessonsData, layout: &KeyboardLayout) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    validate_ids(data, &mut issues);
+    validate_step_texts(data, layout, &mut issues);
+    validate_key_introduction(data, &mut issues);
+
+    issues
+}
+
+pub fn validate_course_file(
+    path: &Path,
+    layout: &KeyboardLayout,
+) -> Result<Vec<ValidationIssue>, Box<dyn std::error::Error>> {
+    let json = std::fs::read_to_string(path)?;
+    let data = LessonsData::from_json(&json)?;
+    Ok(validate_lessons(&data, layout))
+}
+
+// Courses live in data/lessons/{code}.json next to data/keyboard_layouts/{code}.json,
+// so prefer the layout on disk and fall back to the bundled one with the same code
+pub fn layout_for_course_file(path: &Path) -> Result<KeyboardLayout, Box<dyn std::error::Error>> {
+    let code = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| format!("Invalid course file name: {}", path.display()))?;
+
+    let layout_path = path
+        .parent()
+        .and_then(|dir| dir.parent())
+        .map(|dir| dir.join("keyboard_layouts").join(format!("{code}.json")));
+
+    match layout_path {
+        Some(layout_path) if layout_path.is_file() => {
+            let json = std::fs::read_to_string(layout_path)?;
+            Ok(serde_json::from_str(&json)?)
+        }
+        _ => KeyboardLayout::load_from_json(code),
+    }
+}
+
+// Lessons are chained with `id + 1` and steps are addressed by their position,
+// so both sets of ids have to be unique and contiguous
+fn validate_ids(data: &LessonsData, issues: &mut Vec<ValidationIssue>) {
+    let mut seen_lessons = HashSet::new();
+    let first_lesson_id = data.lessons.first().map(|lesson| lesson.id).unwrap_or(0);
+
+    for (index, lesson) in data.lessons.iter().enumerate() {
+        if !seen_lessons.insert(lesson.id) {
+            issues.push(ValidationIssue::DuplicateLessonId { lesson: lesson.id });
+        } else if lesson.id != first_lesson_id + index as u32 {
+            issues.push(ValidationIssue::NonContiguousLessonId {
+                expected: first_lesson_id + index as u32,
+                found: lesson.id,
+            });
+        }
+
+        let mut seen_steps = HashSet::new();
+        for (step_index, step) in lesson.steps.iter().enumerate() {
+            if !seen_steps.insert(step.id) {
+                issues.push(ValidationIssue::DuplicateStepId {
+                    lesson: lesson.id,
+                    step: step.id,
+                });
+            } else if step.id != step_index as u32 {
+                issues.push(ValidationIssue::NonContiguousStepId {
+                    lesson: lesson.id,
+                    expected: step_index as u32,
+                    found: step.id,
+                });
+            }
+        }
+    }
+}
+
+fn validate_step_texts(
+    data: &LessonsData,
+    layout: &KeyboardLayout,
+    issues: &mut Vec<ValidationIssue>,
+) {
+    for lesson in &data.lessons {
+        for step in lesson.steps.iter().filter(|step| !step.introduction) {
+            if step.text.is_empty() {
+                issues.push(ValidationIssue::EmptyStepText {
+                    lesson: lesson.id,
+                    step: step.id,
+                });
+                continue;
+            }
+
+            let mut reported = HashSet::new();
+            for ch in step.text.chars().filter(|ch| !ch.is_whitespace()) {
+                if !layout.can_produce_character(ch) && reported.insert(ch) {
+                    issues.push(ValidationIssue::UnproducibleCharacter {
+                        lesson: lesson.id,
+                        step: step.id,
+                        character: ch,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// A key is introduced by the first drill step that contains it. Drill steps are the
+// practice steps without whitespace (e.g. "a", "fj" or "!)($&%/"), as opposed to the
+// exercises that combine keys into words.
+fn validate_key_introduction(data: &LessonsData, issues: &mut Vec<ValidationIssue>) {
+    let mut introduced_in: HashMap<char, u32> = HashMap::new();
+    for lesson in &data.lessons {
+        for step in &lesson.steps {
+            if step.introduction || step.text.chars().any(char::is_whitespace) {
+                continue;
+            }
+
+            for ch in step.text.chars() {
+                introduced_in
+                    .entry(ch.to_lowercase().next().unwrap_or(ch))
+                    .or_insert(lesson.id);
+            }
+        }
+    }
+
+    for lesson in &data.lessons {
+        for step in lesson.steps.iter().filter(|step| !step.introduction) {
+            let mut reported = HashSet::new();
+            for ch in step.text.chars().filter(|ch| !ch.is_whitespace()) {
+                let key = ch.to_lowercase().next().unwrap_or(ch);
+                if let Some(&introducing_lesson) = introduced_in.get(&key) {
+                    if introducing_lesson > lesson.id && reported.insert(key) {
+                        issues.push(ValidationIssue::KeyUsedBeforeIntroduction {
+                            lesson: lesson.id,
+                            step: step.id,
+                            character: ch,
+                            introduced_in: introducing_lesson,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn validate_bundled_course(language: &str) -> Vec<ValidationIssue> {
+    let data = LessonsData::bundled(language).expect("bundled course is valid JSON");
+    let layout = KeyboardLayout::load_from_json(language).expect("bundled course has a layout");
+    validate_lessons(&data, &layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::course::BUNDLED_LANGUAGES;
+
+    fn lessons_from_json(json: &str) -> LessonsData {
+        LessonsData::from_json(json).unwrap()
+    }
+
+    #[test]
+    fn test_bundled_courses_have_no_errors() {
+        for language in BUNDLED_LANGUAGES {
+            let errors: Vec<String> = validate_bundled_course(language)
+                .iter()
+                .filter(|issue| issue.severity() == Severity::Error)
+                .map(|issue| issue.to_string())
+                .collect();
+            assert!(errors.is_empty(), "{language}: {errors:#?}");
+        }
+    }
+
+    #[test]
+    fn test_non_contiguous_lesson_ids() {
+        let data = lessons_from_json(
+            r#"{"lessons": [
+                {"id": 0, "title": "", "description": "", "steps": []},
+                {"id": 2, "title": "", "description": "", "steps": []},
+                {"id": 2, "title": "", "description": "", "steps": []}
+            ]}"#,
+        );
+        let issues = validate_lessons(&data, &KeyboardLayout::default());
+
+        assert!(issues.contains(&ValidationIssue::NonContiguousLessonId {
+            expected: 1,
+            found: 2
+        }));
+        assert!(issues.contains(&ValidationIssue::DuplicateLessonId { lesson: 2 }));
+    }
+
+    #[test]
+    fn test_duplicate_step_ids_and_empty_text() {
+        let data = lessons_from_json(
+            r#"{"lessons": [
+                {"id": 0, "title": "", "description": "", "steps": [
+                    {"id": 0, "text": "", "description": "Intro", "introduction": true},
+                    {"id": 1, "text": ""},
+                    {"id": 1, "text": "a"}
+                ]}
+            ]}"#,
+        );
+        let issues = validate_lessons(&data, &KeyboardLayout::default());
+
+        assert_eq!(
+            issues,
+            vec![
+                ValidationIssue::DuplicateStepId { lesson: 0, step: 1 },
+                ValidationIssue::EmptyStepText { lesson: 0, step: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unproducible_character() {
+        let data = lessons_from_json(
+            r#"{"lessons": [
+                {"id": 0, "title": "", "description": "", "steps": [
+                    {"id": 0, "text": "aą"}
+                ]}
+            ]}"#,
+        );
+        let layout = KeyboardLayout::load_from_json("us").unwrap();
+        let issues = validate_lessons(&data, &layout);
+
+        assert_eq!(
+            issues,
+            vec![ValidationIssue::UnproducibleCharacter {
+                lesson: 0,
+                step: 0,
+                character: 'ą'
+            }]
+        );
+        assert_eq!(issues[0].severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn test_key_used_before_introduction() {
+        let data = lessons_from_json(
+            r#"{"lessons": [
+                {"id": 0, "title": "", "description": "", "steps": [
+                    {"id": 0, "text": "as"},
+                    {"id": 1, "text": "as sad"}
+                ]},
+                {"id": 1, "title": "", "description": "", "steps": [
+                    {"id": 0, "text": "d"}
+                ]}
+            ]}"#,
+        );
+        let layout = KeyboardLayout::load_from_json("us").unwrap();
+        let issues = validate_lessons(&data, &layout);
+
+        assert_eq!(
+            issues,
+            vec![ValidationIssue::KeyUsedBeforeIntroduction {
+                lesson: 0,
+                step: 1,
+                character: 'd',
+                introduced_in: 1
+            }]
+        );
+    }
+}
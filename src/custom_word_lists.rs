@@ -0,0 +1,276 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use gio::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::text_generation::{split_frequency, WordList};
+
+// Custom word lists are plain text files with one word per line, optionally followed
+// by a tab and a frequency, stored in ~/.local/share/mecalin/word_lists/{name}.txt
+const EXTENSION: &str = "txt";
+
+// Same size as the bundled word lists
+pub const GENERATED_LIST_SIZE: usize = 200;
+
+thread_local! {
+    // The active list as last read, with its name and when its file was modified, so
+    // it's only parsed again once the setting or the file changes
+    static ACTIVE_LIST: RefCell<Option<(String, SystemTime, WordList)>> = const { RefCell::new(None) };
+}
+
+pub fn word_lists_dir() -> PathBuf {
+    glib::user_data_dir().join("mecalin").join("word_lists")
+}
+
+/// The word list selected in preferences, if any. Games and tests fall back to
+/// the words of the current language when this is `None`.
+pub fn active_word_list() -> Option<WordList> {
+    let settings = gio::Settings::new("io.github.nacho.mecalin");
+    let name = settings.string("custom-word-list");
+    if name.is_empty() {
+        return None;
+    }
+
+    let dir = word_lists_dir();
+    let modified = fs::metadata(list_path(&dir, &name))
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    ACTIVE_LIST.with(|active_list| {
+        let mut active_list = active_list.borrow_mut();
+        let is_current = active_list
+            .as_ref()
+            .is_some_and(|(n, m, _)| *n == name.as_str() && *m == modified);
+        if !is_current {
+            let words = load(&dir, &name).ok()?;
+            *active_list = Some((name.to_string(), modified, words));
+        }
+
+        active_list
+            .as_ref()
+            .map(|(_, _, words)| words.clone())
+            .filter(|words| !words.is_empty())
+    })
+}
+
+// How many of the most common words generated texts use, or `None` for all of them
+pub fn vocabulary_size() -> Option<usize> {
+    let settings = gio::Settings::new("io.github.nacho.mecalin");
+    match settings.uint("vocabulary-size") {
+        0 => None,
+        size => Some(size as usize),
+    }
+}
+
+// Vocabulary sizes to choose from, besides all the words
+const VOCABULARY_SIZES: &[u32] = &[50, 100, 200, 500, 1000, 5000, 10000];
+
+// The vocabulary sizes that leave out some words of a list with `word_count` words,
+// after 0 for all of them
+pub fn vocabulary_sizes(word_count: usize) -> Vec<u32> {
+    std::iter::once(0)
+        .chain(
+            VOCABULARY_SIZES
+                .iter()
+                .copied()
+                .filter(|size| (*size as usize) < word_count),
+        )
+        .collect()
+}
+
+// Words in the order they appear, without blank lines, comments or duplicates
+pub fn parse_word_list(contents: &str) -> WordList {
+    let mut entries: Vec<(&str, Option<f64>)> = Vec::new();
+    let mut seen = HashSet::new();
+    for line in contents.lines() {
+        let (word, frequency) = split_frequency(line.trim());
+        let word = word.trim();
+        if word.is_empty() || word.starts_with('#') || !seen.insert(word) {
+            continue;
+        }
+        entries.push((word, frequency));
+    }
+
+    WordList::from_entries(entries)
+}
+
+// The most common words of a document, most frequent first. Words are counted
+// case-insensitively but kept in the spelling used most often, so product names
+// keep their capitals while "The" at the start of a sentence counts as "the".
+pub fn words_by_frequency(document: &str, limit: usize) -> WordList {
+    let mut counts: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+    for word in document.unicode_words() {
+        if word.graphemes(true).count() < 2 || word.chars().all(|c| c.is_numeric()) {
+            continue;
+        }
+
+        *counts
+            .entry(word.to_lowercase())
+            .or_default()
+            .entry(word)
+            .or_default() += 1;
+    }
+
+    let mut words: Vec<(usize, String, &str)> = counts
+        .into_iter()
+        .map(|(key, spellings)| {
+            let total = spellings.values().sum();
+            let (spelling, _) = spellings
+                .into_iter()
+                .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+                .expect("counted words have at least one spelling");
+            (total, key, spelling)
+        })
+        .collect();
+    // Ties are sorted alphabetically so the same document always gives the same list
+    words.sort_by(|(a_count, a, _), (b_count, b, _)| b_count.cmp(a_count).then(a.cmp(b)));
+
+    WordList::from_entries(
+        words
+            .into_iter()
+            .take(limit)
+            .map(|(count, _, spelling)| (spelling, Some(count as f64))),
+    )
+}
+
+pub fn list_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == EXTENSION))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+
+    names
+}
+
+pub fn load(dir: &Path, name: &str) -> io::Result<WordList> {
+    let contents = fs::read_to_string(list_path(dir, name))?;
+    Ok(parse_word_list(&contents))
+}
+
+// Saves the words under a name that isn't taken yet, and returns that name
+pub fn save(dir: &Path, name: &str, words: &WordList) -> io::Result<String> {
+    if words.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "the word list has no words",
+        ));
+    }
+
+    fs::create_dir_all(dir)?;
+
+    let taken = list_names(dir);
+    let name = std::iter::once(name.to_string())
+        .chain((2..).map(|n| format!("{name} {n}")))
+        .find(|candidate| !taken.contains(candidate))
+        .expect("there is always a free name");
+
+    let contents: String = match words.frequencies() {
+        Some(frequencies) => words
+            .words()
+            .iter()
+            .zip(frequencies)
+            .map(|(word, frequency)| format!("{word}\t{frequency}\n"))
+            .collect(),
+        None => words
+            .words()
+            .iter()
+            .map(|word| format!("{word}\n"))
+            .collect(),
+    };
+    fs::write(list_path(dir, &name), contents)?;
+    Ok(name)
+}
+
+pub fn remove(dir: &Path, name: &str) -> io::Result<()> {
+    fs::remove_file(list_path(dir, name))
+}
+
+// Imports a file with one word per line, with or without frequencies
+pub fn import_file(dir: &Path, path: &Path) -> io::Result<String> {
+    let words = parse_word_list(&fs::read_to_string(path)?);
+    save(dir, &name_from_path(path), &words)
+}
+
+// Creates a list with the most common words of any text document
+pub fn generate_from_document(dir: &Path, path: &Path) -> io::Result<String> {
+    let words = words_by_frequency(&fs::read_to_string(path)?, GENERATED_LIST_SIZE);
+    save(dir, &name_from_path(path), &words)
+}
+
+fn name_from_path(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .filter(|stem| !stem.starts_with('.'))
+        .unwrap_or_else(|| "words".to_string())
+}
+
+fn list_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{name}.{EXTENSION}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_word_list() {
+        let words = parse_word_list("Mecalin\n\n  GTK  \n# comment\nmecalin\nGTK\r\n");
+        assert_eq!(words.words(), ["Mecalin", "GTK", "mecalin"]);
+        assert_eq!(words.frequencies(), None);
+
+        let words = parse_word_list("kubectl\t20\nKubernetes\t 350 \n");
+        assert_eq!(words.words(), ["Kubernetes", "kubectl"]);
+        assert_eq!(words.frequencies(), Some(&[350.0, 20.0][..]));
+    }
+
+    #[test]
+    fn test_vocabulary_sizes() {
+        assert_eq!(vocabulary_sizes(200), vec![0, 50, 100]);
+        assert_eq!(vocabulary_sizes(1442), vec![0, 50, 100, 200, 500, 1000]);
+        assert_eq!(vocabulary_sizes(20), vec![0]);
+    }
+
+    #[test]
+    fn test_words_by_frequency() {
+        let document = "The router restarts. Then the Router logs it, and the router waits 10 s.";
+        let words = words_by_frequency(document, 3);
+        assert_eq!(words.words(), ["router", "the", "and"]);
+        assert_eq!(words.frequencies(), Some(&[3.0, 3.0, 1.0][..]));
+
+        // Numbers and single letters aren't words worth drilling
+        let words = words_by_frequency(document, 100);
+        assert!(!words.words().contains(&"10".to_string()));
+        assert!(!words.words().contains(&"s".to_string()));
+    }
+
+    #[test]
+    fn test_save_load_and_remove() {
+        let dir = std::env::temp_dir().join(format!("mecalin-word-lists-{}", std::process::id()));
+        let words = parse_word_list("Kubernetes\nkubectl\n");
+        let counted = parse_word_list("Kubernetes\t2\nkubectl\t1.5\n");
+
+        assert_eq!(save(&dir, "jargon", &words).unwrap(), "jargon");
+        assert_eq!(save(&dir, "jargon", &counted).unwrap(), "jargon 2");
+        assert!(save(&dir, "empty", &WordList::default()).is_err());
+
+        assert_eq!(list_names(&dir), vec!["jargon", "jargon 2"]);
+        assert_eq!(load(&dir, "jargon").unwrap(), words);
+        assert_eq!(load(&dir, "jargon 2").unwrap(), counted);
+
+        remove(&dir, "jargon").unwrap();
+        assert_eq!(list_names(&dir), vec!["jargon 2"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
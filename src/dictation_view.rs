@@ -0,0 +1,279 @@
+use gtk::gdk;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use i18n_format::i18n_fmt;
+use libadwaita as adw;
+use std::cell::{Cell, RefCell};
+use std::str::FromStr;
+
+use crate::quotes::{corpus_lang_code, quotes};
+use crate::speech::{default_backend, SpeechBackend};
+use crate::text_generation::{random_seed, Language};
+use crate::text_utils::{dictation_phrases, transcription_score};
+
+// Longest phrase read at once, in words
+const MAX_PHRASE_WORDS: usize = 8;
+// Speed of the voice when reading slowly, relative to its normal speed
+const SLOW_RATE: f64 = 0.7;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/dictation_view.ui")]
+    pub struct DictationView {
+        #[template_child]
+        pub no_engine_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub dictation_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub show_text_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub slow_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub text_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub progress_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub text_view: TemplateChild<gtk::TextView>,
+        #[template_child]
+        pub repeat_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub next_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub check_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub results_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub accuracy_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub details_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub original_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub restart_button: TemplateChild<gtk::Button>,
+
+        pub backend: RefCell<Option<Box<dyn SpeechBackend>>>,
+        // Language of the voice, which is the one the text is written in
+        pub lang_code: RefCell<String>,
+        pub phrases: RefCell<Vec<String>>,
+        pub current_phrase: Cell<usize>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DictationView {
+        const NAME: &'static str = "DictationView";
+        type Type = super::DictationView;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+
+            klass.add_binding(gdk::Key::r, gdk::ModifierType::CONTROL_MASK, |view| {
+                view.speak_current_phrase();
+                glib::Propagation::Stop
+            });
+            klass.add_binding(gdk::Key::Return, gdk::ModifierType::CONTROL_MASK, |view| {
+                view.next_phrase();
+                glib::Propagation::Stop
+            });
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for DictationView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            let settings = gio::Settings::new("io.github.nacho.mecalin");
+            settings
+                .bind("dictation-show-text", &*self.show_text_check, "active")
+                .build();
+            settings
+                .bind("dictation-slow", &*self.slow_check, "active")
+                .build();
+            self.show_text_check
+                .bind_property("active", &*self.text_label, "visible")
+                .sync_create()
+                .build();
+
+            let backend = default_backend();
+            self.no_engine_page.set_visible(backend.is_none());
+            self.dictation_box.set_visible(backend.is_some());
+            self.backend.replace(backend);
+
+            obj.setup_signals();
+        }
+    }
+
+    impl WidgetImpl for DictationView {
+        fn grab_focus(&self) -> bool {
+            if self.results_box.is_visible() {
+                self.restart_button.grab_focus()
+            } else {
+                self.text_view.grab_focus()
+            }
+        }
+
+        fn unmap(&self) {
+            if let Some(backend) = self.backend.borrow().as_ref() {
+                backend.stop();
+            }
+            self.parent_unmap();
+        }
+    }
+
+    impl BoxImpl for DictationView {}
+}
+
+glib::wrapper! {
+    pub struct DictationView(ObjectSubclass<imp::DictationView>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl DictationView {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    // Picks a new text and reads its first phrase
+    pub fn start(&self) {
+        let imp = self.imp();
+        if imp.backend.borrow().is_none() {
+            return;
+        }
+
+        let lang_code = crate::utils::language_from_locale();
+        let language = Language::from_str(lang_code).unwrap_or(Language::English);
+        let text = quotes(language, random_seed()).text.trim().to_string();
+
+        imp.lang_code
+            .replace(corpus_lang_code(&language.to_string()).to_string());
+        imp.phrases
+            .replace(dictation_phrases(&text, MAX_PHRASE_WORDS));
+        imp.text_label.set_text(&text);
+        imp.current_phrase.set(0);
+        imp.text_view.buffer().set_text("");
+
+        imp.results_box.set_visible(false);
+        imp.dictation_box.set_visible(true);
+        imp.text_view.grab_focus();
+        self.speak_current_phrase();
+    }
+
+    fn speak_current_phrase(&self) {
+        let imp = self.imp();
+        if !imp.dictation_box.is_visible() {
+            return;
+        }
+
+        let phrases = imp.phrases.borrow();
+        let current_phrase = imp.current_phrase.get();
+        let Some(phrase) = phrases.get(current_phrase) else {
+            return;
+        };
+
+        let label_text = i18n_fmt! { i18n_fmt("Phrase {}/{}", current_phrase + 1, phrases.len()) };
+        imp.progress_label.set_text(&label_text);
+        imp.next_button
+            .set_sensitive(current_phrase + 1 < phrases.len());
+
+        let rate = if imp.slow_check.is_active() {
+            SLOW_RATE
+        } else {
+            1.
+        };
+        if let Some(backend) = imp.backend.borrow().as_ref() {
+            if let Err(e) = backend.speak(phrase, &imp.lang_code.borrow(), rate) {
+                glib::g_warning!("mecalin", "Could not read the text out loud: {e}");
+            }
+        }
+    }
+
+    fn next_phrase(&self) {
+        let imp = self.imp();
+        let next_phrase = imp.current_phrase.get() + 1;
+        if next_phrase < imp.phrases.borrow().len() {
+            imp.current_phrase.set(next_phrase);
+            self.speak_current_phrase();
+        }
+        imp.text_view.grab_focus();
+    }
+
+    fn check(&self) {
+        let imp = self.imp();
+        if let Some(backend) = imp.backend.borrow().as_ref() {
+            backend.stop();
+        }
+
+        // Only the phrases that were read count, so it can be checked before the end
+        let phrases = imp.phrases.borrow();
+        let heard = phrases[..(imp.current_phrase.get() + 1).min(phrases.len())].join(" ");
+        let buffer = imp.text_view.buffer();
+        let typed = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+        let score = transcription_score(&heard, &typed);
+
+        let accuracy = (score.accuracy() * 100.).floor();
+        // Translators: The `{}` block will be replaced with a percentage number. Do not
+        // translate it!
+        imp.accuracy_label
+            .set_text(&i18n_fmt! { i18n_fmt("{}% Accuracy", accuracy) });
+        // Translators: Counts of words, compared to what was read out loud
+        imp.details_label.set_text(&i18n_fmt! {
+            i18n_fmt("{} correct, {} wrong, {} missed and {} extra words", score.correct, score.wrong, score.missed, score.extra)
+        });
+        imp.original_label.set_text(&heard);
+
+        imp.dictation_box.set_visible(false);
+        imp.results_box.set_visible(true);
+        imp.restart_button.grab_focus();
+    }
+
+    fn setup_signals(&self) {
+        let imp = self.imp();
+
+        imp.repeat_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.speak_current_phrase();
+                view.imp().text_view.grab_focus();
+            }
+        ));
+
+        imp.next_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.next_phrase();
+            }
+        ));
+
+        imp.check_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.check();
+            }
+        ));
+
+        imp.restart_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.start();
+            }
+        ));
+    }
+}
+
+impl Default for DictationView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,25 +1,87 @@
 use gtk::gdk;
+use gtk::gio;
 use gtk::glib;
 use gtk::graphene;
 use gtk::pango;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use i18n_format::i18n_fmt;
+use rand::seq::SliceRandom;
 use rand::Rng;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-const KEYS: &[char] = &[
-    'a', 's', 'd', 'f', 'j', 'k', 'l', 'q', 'w', 'e', 'r', 'u', 'i', 'o', 'p',
-];
+use crate::course::Course;
+use crate::game_engine::{ArcadeGame, Difficulty, DifficultyCurve, Entity, GameState, GameWidgets};
+use crate::game_setup::GameSetup;
+use crate::high_scores::Game;
+use crate::keyboard_widget::KeyboardLayout;
+use crate::pause::Pausable;
+use crate::pause_overlay::{self, PauseOverlay};
+use crate::sounds::{self, Sound};
+
+// Keys of the current keyboard layout, or only the ones learned so far in the course
+fn game_keys(learned_only: bool) -> Vec<char> {
+    let layout_code = crate::utils::language_from_locale();
+    let keys = KeyboardLayout::load_from_json(layout_code)
+        .unwrap_or_default()
+        .single_key_characters();
+    if !learned_only {
+        return keys;
+    }
+
+    let settings = gio::Settings::new("io.github.nacho.mecalin");
+    let course = Course::new_with_language(layout_code).unwrap_or_default();
+    let learned = course.learned_characters(settings.uint("current-lesson"));
+    let learned_keys: Vec<char> = keys
+        .iter()
+        .copied()
+        .filter(|k| learned.contains(k))
+        .collect();
+
+    // Nothing has been learned before the first lesson with exercises
+    if learned_keys.is_empty() {
+        keys
+    } else {
+        learned_keys
+    }
+}
 
-#[derive(Clone)]
-pub(crate) struct FallingKey {
-    key: char,
-    x: f64,
-    y: f64,
+// How fast keys fall, in pixels per second, and how often they drop
+fn difficulty_curve(difficulty: Difficulty) -> DifficultyCurve {
+    match difficulty {
+        Difficulty::Easy => DifficultyCurve {
+            start_speed: 30.0,
+            speed_step: 5.0,
+            points_per_level: 10,
+            spawn_interval: 2.0,
+            spawn_interval_step: 0.1,
+            min_spawn_interval: 1.2,
+            max_objects: 5,
+        },
+        Difficulty::Normal => DifficultyCurve {
+            start_speed: 40.0,
+            speed_step: 10.0,
+            points_per_level: 10,
+            spawn_interval: 1.5,
+            spawn_interval_step: 0.1,
+            min_spawn_interval: 0.8,
+            max_objects: 8,
+        },
+        Difficulty::Hard => DifficultyCurve {
+            start_speed: 60.0,
+            speed_step: 15.0,
+            points_per_level: 10,
+            spawn_interval: 1.2,
+            spawn_interval_step: 0.1,
+            min_spawn_interval: 0.5,
+            max_objects: 12,
+        },
+    }
 }
 
+pub(crate) type FallingKey = Entity<char>;
+
 mod falling_keys_widget {
     use super::*;
 
@@ -57,7 +119,7 @@ mod falling_keys_widget {
             let text_color = self.text_color.borrow();
 
             for key in self.falling_keys.borrow().borrow().iter() {
-                layout.set_text(&key.key.to_string());
+                layout.set_text(&key.value.to_string());
                 snapshot.save();
                 snapshot.translate(&graphene::Point::new(key.x as f32, key.y as f32));
                 snapshot.append_layout(&layout, &text_color);
@@ -99,6 +161,8 @@ mod imp {
         #[template_child]
         pub difficulty_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub status_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub results_box: TemplateChild<gtk::Box>,
         #[template_child]
         pub results_score_label: TemplateChild<gtk::Label>,
@@ -106,15 +170,23 @@ mod imp {
         pub results_level_label: TemplateChild<gtk::Label>,
         #[template_child]
         pub restart_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub setup_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub learned_keys_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub new_record_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub pause_overlay: TemplateChild<PauseOverlay>,
+        #[template_child]
+        pub game_setup: TemplateChild<GameSetup>,
 
         pub falling_keys_widget: RefCell<Option<FallingKeysWidget>>,
         pub keyboard_widget: RefCell<Option<crate::keyboard_widget::KeyboardWidget>>,
         pub(crate) falling_keys: Rc<RefCell<Vec<FallingKey>>>,
-        pub score: RefCell<u32>,
-        pub difficulty: RefCell<u32>,
-        pub speed: RefCell<f64>,
-        pub game_over: RefCell<bool>,
-        pub game_loop_running: RefCell<bool>,
+        pub state: GameState,
+        // Loaded when a game starts, since the course may have moved on since the last one
+        pub keys: RefCell<Vec<char>>,
     }
 
     #[glib::object_subclass]
@@ -124,6 +196,8 @@ mod imp {
         type ParentType = gtk::Box;
 
         fn class_init(klass: &mut Self::Class) {
+            PauseOverlay::ensure_type();
+            GameSetup::ensure_type();
             klass.bind_template();
         }
 
@@ -183,8 +257,9 @@ impl FallingKeysGame {
         let obj = self.downgrade();
         key_controller.connect_key_pressed(move |_, key, _, _| {
             if let Some(obj) = obj.upgrade() {
+                // Shifted and accented characters are matched as typed
                 if let Some(c) = key.to_unicode() {
-                    obj.handle_key_press(c.to_ascii_lowercase());
+                    obj.handle_key_press(c);
                 }
             }
             glib::Propagation::Stop
@@ -197,95 +272,55 @@ impl FallingKeysGame {
         let obj = self.downgrade();
         imp.restart_button.connect_clicked(move |_| {
             if let Some(obj) = obj.upgrade() {
-                obj.restart_game();
+                obj.start_game();
             }
         });
 
-        // Start game loop
-        self.start_game_loop();
-    }
-
-    fn start_game_loop(&self) {
-        let imp = self.imp();
-
-        // Don't start if already running
-        if *imp.game_loop_running.borrow() {
-            return;
-        }
-        *imp.game_loop_running.borrow_mut() = true;
+        let settings = gio::Settings::new("io.github.nacho.mecalin");
+        settings
+            .bind(
+                "falling-keys-learned-only",
+                &*imp.learned_keys_check,
+                "active",
+            )
+            .build();
 
+        // A different set of keys is a different game
         let obj = self.downgrade();
-        glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
+        imp.learned_keys_check.connect_toggled(move |_| {
             if let Some(obj) = obj.upgrade() {
-                if *obj.imp().game_over.borrow() {
-                    *obj.imp().game_loop_running.borrow_mut() = false;
-                    return glib::ControlFlow::Break;
-                }
-                obj.update_game();
-                glib::ControlFlow::Continue
-            } else {
-                glib::ControlFlow::Break
+                obj.reset();
             }
         });
 
-        // Spawn new keys
         let obj = self.downgrade();
-        glib::timeout_add_local(std::time::Duration::from_millis(1500), move || {
+        imp.setup_button.connect_clicked(move |_| {
             if let Some(obj) = obj.upgrade() {
-                if *obj.imp().game_over.borrow() {
-                    return glib::ControlFlow::Break;
-                }
-                obj.spawn_key();
-                glib::ControlFlow::Continue
-            } else {
-                glib::ControlFlow::Break
+                obj.reset();
             }
         });
-    }
-
-    fn spawn_key(&self) {
-        let imp = self.imp();
-        let mut rng = rand::thread_rng();
-
-        if let Some(drawing_area) = imp.falling_keys_widget.borrow().as_ref() {
-            let width = drawing_area.width() as f64;
-            if width > 100.0 {
-                let key = KEYS[rng.gen_range(0..KEYS.len())];
-
-                imp.falling_keys.borrow_mut().push(FallingKey {
-                    key,
-                    x: rng.gen_range(50.0..width - 50.0),
-                    y: 0.0,
-                });
-            }
-        }
-    }
-
-    fn update_game(&self) {
-        let imp = self.imp();
-        let speed = *imp.speed.borrow();
-
-        if let Some(drawing_area) = imp.falling_keys_widget.borrow().as_ref() {
-            let height = drawing_area.height() as f64;
-            let mut keys = imp.falling_keys.borrow_mut();
 
-            // Update positions
-            for key in keys.iter_mut() {
-                key.y += speed;
+        let obj = self.downgrade();
+        imp.game_setup.connect_start(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.start_game();
             }
+        });
 
-            // Check for game over - key reached bottom of view
-            if keys.iter().any(|k| k.y > height) {
-                *imp.game_over.borrow_mut() = true;
-                self.show_game_over();
+        let obj = self.downgrade();
+        imp.pause_overlay.connect_resume(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.resume();
             }
-
-            drawing_area.queue_draw();
-        }
+        });
+        pause_overlay::add_escape_controller(self);
     }
 
     fn handle_key_press(&self, key: char) {
         let imp = self.imp();
+        if *imp.state.paused.borrow() {
+            return;
+        }
 
         // Highlight key on keyboard
         if let Some(keyboard) = imp.keyboard_widget.borrow().as_ref() {
@@ -299,105 +334,151 @@ impl FallingKeysGame {
 
         let mut keys = imp.falling_keys.borrow_mut();
 
-        if let Some(pos) = keys.iter().position(|k| k.key == key) {
-            keys.remove(pos);
-
-            let mut score = imp.score.borrow_mut();
-            *score += 1;
-            let score_text = i18n_fmt! { i18n_fmt("Score: {}", *score) };
-            imp.score_label.set_text(&score_text);
+        let position = keys.iter().position(|k| k.value == key);
+        imp.state.stats.borrow_mut().key_pressed(position.is_some());
 
-            // Increase difficulty every 10 points
-            if (*score).is_multiple_of(10) {
-                let mut difficulty = imp.difficulty.borrow_mut();
-                *difficulty += 1;
-                let level_text = i18n_fmt! { i18n_fmt("Level: {}", *difficulty) };
-                imp.difficulty_label.set_text(&level_text);
+        if let Some(pos) = position {
+            keys.remove(pos);
 
-                let mut speed = imp.speed.borrow_mut();
-                *speed += 0.5;
+            // Keys fall faster and more often with every level
+            let mut progress = imp.state.progress.borrow_mut();
+            if progress.add_point() {
+                imp.state
+                    .spawner
+                    .borrow_mut()
+                    .set_interval(progress.spawn_interval());
+                sounds::play(Sound::LevelUp);
+            } else {
+                sounds::play(Sound::Destroyed);
             }
+            drop(progress);
+            self.update_labels();
 
             if let Some(drawing_area) = imp.falling_keys_widget.borrow().as_ref() {
                 drawing_area.queue_draw();
             }
         } else {
             // Wrong key pressed - decrease score
-            let mut score = imp.score.borrow_mut();
-            if *score > 0 {
-                *score -= 1;
-                let score_text = i18n_fmt! { i18n_fmt("Score: {}", *score) };
-                imp.score_label.set_text(&score_text);
-            }
+            imp.state.progress.borrow_mut().lose_point();
+            sounds::play(Sound::Error);
+            self.update_labels();
         }
     }
+}
 
-    fn show_game_over(&self) {
-        let imp = self.imp();
-        *imp.game_over.borrow_mut() = true;
+impl ArcadeGame for FallingKeysGame {
+    const GAME: Game = Game::FallingKeys;
 
-        if let Some(child) = imp.game_area.child() {
-            child.set_visible(false);
-        }
-        if let Some(keyboard) = imp.keyboard_widget.borrow().as_ref() {
-            keyboard.set_visible(false);
+    fn state(&self) -> &GameState {
+        &self.imp().state
+    }
+
+    fn widgets(&self) -> GameWidgets {
+        let imp = self.imp();
+        GameWidgets {
+            game_setup: imp.game_setup.get(),
+            pause_overlay: imp.pause_overlay.get(),
+            labels: vec![imp.score_label.get(), imp.difficulty_label.get()],
+            status_label: imp.status_label.get(),
+            results_box: imp.results_box.get(),
+            results_score_label: imp.results_score_label.get(),
+            results_level_label: imp.results_level_label.get(),
+            new_record_label: imp.new_record_label.get(),
         }
+    }
 
-        imp.results_score_label
-            .set_text(&imp.score.borrow().to_string());
-        imp.results_level_label
-            .set_text(&imp.difficulty.borrow().to_string());
-        imp.results_box.set_visible(true);
+    fn curve(difficulty: Difficulty) -> DifficultyCurve {
+        difficulty_curve(difficulty)
     }
 
-    fn restart_game(&self) {
+    fn prepare_game(&self) {
         let imp = self.imp();
+        *imp.keys.borrow_mut() = game_keys(imp.learned_keys_check.is_active());
+        if let Some(drawing_area) = imp.falling_keys_widget.borrow().as_ref() {
+            drawing_area.queue_draw();
+        }
+    }
 
-        imp.results_box.set_visible(false);
+    fn clear(&self) {
+        self.imp().falling_keys.borrow_mut().clear();
+    }
+
+    fn object_count(&self) -> usize {
+        self.imp().falling_keys.borrow().len()
+    }
+
+    fn spawn(&self) {
+        let imp = self.imp();
+        let mut rng = rand::thread_rng();
 
-        if let Some(child) = imp.game_area.child() {
-            child.set_visible(true);
-        }
         if let Some(drawing_area) = imp.falling_keys_widget.borrow().as_ref() {
-            drawing_area.set_visible(true);
-            drawing_area.grab_focus();
-        }
-        if let Some(keyboard) = imp.keyboard_widget.borrow().as_ref() {
-            keyboard.set_visible(true);
+            let width = drawing_area.width() as f64;
+            if width > 100.0 {
+                let Some(key) = imp.keys.borrow().choose(&mut rng).copied() else {
+                    return;
+                };
+
+                imp.falling_keys.borrow_mut().push(FallingKey::new(
+                    key,
+                    rng.gen_range(50.0..width - 50.0),
+                    0.0,
+                ));
+            }
         }
+    }
+
+    // Keys that reach the bottom of the view are missed
+    fn advance(&self, dt: f64) -> usize {
+        let imp = self.imp();
+        let speed = imp.state.progress.borrow().speed();
+        let Some(drawing_area) = imp.falling_keys_widget.borrow().clone() else {
+            return 0;
+        };
 
-        self.reset();
+        let height = drawing_area.height() as f64;
+        let mut keys = imp.falling_keys.borrow_mut();
+        for key in keys.iter_mut() {
+            key.advance(0.0, speed, dt);
+        }
+        let count = keys.len();
+        keys.retain(|k| k.y <= height);
+        drawing_area.queue_draw();
+        count - keys.len()
     }
 
-    pub fn reset(&self) {
+    fn set_game_visible(&self, visible: bool) {
         let imp = self.imp();
-        imp.falling_keys.borrow_mut().clear();
-        *imp.score.borrow_mut() = 0;
-        *imp.difficulty.borrow_mut() = 1;
-        *imp.speed.borrow_mut() = 2.0;
-        *imp.game_over.borrow_mut() = false;
-
-        // Hide game over overlay
-        imp.results_box.set_visible(false);
-
-        // Show game area and keyboard
-        if let Some(child) = imp.game_area.child() {
-            child.set_visible(true);
+        if let Some(drawing_area) = imp.falling_keys_widget.borrow().as_ref() {
+            drawing_area.set_visible(visible);
+            if visible {
+                drawing_area.grab_focus();
+            }
         }
         if let Some(keyboard) = imp.keyboard_widget.borrow().as_ref() {
-            keyboard.set_visible(true);
+            keyboard.set_visible(visible);
         }
+    }
 
+    fn update_labels(&self) {
+        let imp = self.imp();
+        let progress = imp.state.progress.borrow();
         imp.score_label
-            .set_text(&i18n_fmt! { i18n_fmt("Score: {}", 0) });
+            .set_text(&i18n_fmt! { i18n_fmt("Score: {}", progress.score) });
         imp.difficulty_label
-            .set_text(&i18n_fmt! { i18n_fmt("Level: {}", 1) });
+            .set_text(&i18n_fmt! { i18n_fmt("Level: {}", progress.level) });
 
-        if let Some(drawing_area) = imp.falling_keys_widget.borrow().as_ref() {
-            drawing_area.grab_focus();
-            drawing_area.queue_draw();
-        }
+        let status = progress.status_text();
+        imp.status_label.set_visible(status.is_some());
+        imp.status_label
+            .set_text(status.as_deref().unwrap_or_default());
+    }
 
-        self.start_game_loop();
+    // A different set of keys is a different game
+    fn score_variant(&self) -> Option<&'static str> {
+        if self.imp().learned_keys_check.is_active() {
+            Some("learned-keys")
+        } else {
+            Some("all-keys")
+        }
     }
 }
@@ -0,0 +1,606 @@
+use gettextrs::gettext;
+use gtk::glib;
+use gtk::prelude::*;
+use i18n_format::i18n_fmt;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use strum_macros::{Display as EnumDisplay, EnumIter, EnumString};
+
+use crate::game_setup::GameSetup;
+use crate::high_scores::{self, Game, GameStats};
+use crate::pause::Pausable;
+use crate::pause_overlay::PauseOverlay;
+use crate::sounds::{self, Sound};
+
+// Longest step a single frame may advance the game, so objects don't jump ahead
+// after the window was hidden or the system stalled
+const MAX_FRAME_SECONDS: f64 = 0.1;
+
+// Length of a sprint, in seconds
+pub const SPRINT_SECONDS: f64 = 60.0;
+// Misses allowed in a game with lives
+pub const LIVES: u32 = 3;
+
+// How a game ends
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumString, EnumDisplay, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum GameMode {
+    // Until the first miss
+    #[default]
+    Endless,
+    // As many points as possible in a minute
+    Sprint,
+    // Until all lives are lost
+    Lives,
+    // Never, and misses don't count
+    Zen,
+}
+
+impl GameMode {
+    pub fn ui_string(&self) -> String {
+        match self {
+            GameMode::Endless => gettext("Endless"),
+            GameMode::Sprint => gettext("Sprint"),
+            GameMode::Lives => gettext("Lives"),
+            GameMode::Zen => gettext("Zen"),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            GameMode::Endless => gettext("Play until the first miss"),
+            GameMode::Sprint => gettext("Score as much as you can in one minute"),
+            GameMode::Lives => gettext("Play until you miss three times"),
+            GameMode::Zen => gettext("Practice without pressure, misses don't count"),
+        }
+    }
+
+    // Zen games never end, so they don't make it to the high scores
+    pub fn keeps_score(&self) -> bool {
+        *self != GameMode::Zen
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumString, EnumDisplay, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn ui_string(&self) -> String {
+        match self {
+            Difficulty::Easy => gettext("Easy"),
+            Difficulty::Normal => gettext("Normal"),
+            Difficulty::Hard => gettext("Hard"),
+        }
+    }
+}
+
+// Something moving across the play area, in pixels
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entity<T> {
+    pub value: T,
+    pub x: f64,
+    pub y: f64,
+}
+
+impl<T> Entity<T> {
+    pub fn new(value: T, x: f64, y: f64) -> Self {
+        Self { value, x, y }
+    }
+
+    // Moves by a velocity in pixels per second over `dt` seconds
+    pub fn advance(&mut self, dx: f64, dy: f64, dt: f64) {
+        self.x += dx * dt;
+        self.y += dy * dt;
+    }
+}
+
+// Tells when to add a new object, every `interval` seconds of play
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Spawner {
+    interval: f64,
+    until_next: f64,
+}
+
+impl Spawner {
+    pub fn new(interval: f64) -> Self {
+        Self {
+            interval,
+            until_next: interval,
+        }
+    }
+
+    // A shorter interval applies to the next object already
+    pub fn set_interval(&mut self, interval: f64) {
+        self.until_next = self.until_next.min(interval);
+        self.interval = interval;
+    }
+
+    // Returns how many objects are due after `dt` more seconds
+    pub fn tick(&mut self, dt: f64) -> u32 {
+        // A spawner without an interval never spawns
+        if self.interval <= 0.0 {
+            return 0;
+        }
+
+        self.until_next -= dt;
+        let mut due = 0;
+        while self.until_next <= 0.0 {
+            self.until_next += self.interval;
+            due += 1;
+        }
+        due
+    }
+}
+
+// How the game speeds up as the score grows
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DifficultyCurve {
+    // Pixels per second at the first level
+    pub start_speed: f64,
+    // Pixels per second added with every level
+    pub speed_step: f64,
+    pub points_per_level: u32,
+    // Seconds between new objects at the first level
+    pub spawn_interval: f64,
+    // Seconds taken off the spawn interval with every level
+    pub spawn_interval_step: f64,
+    pub min_spawn_interval: f64,
+    // Most objects on screen at once
+    pub max_objects: usize,
+}
+
+impl DifficultyCurve {
+    pub fn level(&self, score: u32) -> u32 {
+        score / self.points_per_level.max(1) + 1
+    }
+
+    pub fn speed(&self, level: u32) -> f64 {
+        self.start_speed + self.speed_step * level.saturating_sub(1) as f64
+    }
+
+    pub fn spawn_interval(&self, level: u32) -> f64 {
+        let interval =
+            self.spawn_interval - self.spawn_interval_step * level.saturating_sub(1) as f64;
+        interval.max(self.min_spawn_interval)
+    }
+}
+
+// Score, level, lives and time of a game, where the level never goes down even if
+// points are lost
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GameProgress {
+    pub mode: GameMode,
+    pub curve: DifficultyCurve,
+    pub score: u32,
+    pub level: u32,
+    pub lives: u32,
+    // Seconds left in a sprint
+    pub time_left: f64,
+}
+
+impl GameProgress {
+    pub fn new(mode: GameMode, curve: DifficultyCurve) -> Self {
+        Self {
+            mode,
+            curve,
+            score: 0,
+            level: 1,
+            lives: LIVES,
+            time_left: SPRINT_SECONDS,
+        }
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.curve.speed(self.level)
+    }
+
+    pub fn spawn_interval(&self) -> f64 {
+        self.curve.spawn_interval(self.level)
+    }
+
+    pub fn can_spawn(&self, objects: usize) -> bool {
+        objects < self.curve.max_objects
+    }
+
+    // Counts down the time of a sprint, and returns whether it ran out
+    pub fn tick(&mut self, dt: f64) -> bool {
+        if self.mode != GameMode::Sprint {
+            return false;
+        }
+        self.time_left = (self.time_left - dt).max(0.0);
+        self.time_left == 0.0
+    }
+
+    // An object got away, returns whether that ends the game
+    pub fn miss(&mut self) -> bool {
+        match self.mode {
+            GameMode::Endless => true,
+            GameMode::Lives => {
+                self.lives = self.lives.saturating_sub(1);
+                self.lives == 0
+            }
+            GameMode::Sprint => {
+                self.lose_point();
+                false
+            }
+            GameMode::Zen => false,
+        }
+    }
+
+    // Returns whether a new level was reached
+    pub fn add_point(&mut self) -> bool {
+        self.score += 1;
+        let level = self.curve.level(self.score);
+        if level > self.level {
+            self.level = level;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn lose_point(&mut self) {
+        self.score = self.score.saturating_sub(1);
+    }
+
+    // Lives or time left, for the modes that have them
+    pub fn status_text(&self) -> Option<String> {
+        match self.mode {
+            GameMode::Lives => Some(i18n_fmt! { i18n_fmt("Lives: {}", self.lives) }),
+            GameMode::Sprint => {
+                let seconds = self.time_left.ceil() as u32;
+                let time = format!("{}:{:02}", seconds / 60, seconds % 60);
+                Some(i18n_fmt! { i18n_fmt("Time: {}", time) })
+            }
+            GameMode::Endless | GameMode::Zen => None,
+        }
+    }
+}
+
+// Runs a game once per frame of the widget's frame clock, with the seconds since the last
+// frame, until the update returns `ControlFlow::Break`
+#[derive(Default)]
+pub struct GameLoop {
+    tick_id: Rc<RefCell<Option<gtk::TickCallbackId>>>,
+}
+
+impl GameLoop {
+    pub fn is_running(&self) -> bool {
+        self.tick_id.borrow().is_some()
+    }
+
+    pub fn start<W, F>(&self, widget: &W, update: F)
+    where
+        W: IsA<gtk::Widget>,
+        F: Fn(f64) -> glib::ControlFlow + 'static,
+    {
+        if self.is_running() {
+            return;
+        }
+
+        let last_frame_time = Cell::new(None::<i64>);
+        let tick_id = self.tick_id.clone();
+        let id = widget.add_tick_callback(move |_, frame_clock| {
+            // Frame times are in microseconds
+            let frame_time = frame_clock.frame_time();
+            let dt = last_frame_time
+                .replace(Some(frame_time))
+                .map_or(0.0, |last| (frame_time - last) as f64 / 1_000_000.0)
+                .clamp(0.0, MAX_FRAME_SECONDS);
+
+            let control_flow = update(dt);
+            if control_flow.is_break() {
+                tick_id.borrow_mut().take();
+            }
+            control_flow
+        });
+        self.tick_id.replace(Some(id));
+    }
+}
+
+// What every arcade game keeps track of while it runs
+#[derive(Default)]
+pub struct GameState {
+    pub progress: RefCell<GameProgress>,
+    pub spawner: RefCell<Spawner>,
+    pub game_over: RefCell<bool>,
+    pub paused: RefCell<bool>,
+    pub game_loop: GameLoop,
+    pub stats: RefCell<GameStats>,
+}
+
+// The parts of an arcade game's view that are shown and hidden along with it
+pub struct GameWidgets {
+    pub game_setup: GameSetup,
+    pub pause_overlay: PauseOverlay,
+    // Score and level, shown while playing
+    pub labels: Vec<gtk::Label>,
+    pub status_label: gtk::Label,
+    pub results_box: gtk::Box,
+    pub results_score_label: gtk::Label,
+    pub results_level_label: gtk::Label,
+    pub new_record_label: gtk::Label,
+}
+
+// Games where objects spawn and move until they're typed away or missed. The game only
+// provides what's on its board, the engine runs the loop, pauses and goes back to setup.
+pub trait ArcadeGame: IsA<gtk::Widget> {
+    const GAME: Game;
+
+    fn state(&self) -> &GameState;
+    fn widgets(&self) -> GameWidgets;
+    fn curve(difficulty: Difficulty) -> DifficultyCurve;
+
+    // Sets up the board for a new game, once the progress has been reset
+    fn prepare_game(&self);
+    // Removes everything from the board
+    fn clear(&self);
+    // Objects on the board, which the difficulty caps
+    fn object_count(&self) -> usize;
+    fn spawn(&self);
+    // Moves the objects by `dt` seconds, returning how many got away
+    fn advance(&self, dt: f64) -> usize;
+    fn set_game_visible(&self, visible: bool);
+    fn update_labels(&self);
+
+    // Scores are only comparable with the same mode, difficulty and variant
+    fn score_variant(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn level(&self) -> u32 {
+        self.state().progress.borrow().level
+    }
+
+    fn start_game(&self) {
+        let state = self.state();
+        let widgets = self.widgets();
+        let curve = Self::curve(widgets.game_setup.difficulty());
+
+        *state.progress.borrow_mut() = GameProgress::new(widgets.game_setup.mode(), curve);
+        *state.spawner.borrow_mut() = Spawner::new(curve.spawn_interval);
+        *state.game_over.borrow_mut() = false;
+        *state.paused.borrow_mut() = false;
+        *state.stats.borrow_mut() = GameStats::default();
+        self.clear();
+        self.prepare_game();
+
+        widgets.game_setup.set_visible(false);
+        widgets.pause_overlay.set_visible(false);
+        widgets.results_box.set_visible(false);
+        for label in &widgets.labels {
+            label.set_visible(true);
+        }
+        self.update_labels();
+        self.set_game_visible(true);
+        self.start_game_loop();
+    }
+
+    fn start_game_loop(&self) {
+        let obj = self.downgrade();
+        self.state()
+            .game_loop
+            .start(self, move |dt| match obj.upgrade() {
+                Some(obj) => obj.update_game(dt),
+                None => glib::ControlFlow::Break,
+            });
+    }
+
+    // Advances the game by `dt` seconds
+    fn update_game(&self, dt: f64) -> glib::ControlFlow {
+        let state = self.state();
+        if *state.game_over.borrow() {
+            return glib::ControlFlow::Break;
+        }
+        if *state.paused.borrow() {
+            return glib::ControlFlow::Continue;
+        }
+
+        // A sprint ends when the time is up
+        if state.progress.borrow_mut().tick(dt) {
+            self.show_game_over();
+            return glib::ControlFlow::Break;
+        }
+
+        let spawns = state.spawner.borrow_mut().tick(dt);
+        for _ in 0..spawns {
+            if state.progress.borrow().can_spawn(self.object_count()) {
+                self.spawn();
+            }
+        }
+
+        let missed = self.advance(dt);
+        let mut game_over = false;
+        for _ in 0..missed {
+            game_over |= state.progress.borrow_mut().miss();
+        }
+        self.update_labels();
+        if game_over {
+            self.show_game_over();
+            return glib::ControlFlow::Break;
+        }
+
+        glib::ControlFlow::Continue
+    }
+
+    fn show_game_over(&self) {
+        let state = self.state();
+        let widgets = self.widgets();
+        *state.game_over.borrow_mut() = true;
+        sounds::play(Sound::GameOver);
+        self.set_game_visible(false);
+
+        let progress = *state.progress.borrow();
+        let level = self.level();
+        widgets
+            .results_score_label
+            .set_text(&progress.score.to_string());
+        widgets.results_level_label.set_text(&level.to_string());
+
+        let category = high_scores::category(
+            progress.mode,
+            widgets.game_setup.difficulty(),
+            self.score_variant(),
+        );
+        let is_record = progress.mode.keeps_score()
+            && high_scores::record(
+                Self::GAME,
+                &category,
+                progress.score,
+                level,
+                &state.stats.borrow(),
+            );
+        widgets.new_record_label.set_visible(is_record);
+        widgets.results_box.set_visible(true);
+    }
+
+    // Goes back to the setup screen
+    fn reset(&self) {
+        let state = self.state();
+        let widgets = self.widgets();
+        self.clear();
+        *state.game_over.borrow_mut() = true;
+        *state.paused.borrow_mut() = false;
+        widgets.pause_overlay.set_visible(false);
+        widgets.results_box.set_visible(false);
+        self.set_game_visible(false);
+
+        for label in &widgets.labels {
+            label.set_visible(false);
+        }
+        widgets.status_label.set_visible(false);
+        widgets.game_setup.show();
+    }
+}
+
+// The board is hidden while paused, so it can't be read ahead
+impl<T: ArcadeGame> Pausable for T {
+    fn pause(&self) {
+        let state = self.state();
+        if *state.game_over.borrow() || *state.paused.borrow() {
+            return;
+        }
+
+        *state.paused.borrow_mut() = true;
+        state.stats.borrow_mut().pause();
+        self.set_game_visible(false);
+        self.widgets().pause_overlay.show();
+    }
+
+    fn resume(&self) {
+        let state = self.state();
+        if !*state.paused.borrow() {
+            return;
+        }
+
+        *state.paused.borrow_mut() = false;
+        state.stats.borrow_mut().resume();
+        self.widgets().pause_overlay.set_visible(false);
+        self.set_game_visible(true);
+    }
+
+    fn is_paused(&self) -> bool {
+        *self.state().paused.borrow()
+    }
+
+    fn can_pause(&self) -> bool {
+        !*self.state().game_over.borrow()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVE: DifficultyCurve = DifficultyCurve {
+        start_speed: 40.0,
+        speed_step: 10.0,
+        points_per_level: 10,
+        spawn_interval: 1.5,
+        spawn_interval_step: 0.25,
+        min_spawn_interval: 0.8,
+        max_objects: 3,
+    };
+
+    #[test]
+    fn test_entity_advance() {
+        let mut entity = Entity::new('a', 10.0, 0.0);
+        entity.advance(0.0, 40.0, 0.5);
+        entity.advance(-20.0, 40.0, 0.25);
+        assert_eq!(entity, Entity::new('a', 5.0, 30.0));
+    }
+
+    #[test]
+    fn test_spawner() {
+        let mut spawner = Spawner::new(1.5);
+        assert_eq!(spawner.tick(1.0), 0);
+        assert_eq!(spawner.tick(0.5), 1);
+        assert_eq!(spawner.tick(3.2), 2);
+        assert_eq!(Spawner::default().tick(10.0), 0);
+
+        spawner.set_interval(0.1);
+        assert_eq!(spawner.tick(0.1), 1);
+    }
+
+    #[test]
+    fn test_game_progress() {
+        let mut progress = GameProgress::new(GameMode::Endless, CURVE);
+        assert_eq!(progress.speed(), 40.0);
+        assert_eq!(progress.spawn_interval(), 1.5);
+        assert!(progress.can_spawn(2));
+        assert!(!progress.can_spawn(3));
+
+        for _ in 0..9 {
+            assert!(!progress.add_point());
+        }
+        assert!(progress.add_point());
+        assert_eq!((progress.score, progress.level), (10, 2));
+        assert_eq!(progress.speed(), 50.0);
+        assert_eq!(progress.spawn_interval(), 1.25);
+        assert_eq!(CURVE.spawn_interval(10), 0.8);
+
+        // Losing points doesn't take the level back, nor does reaching it again
+        progress.lose_point();
+        assert!(!progress.add_point());
+        assert_eq!((progress.score, progress.level), (10, 2));
+
+        let mut empty = GameProgress::new(GameMode::Endless, CURVE);
+        empty.lose_point();
+        assert_eq!(empty.score, 0);
+    }
+
+    #[test]
+    fn test_game_modes() {
+        let mut endless = GameProgress::new(GameMode::Endless, CURVE);
+        assert!(!endless.tick(SPRINT_SECONDS));
+        assert!(endless.miss());
+
+        let mut lives = GameProgress::new(GameMode::Lives, CURVE);
+        for _ in 1..LIVES {
+            assert!(!lives.miss());
+        }
+        assert!(lives.miss());
+        assert_eq!(lives.lives, 0);
+
+        let mut sprint = GameProgress::new(GameMode::Sprint, CURVE);
+        sprint.add_point();
+        assert!(!sprint.miss());
+        assert_eq!(sprint.score, 0);
+        assert!(!sprint.tick(SPRINT_SECONDS - 1.0));
+        assert_eq!(sprint.time_left, 1.0);
+        assert!(sprint.tick(2.0));
+
+        let mut zen = GameProgress::new(GameMode::Zen, CURVE);
+        for _ in 0..10 {
+            assert!(!zen.miss());
+        }
+        assert!(!zen.tick(SPRINT_SECONDS * 2.0));
+        assert!(!GameMode::Zen.keeps_score());
+    }
+}
@@ -0,0 +1,213 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::{gio, glib};
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::str::FromStr;
+use strum::{EnumMessage, IntoEnumIterator};
+
+use crate::game_engine::{Difficulty, GameMode};
+use crate::text_generation::{language_word_list, Language, WordList};
+
+// The language chosen for the word games, or the one of the system
+pub fn game_language() -> Language {
+    let settings = gio::Settings::new("io.github.nacho.mecalin");
+    Language::from_str(&settings.string("game-language"))
+        .or_else(|_| Language::from_str(crate::utils::language_from_locale()))
+        .unwrap_or(Language::English)
+}
+
+// The custom word list selected in preferences, or words of the game language,
+// limited to the vocabulary size from preferences
+pub fn game_words() -> WordList {
+    let word_list = crate::custom_word_lists::active_word_list()
+        .unwrap_or_else(|| language_word_list(game_language()).as_ref().clone());
+
+    match crate::custom_word_lists::vocabulary_size() {
+        Some(count) => word_list.top(count),
+        None => word_list,
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/game_setup.ui")]
+    pub struct GameSetup {
+        #[template_child]
+        pub options_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub mode_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub difficulty_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub start_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for GameSetup {
+        const NAME: &'static str = "MecalinGameSetup";
+        type Type = super::GameSetup;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for GameSetup {
+        fn signals() -> &'static [glib::subclass::Signal] {
+            use std::sync::OnceLock;
+            static SIGNALS: OnceLock<Vec<glib::subclass::Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| vec![glib::subclass::Signal::builder("start").build()])
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.setup_rows();
+
+            self.start_button.connect_clicked(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.obj().emit_by_name::<()>("start", &[]);
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for GameSetup {}
+    impl BoxImpl for GameSetup {}
+
+    impl GameSetup {
+        fn setup_rows(&self) {
+            let settings = gio::Settings::new("io.github.nacho.mecalin");
+
+            let mode_names: Vec<String> = GameMode::iter().map(|mode| mode.ui_string()).collect();
+            let mode_strs: Vec<&str> = mode_names.iter().map(String::as_str).collect();
+            self.mode_row
+                .set_model(Some(&gtk::StringList::new(&mode_strs)));
+            let mode = GameMode::from_str(&settings.string("game-mode")).unwrap_or_default();
+            let position = GameMode::iter().position(|m| m == mode).unwrap_or(0);
+            self.mode_row.set_selected(position as u32);
+            self.mode_row.set_subtitle(&mode.description());
+
+            self.mode_row.connect_selected_notify(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    let mode = imp.obj().mode();
+                    imp.mode_row.set_subtitle(&mode.description());
+                    let settings = gio::Settings::new("io.github.nacho.mecalin");
+                    settings.set_string("game-mode", &mode.to_string()).ok();
+                }
+            ));
+
+            let difficulty_names: Vec<String> = Difficulty::iter()
+                .map(|difficulty| difficulty.ui_string())
+                .collect();
+            let difficulty_strs: Vec<&str> = difficulty_names.iter().map(String::as_str).collect();
+            self.difficulty_row
+                .set_model(Some(&gtk::StringList::new(&difficulty_strs)));
+            let difficulty =
+                Difficulty::from_str(&settings.string("game-difficulty")).unwrap_or_default();
+            let position = Difficulty::iter()
+                .position(|d| d == difficulty)
+                .unwrap_or(0);
+            self.difficulty_row.set_selected(position as u32);
+
+            self.difficulty_row.connect_selected_notify(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    let difficulty = imp.obj().difficulty();
+                    let settings = gio::Settings::new("io.github.nacho.mecalin");
+                    settings
+                        .set_string("game-difficulty", &difficulty.to_string())
+                        .ok();
+                }
+            ));
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct GameSetup(ObjectSubclass<imp::GameSetup>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+// Where a game's mode and difficulty are chosen before it starts
+impl GameSetup {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn mode(&self) -> GameMode {
+        GameMode::iter()
+            .nth(self.imp().mode_row.selected() as usize)
+            .unwrap_or_default()
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty::iter()
+            .nth(self.imp().difficulty_row.selected() as usize)
+            .unwrap_or_default()
+    }
+
+    // Options of a single game go below the mode and difficulty
+    pub fn add_row(&self, row: &impl IsA<gtk::Widget>) {
+        self.imp().options_list.append(row);
+    }
+
+    // For the games that type words of a language
+    pub fn add_language_row(&self) {
+        let settings = gio::Settings::new("io.github.nacho.mecalin");
+        let language_names: Vec<&str> = Language::iter()
+            .map(|language| language.get_message().unwrap_or_default())
+            .collect();
+        let language_row = adw::ComboRow::new();
+        language_row.set_title(&gettext("Language"));
+        language_row.set_model(Some(&gtk::StringList::new(&language_names)));
+        let language = game_language();
+        let position = Language::iter().position(|l| l == language).unwrap_or(0);
+        language_row.set_selected(position as u32);
+        // A custom word list takes the place of the language's words
+        language_row.set_sensitive(settings.string("custom-word-list").is_empty());
+        language_row.connect_selected_notify(|row| {
+            if let Some(language) = Language::iter().nth(row.selected() as usize) {
+                let settings = gio::Settings::new("io.github.nacho.mecalin");
+                settings
+                    .set_string("game-language", &language.to_string())
+                    .ok();
+            }
+        });
+        self.add_row(&language_row);
+    }
+
+    pub fn connect_start<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "start",
+            false,
+            glib::closure_local!(move |setup: GameSetup| f(&setup)),
+        )
+    }
+
+    // Shown with the focus on the start button, so Enter starts right away
+    pub fn show(&self) {
+        self.set_visible(true);
+        self.imp().start_button.grab_focus();
+    }
+}
+
+impl Default for GameSetup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -3,14 +3,34 @@ use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{graphene, gsk};
-use std::cell::RefCell;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::cell::{Cell, OnceCell, RefCell};
+
+use crate::keyboard_widget::KeyStroke;
+
+// How far a finger travels to reach the next key or row, in pixels
+const COLUMN_STEP: f32 = 21.0;
+const ROW_STEP: f32 = 16.0;
+// Reaches further than this are drawn at the edge instead
+const MAX_COLUMNS: f64 = 2.0;
+const MAX_ROWS: f64 = 2.0;
+// Room around the hands for the fingers to travel
+const MARGIN_X: f32 = COLUMN_STEP * MAX_COLUMNS as f32;
+const MARGIN_TOP: f32 = ROW_STEP * MAX_ROWS as f32;
+// Shift is on the bottom row, outwards from the pinky
+const SHIFT_REACH: (f64, f64) = (1.0, 1.0);
+const KEY_SIZE: f32 = 17.0;
 
 mod imp {
     use super::*;
 
     #[derive(Default)]
     pub struct HandWidget {
-        pub current_finger: RefCell<Option<String>>,
+        pub key_stroke: RefCell<Option<KeyStroke>>,
+        // From 0 with the fingers on the home row, to 1 with them on their keys
+        pub travel: Cell<f64>,
+        pub travel_animation: OnceCell<adw::TimedAnimation>,
     }
 
     #[glib::object_subclass]
@@ -32,13 +52,18 @@ mod imp {
                 return;
             }
 
-            Self::draw_hand(snapshot, &widget, &self.current_finger);
+            snapshot.save();
+            snapshot.translate(&graphene::Point::new(MARGIN_X, MARGIN_TOP));
+            self.draw_hand(snapshot);
+            snapshot.restore();
         }
 
         fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            let width = 240 + 2 * MARGIN_X as i32;
+            let height = 125 + MARGIN_TOP as i32;
             match orientation {
-                gtk::Orientation::Horizontal => (240, 240, -1, -1),
-                gtk::Orientation::Vertical => (125, 125, -1, -1),
+                gtk::Orientation::Horizontal => (width, width, -1, -1),
+                gtk::Orientation::Vertical => (height, height, -1, -1),
                 _ => (0, 0, -1, -1),
             }
         }
@@ -49,12 +74,65 @@ mod imp {
             format!("finger-{}", finger.replace('_', "-"))
         }
 
-        fn draw_hand(
-            snapshot: &gtk::Snapshot,
-            widget: &super::HandWidget,
-            current_finger: &RefCell<Option<String>>,
-        ) {
-            let current = current_finger.borrow();
+        pub(super) fn travel_animation(&self) -> adw::TimedAnimation {
+            self.travel_animation
+                .get_or_init(|| {
+                    let obj = self.obj();
+                    let target = adw::CallbackAnimationTarget::new(glib::clone!(
+                        #[weak]
+                        obj,
+                        move |travel| {
+                            obj.imp().travel.set(travel);
+                            obj.queue_draw();
+                        }
+                    ));
+
+                    adw::TimedAnimation::builder()
+                        .duration(250)
+                        .widget(&*obj)
+                        .value_from(0.0)
+                        .value_to(1.0)
+                        .easing(adw::Easing::EaseOutCubic)
+                        .target(&target)
+                        .build()
+                })
+                .clone()
+        }
+
+        // How far a finger goes from its place on the home row to reach its key
+        fn finger_reach(&self, finger_name: &str) -> Option<(f32, f32)> {
+            let key_stroke = self.key_stroke.borrow();
+            let key_stroke = key_stroke.as_ref()?;
+
+            let reach = if key_stroke.finger == finger_name {
+                key_stroke.reach
+            } else if key_stroke.modifier_finger.as_deref() == Some(finger_name) {
+                let outwards = if finger_name.starts_with("left") {
+                    -1.0
+                } else {
+                    1.0
+                };
+                (SHIFT_REACH.0 * outwards, SHIFT_REACH.1)
+            } else {
+                return None;
+            };
+
+            Some((
+                reach.0.clamp(-MAX_COLUMNS, MAX_COLUMNS) as f32 * COLUMN_STEP,
+                reach.1.clamp(-MAX_ROWS, MAX_ROWS) as f32 * ROW_STEP,
+            ))
+        }
+
+        fn draw_hand(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let key_stroke = self.key_stroke.borrow();
+            let current = key_stroke
+                .as_ref()
+                .map(|key_stroke| key_stroke.finger.as_str());
+            let modifier_finger = key_stroke
+                .as_ref()
+                .and_then(|key_stroke| key_stroke.modifier_finger.as_deref());
+            let travel = self.travel.get() as f32;
 
             let get_color = |class_name: &str| -> gdk::RGBA {
                 widget.add_css_class(class_name);
@@ -122,12 +200,45 @@ mod imp {
             snapshot.append_border(&right_palm_rounded, &border_width, &border_color);
             snapshot.pop();
 
-            // Draw fingers
+            // Draw the home key of the current finger and the key it goes to, under the
+            // fingers so it travels over them
+            for (finger_name, x, y, w, _) in &fingers {
+                if current != Some(*finger_name) {
+                    continue;
+                }
+                let Some((reach_x, reach_y)) = self.finger_reach(finger_name) else {
+                    continue;
+                };
+                if (reach_x, reach_y) == (0.0, 0.0) {
+                    continue;
+                }
+
+                let key_x = x + (w - KEY_SIZE) / 2.0;
+                let key_y = y - 2.0;
+                Self::draw_key(snapshot, key_x, key_y, &default_border);
+                Self::draw_key(
+                    snapshot,
+                    key_x + reach_x,
+                    key_y + reach_y,
+                    &get_color("hand-finger-current"),
+                );
+            }
+
+            // Draw fingers, on their way to their keys
             for (finger_name, x, y, w, h) in &fingers {
-                let is_current = current.as_ref().is_some_and(|f| f == finger_name);
+                let is_current = current == Some(*finger_name);
+                let is_modifier = modifier_finger == Some(*finger_name);
+                let (offset_x, offset_y) = self
+                    .finger_reach(finger_name)
+                    .map(|(reach_x, reach_y)| (reach_x * travel, reach_y * travel))
+                    .unwrap_or_default();
+
                 let (fill_color, border_color) = if is_current {
                     let c = get_color("hand-finger-current");
                     (c, c)
+                } else if is_modifier {
+                    let c = get_color("hand-finger-modifier");
+                    (c, c)
                 } else if use_finger_colors {
                     let c = get_color(&Self::get_finger_css_class(finger_name));
                     (default_color, c)
@@ -136,24 +247,27 @@ mod imp {
                 };
                 Self::draw_finger(
                     snapshot,
-                    *x,
-                    *y,
+                    x + offset_x,
+                    y + offset_y,
                     *w,
                     *h,
                     &fill_color,
                     &border_color,
-                    is_current,
+                    is_current || is_modifier,
                 );
             }
 
             // Draw thumbs
             for (i, (thumb_name, x, y, w, h)) in thumbs.iter().enumerate() {
-                let is_current = current
-                    .as_ref()
-                    .is_some_and(|f| f == "both_thumbs" || f == thumb_name);
+                let is_current = current.is_some_and(|f| f == "both_thumbs" || f == *thumb_name);
+                // AltGr is held with the right thumb
+                let is_modifier = modifier_finger == Some(*thumb_name);
                 let (fill_color, border_color) = if is_current {
                     let c = get_color("hand-finger-current");
                     (c, c)
+                } else if is_modifier {
+                    let c = get_color("hand-finger-modifier");
+                    (c, c)
                 } else if use_finger_colors {
                     let c = get_color(&Self::get_finger_css_class(thumb_name));
                     (default_color, c)
@@ -169,12 +283,19 @@ mod imp {
                     *h,
                     &fill_color,
                     &border_color,
-                    is_current,
+                    is_current || is_modifier,
                     angle,
                 );
             }
         }
 
+        // The outline of a key, as big as a fingertip
+        fn draw_key(snapshot: &gtk::Snapshot, x: f32, y: f32, color: &gdk::RGBA) {
+            let rect = graphene::Rect::new(x, y, KEY_SIZE, KEY_SIZE);
+            let rounded = gsk::RoundedRect::from_rect(rect, 4.0);
+            snapshot.append_border(&rounded, &[1.5; 4], &[*color; 4]);
+        }
+
         #[allow(clippy::too_many_arguments)]
         fn draw_finger(
             snapshot: &gtk::Snapshot,
@@ -282,9 +403,23 @@ impl HandWidget {
         glib::Object::new()
     }
 
-    pub fn set_current_finger(&self, finger: Option<String>) {
-        *self.imp().current_finger.borrow_mut() = finger;
-        self.queue_draw();
+    /// Shows the fingers moving from the home row to type the next key.
+    pub fn set_key_stroke(&self, key_stroke: Option<KeyStroke>) {
+        let imp = self.imp();
+        if *imp.key_stroke.borrow() == key_stroke {
+            return;
+        }
+
+        let travels = key_stroke.is_some();
+        imp.key_stroke.replace(key_stroke);
+        imp.travel.set(0.0);
+        if travels {
+            let travel_animation = imp.travel_animation();
+            travel_animation.reset();
+            travel_animation.play();
+        } else {
+            self.queue_draw();
+        }
     }
 }
 
@@ -0,0 +1,369 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use gettextrs::gettext;
+use gio::prelude::*;
+
+use crate::game_engine::{Difficulty, GameMode};
+use crate::pause::PauseClock;
+
+// How many scores are kept for each game, difficulty and profile
+const SCORES_PER_PROFILE: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Game {
+    FallingKeys,
+    ScrollingLanes,
+    WordDefense,
+}
+
+impl Game {
+    pub fn ui_string(&self) -> String {
+        match self {
+            Game::FallingKeys => gettext("Falling Keys"),
+            Game::ScrollingLanes => gettext("Scrolling Lanes"),
+            Game::WordDefense => gettext("Word Defense"),
+        }
+    }
+}
+
+// Game settings are stored by name, so new ones don't break existing score files
+pub fn category(mode: GameMode, difficulty: Difficulty, variant: Option<&str>) -> String {
+    let mut category = format!("{mode}/{difficulty}");
+    if let Some(variant) = variant {
+        category.push('/');
+        category.push_str(variant);
+    }
+    category
+}
+
+pub fn difficulty_ui_string(difficulty: &str) -> String {
+    difficulty
+        .split('/')
+        .map(|part| {
+            if let Ok(mode) = GameMode::from_str(part) {
+                return mode.ui_string();
+            }
+            if let Ok(difficulty) = Difficulty::from_str(part) {
+                return difficulty.ui_string();
+            }
+            match part {
+                "learned-keys" => gettext("Learned Keys"),
+                "all-keys" => gettext("All Keys"),
+                "auto-target" => gettext("Auto-Target"),
+                other => other.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub game: Game,
+    pub difficulty: String,
+    pub profile: String,
+    pub score: u32,
+    pub level: u32,
+    pub keys_per_minute: f64,
+    pub accuracy: f64,
+    // Seconds since the Unix epoch
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HighScoreTable {
+    scores: Vec<HighScore>,
+}
+
+impl HighScoreTable {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    // Adds a score and returns whether it beats every earlier score of the same
+    // profile in that game and difficulty
+    pub fn add(&mut self, high_score: HighScore) -> bool {
+        let is_record = self
+            .personal_scores(&high_score)
+            .all(|previous| high_score.score > previous.score);
+
+        self.scores.push(high_score.clone());
+        self.scores.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(b.keys_per_minute.total_cmp(&a.keys_per_minute))
+        });
+
+        // Only the best scores of each profile are kept
+        let mut kept = 0;
+        self.scores.retain(|score| {
+            let same_category = score.game == high_score.game
+                && score.difficulty == high_score.difficulty
+                && score.profile == high_score.profile;
+            if !same_category {
+                return true;
+            }
+            kept += 1;
+            kept <= SCORES_PER_PROFILE
+        });
+
+        is_record
+    }
+
+    // Best scores first
+    pub fn top(&self, game: Game, difficulty: &str) -> Vec<&HighScore> {
+        self.scores
+            .iter()
+            .filter(|score| score.game == game && score.difficulty == difficulty)
+            .collect()
+    }
+
+    // Every game and difficulty with at least one score, in the order first played
+    pub fn categories(&self) -> Vec<(Game, String)> {
+        let mut categories: Vec<(Game, String)> = Vec::new();
+        let mut by_date: Vec<&HighScore> = self.scores.iter().collect();
+        by_date.sort_by_key(|score| score.timestamp);
+
+        for score in by_date {
+            if !categories
+                .iter()
+                .any(|(game, difficulty)| *game == score.game && *difficulty == score.difficulty)
+            {
+                categories.push((score.game, score.difficulty.clone()));
+            }
+        }
+
+        categories
+    }
+
+    fn personal_scores<'a>(
+        &'a self,
+        high_score: &'a HighScore,
+    ) -> impl Iterator<Item = &'a HighScore> + 'a {
+        self.scores.iter().filter(move |score| {
+            score.game == high_score.game
+                && score.difficulty == high_score.difficulty
+                && score.profile == high_score.profile
+        })
+    }
+}
+
+// Keys typed during a game, for the stats that go with its score
+#[derive(Clone, Copy, Debug)]
+pub struct GameStats {
+    started: Instant,
+    pause_clock: PauseClock,
+    correct_keys: u32,
+    total_keys: u32,
+}
+
+impl Default for GameStats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            pause_clock: PauseClock::default(),
+            correct_keys: 0,
+            total_keys: 0,
+        }
+    }
+}
+
+impl GameStats {
+    pub fn key_pressed(&mut self, correct: bool) {
+        self.total_keys += 1;
+        if correct {
+            self.correct_keys += 1;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.pause_clock.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.pause_clock.resume();
+    }
+
+    pub fn keys_per_minute(&self) -> f64 {
+        let minutes = self.pause_clock.active_since(self.started).as_secs_f64() / 60.0;
+        if minutes > 0.0 {
+            self.correct_keys as f64 / minutes
+        } else {
+            0.0
+        }
+    }
+
+    pub fn accuracy(&self) -> f64 {
+        if self.total_keys == 0 {
+            return 0.0;
+        }
+        self.correct_keys as f64 / self.total_keys as f64
+    }
+}
+
+pub fn high_scores_path() -> PathBuf {
+    glib::user_data_dir()
+        .join("mecalin")
+        .join("high_scores.json")
+}
+
+// The player name from preferences, or the name of the user account
+pub fn profile_name() -> String {
+    let settings = gio::Settings::new("io.github.nacho.mecalin");
+    let name = settings.string("player-name");
+    if !name.trim().is_empty() {
+        return name.trim().to_string();
+    }
+
+    let real_name = glib::real_name();
+    match real_name.to_str() {
+        Some(real_name) if !real_name.is_empty() && real_name != "Unknown" => real_name.to_string(),
+        _ => glib::user_name().to_string_lossy().into_owned(),
+    }
+}
+
+// Saves the score of a finished game, and returns whether it's a new personal record
+pub fn record(game: Game, difficulty: &str, score: u32, level: u32, stats: &GameStats) -> bool {
+    let path = high_scores_path();
+    let mut table = match HighScoreTable::load(&path) {
+        Ok(table) => table,
+        Err(e) => {
+            glib::g_warning!("mecalin", "Could not read high scores: {e}");
+            return false;
+        }
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() as i64);
+    let is_record = table.add(HighScore {
+        game,
+        difficulty: difficulty.to_string(),
+        profile: profile_name(),
+        score,
+        level,
+        keys_per_minute: stats.keys_per_minute(),
+        accuracy: stats.accuracy(),
+        timestamp,
+    });
+
+    if let Err(e) = table.save(&path) {
+        glib::g_warning!("mecalin", "Could not save high scores: {e}");
+    }
+
+    // A game without points isn't much of a record
+    is_record && score > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn high_score(profile: &str, score: u32, timestamp: i64) -> HighScore {
+        HighScore {
+            game: Game::FallingKeys,
+            difficulty: "all-keys".to_string(),
+            profile: profile.to_string(),
+            score,
+            level: score / 10 + 1,
+            keys_per_minute: 60.0,
+            accuracy: 0.9,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_personal_records() {
+        let mut table = HighScoreTable::default();
+        assert!(table.add(high_score("Ana", 20, 1)));
+        assert!(!table.add(high_score("Ana", 15, 2)));
+        assert!(!table.add(high_score("Ana", 20, 3)));
+        assert!(table.add(high_score("Ana", 21, 4)));
+
+        // Records are kept separately for every profile and difficulty
+        assert!(table.add(high_score("Bo", 5, 5)));
+        let mut other_difficulty = high_score("Ana", 1, 6);
+        other_difficulty.difficulty = "learned-keys".to_string();
+        assert!(table.add(other_difficulty));
+
+        let scores: Vec<u32> = table
+            .top(Game::FallingKeys, "all-keys")
+            .iter()
+            .map(|score| score.score)
+            .collect();
+        assert_eq!(scores, vec![21, 20, 20, 15, 5]);
+        assert_eq!(
+            table.categories(),
+            vec![
+                (Game::FallingKeys, "all-keys".to_string()),
+                (Game::FallingKeys, "learned-keys".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_only_best_scores_are_kept() {
+        let mut table = HighScoreTable::default();
+        for score in 0..20 {
+            table.add(high_score("Ana", score, score as i64));
+        }
+        table.add(high_score("Bo", 1, 30));
+
+        let top = table.top(Game::FallingKeys, "all-keys");
+        assert_eq!(top.len(), SCORES_PER_PROFILE + 1);
+        assert_eq!(top[0].score, 19);
+        assert!(top.iter().any(|score| score.profile == "Bo"));
+    }
+
+    #[test]
+    fn test_categories() {
+        let category = category(GameMode::Lives, Difficulty::Hard, Some("learned-keys"));
+        assert_eq!(category, "lives/hard/learned-keys");
+        assert_eq!(
+            difficulty_ui_string(&category),
+            "Lives · Hard · Learned Keys"
+        );
+        assert_eq!(
+            super::category(GameMode::Endless, Difficulty::Normal, None),
+            "endless/normal"
+        );
+
+        // Scores from before game modes existed
+        assert_eq!(difficulty_ui_string("all-keys"), "All Keys");
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let path = std::env::temp_dir().join(format!(
+            "mecalin-high-scores-{}/high_scores.json",
+            std::process::id()
+        ));
+        assert!(HighScoreTable::load(&path).unwrap().scores.is_empty());
+
+        let mut table = HighScoreTable::default();
+        table.add(high_score("Ana", 12, 1));
+        table.save(&path).unwrap();
+
+        let loaded = HighScoreTable::load(&path).unwrap();
+        assert_eq!(loaded.scores, table.scores);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
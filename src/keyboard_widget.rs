@@ -6,7 +6,7 @@ use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{graphene, gsk};
 use serde::{Deserialize, Serialize};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::collections::HashSet;
 
@@ -69,6 +69,23 @@ pub struct ModifierKey {
     pub finger: String,
 }
 
+// The row the fingers rest on, and where each row starts in key widths, as they are
+// staggered on the keyboard
+const HOME_ROW: usize = 2;
+const ROW_OFFSETS: [f64; 4] = [0.0, 1.5, 1.75, 1.25];
+
+/// How a character is typed, as shown by the hand widget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyStroke {
+    pub finger: String,
+    /// Where the key is from the home key of the finger, in keys to the right and rows down.
+    pub reach: (f64, f64),
+    /// The modifier held along with the key, by its name in the layout's `modifiers`,
+    /// and the finger holding it.
+    pub modifier_key: Option<String>,
+    pub modifier_finger: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyboardLayout {
     pub name: String,
@@ -76,6 +93,12 @@ pub struct KeyboardLayout {
     pub space: KeyInfo,
     #[serde(default)]
     pub modifiers: HashMap<String, ModifierKey>,
+    // Optional numeric keypad, drawn to the right of the main block
+    #[serde(default)]
+    pub numpad: Vec<Vec<KeyInfo>>,
+    // Characters whose keys are dead keys on this layout, so they don't type anything alone
+    #[serde(default)]
+    pub dead_keys: Vec<char>,
 }
 
 impl KeyboardLayout {
@@ -94,41 +117,110 @@ impl KeyboardLayout {
     }
 
     pub fn get_finger_for_char(&self, ch: char) -> Option<String> {
-        let ch_lower = ch.to_lowercase().next().unwrap();
-
         // Check space key
         if ch == ' ' {
             return Some(self.space.finger.clone());
         }
 
-        // Check all keys in the layout
-        for row in &self.keys {
-            for key_info in row {
+        self.find_key(ch)
+            .map(|(_, _, key_info)| key_info.finger.clone())
+    }
+
+    /// Row and column of the first key typing the character, on its own or with a modifier.
+    fn find_key(&self, ch: char) -> Option<(usize, usize, &KeyInfo)> {
+        let ch_lower = ch.to_lowercase().next().unwrap();
+
+        self.keys
+            .iter()
+            .enumerate()
+            .flat_map(|(row, keys)| {
+                keys.iter()
+                    .enumerate()
+                    .map(move |(column, key_info)| (row, column, key_info))
+            })
+            .find(|(_, _, key_info)| {
                 let key_char = key_info.base.chars().next().unwrap_or(' ');
-                let base_lower = key_char.to_lowercase().next().unwrap();
+                ch_lower == key_char.to_lowercase().next().unwrap()
+                    || key_info
+                        .shift
+                        .as_ref()
+                        .is_some_and(|shift| shift.chars().next().unwrap_or(' ') == ch)
+                    || key_info
+                        .altgr
+                        .as_ref()
+                        .is_some_and(|altgr| altgr.chars().next().unwrap_or(' ') == ch)
+            })
+    }
 
-                // Check base (lowercase comparison)
-                if ch_lower == base_lower {
-                    return Some(key_info.finger.clone());
-                }
+    /// The home row key a finger rests on. Index fingers also cover the keys next to
+    /// theirs towards the middle of the keyboard, and pinkies the ones further out.
+    fn home_column(&self, finger: &str) -> Option<f64> {
+        let columns = self
+            .keys
+            .get(HOME_ROW)?
+            .iter()
+            .enumerate()
+            .filter(|(_, key_info)| key_info.finger == finger)
+            .map(|(column, _)| column);
+        let leftmost = finger.starts_with("left") == finger.ends_with("index");
+        let column = if leftmost {
+            columns.min()
+        } else {
+            columns.max()
+        }?;
 
-                // Check shift (exact match)
-                if let Some(shift) = &key_info.shift {
-                    if shift.chars().next().unwrap_or(' ') == ch {
-                        return Some(key_info.finger.clone());
-                    }
-                }
+        Some(ROW_OFFSETS[HOME_ROW] + column as f64)
+    }
 
-                // Check altgr (exact match)
-                if let Some(altgr) = &key_info.altgr {
-                    if altgr.chars().next().unwrap_or(' ') == ch {
-                        return Some(key_info.finger.clone());
-                    }
-                }
-            }
+    /// How the character is typed, from the finger resting on the home row.
+    pub fn key_stroke(&self, ch: char) -> Option<KeyStroke> {
+        if ch == ' ' {
+            return Some(KeyStroke {
+                finger: self.space.finger.clone(),
+                reach: (0.0, 0.0),
+                modifier_key: None,
+                modifier_finger: None,
+            });
         }
 
-        None
+        let (row, column, key_info) = self.find_key(ch)?;
+        let reach = self
+            .home_column(&key_info.finger)
+            .map(|home_column| {
+                let row_offset = ROW_OFFSETS.get(row).copied().unwrap_or_default();
+                (
+                    row_offset + column as f64 - home_column,
+                    row as f64 - HOME_ROW as f64,
+                )
+            })
+            .unwrap_or_default();
+
+        let modifier_key = Self::modifier_key(key_info, ch);
+        let modifier_finger = modifier_key
+            .and_then(|modifier_key| self.modifiers.get(modifier_key))
+            .map(|modifier| modifier.finger.clone());
+
+        Some(KeyStroke {
+            finger: key_info.finger.clone(),
+            reach,
+            modifier_key: modifier_key.map(str::to_string),
+            modifier_finger,
+        })
+    }
+
+    /// The modifier to hold while pressing the key to type the character, if any.
+    fn modifier_key(key_info: &KeyInfo, ch: char) -> Option<&'static str> {
+        let on_key = |label: &Option<String>| label.as_ref().is_some_and(|l| l.starts_with(ch));
+        if key_info.base.starts_with(ch) {
+            None
+        } else if !on_key(&key_info.shift) && on_key(&key_info.altgr) {
+            Some("alt_right")
+        } else if key_info.finger.starts_with("left") {
+            // Shift is held with the other hand, leaving the finger typing the key free
+            Some("shift_right")
+        } else {
+            Some("shift_left")
+        }
     }
 }
 
@@ -170,6 +262,75 @@ impl KeyboardLayout {
 
         false
     }
+
+    /// Finger for a character typed on the numeric keypad, if the layout has one.
+    pub fn get_numpad_finger_for_char(&self, ch: char) -> Option<String> {
+        self.numpad
+            .iter()
+            .flatten()
+            .find(|key_info| key_info.base.starts_with(ch))
+            .map(|key_info| key_info.finger.clone())
+    }
+
+    /// The character typed by the decimal key of the numeric keypad, if the layout has one.
+    pub fn numpad_decimal_separator(&self) -> Option<&str> {
+        self.numpad
+            .iter()
+            .flatten()
+            .map(|key_info| key_info.base.as_str())
+            .find(|base| matches!(*base, "." | ","))
+    }
+
+    /// Characters typed with a single key press, with or without Shift, in the order of
+    /// the keys. Dead keys are left out, since they don't type anything on their own.
+    pub fn single_key_characters(&self) -> Vec<char> {
+        let mut characters = Vec::new();
+        for key_info in self.keys.iter().flatten() {
+            for output in std::iter::once(&key_info.base).chain(&key_info.shift) {
+                let Some(ch) = output.chars().next() else {
+                    continue;
+                };
+                if ch.is_whitespace() || self.dead_keys.contains(&ch) || characters.contains(&ch) {
+                    continue;
+                }
+                characters.push(ch);
+            }
+        }
+
+        characters
+    }
+
+    /// Check if the character can be typed on this layout, either directly or
+    /// as a dead key followed by its base character (e.g. ´ + e for é).
+    pub fn can_produce_character(&self, ch: char) -> bool {
+        if self.contains_character(ch) {
+            return true;
+        }
+
+        if let glib::CharacterDecomposition::Pair(base_char, mark) = ch.decompose() {
+            let dead_key = spacing_dead_key(mark).unwrap_or(mark);
+            return self.contains_character(base_char) && self.contains_character(dead_key);
+        }
+
+        false
+    }
+}
+
+// Combining marks and the spacing characters that dead keys are labelled with
+const DEAD_KEYS: &[(char, char)] = &[
+    ('\u{0300}', '`'),
+    ('\u{0301}', '´'),
+    ('\u{0302}', '^'),
+    ('\u{0303}', '~'),
+    ('\u{0308}', '¨'),
+    ('\u{0327}', '¸'),
+];
+
+fn spacing_dead_key(mark: char) -> Option<char> {
+    DEAD_KEYS
+        .iter()
+        .find(|(combining, _)| *combining == mark)
+        .map(|(_, spacing)| *spacing)
 }
 
 impl Default for KeyboardLayout {
@@ -207,6 +368,8 @@ impl Default for KeyboardLayout {
                 finger: "both_thumbs".to_string(),
             },
             modifiers: HashMap::new(),
+            numpad: Vec::new(),
+            dead_keys: Vec::new(),
         })
     }
 }
@@ -229,6 +392,16 @@ mod tests {
         assert!(!layout.keys.is_empty());
     }
 
+    #[test]
+    fn test_load_from_json_fr() {
+        let layout = KeyboardLayout::load_from_json("fr").unwrap();
+        assert_eq!(layout.name, "French AZERTY");
+        assert!(!layout.keys.is_empty());
+        // The space bar and modifiers use the same format as the other layouts
+        assert_eq!(layout.space.label.as_deref(), Some("ESPACE"));
+        assert!(layout.modifiers.contains_key("shift_left"));
+    }
+
     #[test]
     fn test_load_from_json_it() {
         let layout = KeyboardLayout::load_from_json("it").unwrap();
@@ -372,6 +545,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_key_stroke_us() {
+        let layout = KeyboardLayout::load_from_json("us").unwrap();
+        let stroke = |ch| layout.key_stroke(ch).unwrap();
+
+        assert_eq!(
+            stroke('f'),
+            KeyStroke {
+                finger: "left_index".to_string(),
+                reach: (0.0, 0.0),
+                modifier_key: None,
+                modifier_finger: None,
+            }
+        );
+        assert_eq!(stroke('g').reach, (1.0, 0.0));
+        assert_eq!(stroke('h').reach, (-1.0, 0.0));
+        assert_eq!(stroke('r').reach, (-0.25, -1.0));
+        assert_eq!(stroke('1').reach, (-0.75, -2.0));
+        assert_eq!(stroke(' ').finger, "both_thumbs");
+
+        // Shift is held by the pinky of the other hand
+        assert_eq!(stroke('J').modifier_key.as_deref(), Some("shift_left"));
+        assert_eq!(stroke('J').modifier_finger.as_deref(), Some("left_pinky"));
+        assert_eq!(stroke('!').modifier_key.as_deref(), Some("shift_right"));
+        assert_eq!(stroke('!').modifier_finger.as_deref(), Some("right_pinky"));
+        assert_eq!(stroke('j').modifier_key, None);
+        assert!(layout.key_stroke('Ñ').is_none());
+    }
+
+    #[test]
+    fn test_key_stroke_modifiers_es() {
+        let layout = KeyboardLayout::load_from_json("es").unwrap();
+        let stroke = |ch| layout.key_stroke(ch).unwrap();
+
+        assert_eq!(stroke('@').modifier_key.as_deref(), Some("alt_right"));
+        assert_eq!(stroke('@').modifier_finger.as_deref(), Some("right_thumb"));
+        assert_eq!(stroke('[').modifier_key.as_deref(), Some("alt_right"));
+        assert_eq!(stroke('"').modifier_key.as_deref(), Some("shift_right"));
+        assert_eq!(stroke('^').modifier_key.as_deref(), Some("shift_left"));
+        assert_eq!(stroke('Ñ').modifier_key.as_deref(), Some("shift_left"));
+        assert_eq!(stroke('ñ').modifier_key, None);
+        assert_eq!(stroke('2').modifier_key, None);
+    }
+
     fn create_test_layout() -> KeyboardLayout {
         KeyboardLayout {
             name: "Test".to_string(),
@@ -399,6 +616,8 @@ mod tests {
                 },
             ]],
             modifiers: std::collections::HashMap::new(),
+            numpad: Vec::new(),
+            dead_keys: Vec::new(),
             space: KeyInfo {
                 base: " ".to_string(),
                 label: None,
@@ -409,6 +628,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_single_key_characters() {
+        let layout = KeyboardLayout::load_from_json("es").unwrap();
+        let characters = layout.single_key_characters();
+
+        assert!(characters.contains(&'ñ') && characters.contains(&'Ñ'));
+        assert!(characters.contains(&'ç') && characters.contains(&'?'));
+        // Accents are dead keys, and AltGr characters need two keys
+        assert!(!characters.contains(&'´') && !characters.contains(&'¨'));
+        assert!(!characters.contains(&'@'));
+
+        // The same characters are typed on their own where they aren't dead keys
+        let us = KeyboardLayout::load_from_json("us")
+            .unwrap()
+            .single_key_characters();
+        assert!(us.contains(&'`') && us.contains(&'^') && us.contains(&'~'));
+    }
+
     #[test]
     fn test_matches_char_base_priority() {
         let layout = create_test_layout();
@@ -462,6 +699,43 @@ mod tests {
 
         assert!(!key.matches_char(' ', &layout));
     }
+
+    #[test]
+    fn test_numpad() {
+        let layout = KeyboardLayout::load_from_json("us").unwrap();
+
+        assert_eq!(
+            layout.get_numpad_finger_for_char('0'),
+            Some("right_thumb".to_string())
+        );
+        assert_eq!(
+            layout.get_numpad_finger_for_char('4'),
+            Some("right_index".to_string())
+        );
+        assert_eq!(layout.get_numpad_finger_for_char('a'), None);
+        assert_eq!(layout.numpad_decimal_separator(), Some("."));
+
+        // Layouts without a numpad still load
+        let layout = create_test_layout();
+        assert_eq!(layout.get_numpad_finger_for_char('0'), None);
+        assert_eq!(layout.numpad_decimal_separator(), None);
+    }
+
+    #[test]
+    fn test_can_produce_character_dead_keys() {
+        let layout = KeyboardLayout::load_from_json("es").unwrap();
+
+        // Not on any key, but reachable through the ´ dead key
+        assert!(!layout.contains_character('á'));
+        assert!(layout.can_produce_character('á'));
+        assert!(layout.can_produce_character('é'));
+
+        // Direct characters are still producible
+        assert!(layout.can_produce_character('ñ'));
+
+        // No dead key for the ogonek on the Spanish layout
+        assert!(!layout.can_produce_character('ą'));
+    }
 }
 
 mod imp {
@@ -470,10 +744,13 @@ mod imp {
     #[derive(Default)]
     pub struct KeyboardWidget {
         pub current_key: RefCell<Option<char>>,
+        // The modifier to hold along with the current key, by its name in `modifiers`
+        pub current_modifier: RefCell<Option<String>>,
         pub visible_keys: RefCell<Option<HashSet<char>>>,
         pub current_key_sequence: RefCell<Vec<char>>,
         pub sequence_index: RefCell<usize>,
         pub layout: RefCell<KeyboardLayout>,
+        pub show_numpad: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -506,13 +783,16 @@ mod imp {
                 snapshot,
                 &widget,
                 &self.current_key,
+                self.current_modifier.borrow().as_deref(),
                 &self.layout,
                 &self.visible_keys,
+                self.show_numpad.get(),
             );
         }
 
         fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
-            let (width, height) = Self::calculate_size(&self.layout.borrow());
+            let (width, height) =
+                Self::calculate_size(&self.layout.borrow(), self.show_numpad.get());
             match orientation {
                 gtk::Orientation::Horizontal => (width, width, -1, -1),
                 gtk::Orientation::Vertical => (height, height, -1, -1),
@@ -522,7 +802,10 @@ mod imp {
     }
 
     impl KeyboardWidget {
-        fn calculate_size(layout: &KeyboardLayout) -> (i32, i32) {
+        // Space between the main block and the numeric keypad
+        const NUMPAD_GAP: f64 = 25.0;
+
+        fn calculate_size(layout: &KeyboardLayout, show_numpad: bool) -> (i32, i32) {
             let key_width = 50.0;
             let key_height = 50.0;
             let key_spacing = 5.0;
@@ -574,11 +857,17 @@ mod imp {
                 + key_spacing
                 + key_width * 1.5;
 
-            let keyboard_width = row0_width
+            let mut keyboard_width = row0_width
                 .max(row1_width)
                 .max(row2_width)
                 .max(row3_width)
-                .max(row4_width) as i32;
+                .max(row4_width);
+
+            if show_numpad && !layout.numpad.is_empty() {
+                keyboard_width += Self::NUMPAD_GAP + 4.0 * key_width + 3.0 * key_spacing;
+            }
+
+            let keyboard_width = keyboard_width as i32;
             let keyboard_height = (5.0 * key_height + 4.0 * row_spacing) as i32;
 
             (keyboard_width, keyboard_height)
@@ -722,8 +1011,10 @@ mod imp {
             snapshot: &gtk::Snapshot,
             widget: &super::KeyboardWidget,
             current_key: &RefCell<Option<char>>,
+            current_modifier: Option<&str>,
             layout: &RefCell<KeyboardLayout>,
             visible_keys: &RefCell<Option<HashSet<char>>>,
+            show_numpad: bool,
         ) {
             let layout_borrowed = layout.borrow();
             let visible_keys_borrowed = visible_keys.borrow();
@@ -763,8 +1054,14 @@ mod imp {
 
             let current = current_key.borrow();
 
+            let show_numpad = show_numpad && !layout_borrowed.numpad.is_empty();
+
+            // Characters on a visible numpad are only highlighted there
             let is_key_current = |key_info: &KeyInfo| -> bool {
-                current.is_some_and(|c| key_info.matches_char(c, &layout_borrowed))
+                current.is_some_and(|c| {
+                    key_info.matches_char(c, &layout_borrowed)
+                        && !(show_numpad && layout_borrowed.get_numpad_finger_for_char(c).is_some())
+                })
             };
 
             let should_show_key = |key_char: char| -> bool {
@@ -953,7 +1250,7 @@ mod imp {
                     key_height,
                     None,
                     Some(&shift_l.label),
-                    false,
+                    current_modifier == Some("shift_left"),
                     true,
                     &modifier_color,
                     &key_current_color,
@@ -998,7 +1295,7 @@ mod imp {
                     key_height,
                     None,
                     Some(&shift_r.label),
-                    false,
+                    current_modifier == Some("shift_right"),
                     true,
                     &modifier_color,
                     &key_current_color,
@@ -1106,7 +1403,7 @@ mod imp {
                     key_height,
                     None,
                     Some(&alt_r.label),
-                    false,
+                    current_modifier == Some("alt_right"),
                     true,
                     &modifier_color,
                     &key_current_color,
@@ -1179,6 +1476,103 @@ mod imp {
                     &key_border_color,
                 );
             }
+
+            if !show_numpad {
+                return;
+            }
+
+            // Numpad: Num Lock + / * -, 7 8 9 + (spans to row 2), 4 5 6,
+            // 1 2 3 + Enter (spans to row 4), 0 (two keys wide) and the decimal key
+            let numpad_x =
+                (Self::calculate_size(&layout_borrowed, false).0 as f64 + Self::NUMPAD_GAP) as f32;
+            let row_ys = [y, y1, y2, y3, y4];
+            let tall_key_height = key_height * 2.0 + row_spacing;
+
+            if let Some(num_lock) = layout_borrowed.modifiers.get("num_lock") {
+                Self::draw_single_key(
+                    snapshot,
+                    &pango_context,
+                    numpad_x,
+                    y,
+                    key_width,
+                    key_height,
+                    None,
+                    Some(&num_lock.label),
+                    false,
+                    true,
+                    &modifier_color,
+                    &key_current_color,
+                    &modifier_text_color,
+                    &key_current_text_color,
+                    &key_border_color,
+                    &key_border_color,
+                );
+            }
+
+            for (row_index, row) in layout_borrowed.numpad.iter().enumerate().take(5) {
+                // The first row starts after Num Lock, the others at the left edge
+                let mut x = if row_index == 0 {
+                    numpad_x + key_width + key_spacing
+                } else {
+                    numpad_x
+                };
+
+                for (column, key_info) in row.iter().enumerate() {
+                    let width = if row_index == 4 && column == 0 {
+                        key_width * 2.0 + key_spacing
+                    } else {
+                        key_width
+                    };
+                    let height = if row_index == 1 && column == 3 {
+                        tall_key_height
+                    } else {
+                        key_height
+                    };
+
+                    let key_char = key_info.base.chars().next().unwrap_or(' ');
+                    let is_current = current.is_some_and(|c| c == key_char);
+                    Self::draw_single_key(
+                        snapshot,
+                        &pango_context,
+                        x,
+                        row_ys[row_index],
+                        width,
+                        height,
+                        Some(key_info),
+                        None,
+                        is_current,
+                        should_show_key(key_char),
+                        &key_color,
+                        &key_current_color,
+                        &key_text_color,
+                        &key_current_text_color,
+                        &key_border_color,
+                        &get_finger_color(&key_info.finger),
+                    );
+                    x += width + key_spacing;
+                }
+            }
+
+            if let Some(enter) = layout_borrowed.modifiers.get("numpad_enter") {
+                Self::draw_single_key(
+                    snapshot,
+                    &pango_context,
+                    numpad_x + 3.0 * (key_width + key_spacing),
+                    y3,
+                    key_width,
+                    tall_key_height,
+                    None,
+                    Some(&enter.label),
+                    false,
+                    true,
+                    &modifier_color,
+                    &key_current_color,
+                    &modifier_text_color,
+                    &key_current_text_color,
+                    &key_border_color,
+                    &key_border_color,
+                );
+            }
         }
     }
 }
@@ -1217,19 +1611,35 @@ impl KeyboardWidget {
             *imp.sequence_index.borrow_mut() = 0;
             *imp.current_key.borrow_mut() = None;
         }
+        self.update_current_modifier();
         self.queue_draw();
     }
 
     pub fn advance_sequence(&self) {
         let imp = self.imp();
-        let sequence = imp.current_key_sequence.borrow();
-        let mut index = imp.sequence_index.borrow_mut();
+        {
+            let sequence = imp.current_key_sequence.borrow();
+            let mut index = imp.sequence_index.borrow_mut();
 
-        if !sequence.is_empty() && *index < sequence.len() - 1 {
+            if sequence.is_empty() || *index >= sequence.len() - 1 {
+                return;
+            }
             *index += 1;
             *imp.current_key.borrow_mut() = Some(sequence[*index]);
-            self.queue_draw();
         }
+        self.update_current_modifier();
+        self.queue_draw();
+    }
+
+    // Uppercase letters and symbols need Shift or AltGr highlighted too, so the
+    // modifier is held with the right hand
+    fn update_current_modifier(&self) {
+        let imp = self.imp();
+        let current_key = *imp.current_key.borrow();
+        let modifier_key = current_key
+            .and_then(|ch| self.key_stroke(ch))
+            .and_then(|key_stroke| key_stroke.modifier_key);
+        imp.current_modifier.replace(modifier_key);
     }
 
     pub fn set_visible_keys(&self, keys: Option<HashSet<char>>) {
@@ -1237,8 +1647,50 @@ impl KeyboardWidget {
         self.queue_draw();
     }
 
+    /// Show the numeric keypad next to the main block, if the layout has one.
+    /// While it is visible, characters on the numpad are typed there.
+    pub fn set_show_numpad(&self, show: bool) {
+        self.imp().show_numpad.set(show);
+        self.queue_resize();
+    }
+
     pub fn get_finger_for_char(&self, ch: char) -> Option<String> {
-        self.imp().layout.borrow().get_finger_for_char(ch)
+        let imp = self.imp();
+        let layout = imp.layout.borrow();
+
+        if imp.show_numpad.get() {
+            if let Some(finger) = layout.get_numpad_finger_for_char(ch) {
+                return Some(finger);
+            }
+        }
+
+        layout.get_finger_for_char(ch)
+    }
+
+    pub fn key_stroke(&self, ch: char) -> Option<KeyStroke> {
+        let imp = self.imp();
+        let layout = imp.layout.borrow();
+
+        if imp.show_numpad.get() {
+            if let Some(finger) = layout.get_numpad_finger_for_char(ch) {
+                return Some(KeyStroke {
+                    finger,
+                    reach: (0.0, 0.0),
+                    modifier_key: None,
+                    modifier_finger: None,
+                });
+            }
+        }
+
+        layout.key_stroke(ch)
+    }
+
+    pub fn numpad_decimal_separator(&self) -> Option<String> {
+        self.imp()
+            .layout
+            .borrow()
+            .numpad_decimal_separator()
+            .map(str::to_string)
     }
 }
 
@@ -0,0 +1,648 @@
+use gio::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::rc::Rc;
+
+// Racers with another protocol version can't join
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 7890;
+// The host races too, always with this id
+pub const HOST_ID: u32 = 0;
+// Lines longer than this aren't messages, and the connection sending them is dropped
+const MAX_LINE_LENGTH: usize = 64 * 1024;
+// Messages waiting for a racer that doesn't read them. Past this it's dropped, rather than
+// keeping more and more of them around
+const MAX_QUEUED_MESSAGES: usize = 1024;
+const READ_SIZE: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Racer {
+    pub id: u32,
+    pub name: String,
+}
+
+// Sent as one line of JSON each. Racers send `Join`, `Progress` and `Finished` to the host,
+// which passes them on to everyone along with its own messages
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Message {
+    Join { name: String, version: u32 },
+    // The id the host gave to a racer that joined
+    Welcome { id: u32 },
+    Lobby { racers: Vec<Racer> },
+    // Everyone types the same text, generated from the seed
+    Start { language: String, seed: u64 },
+    // Graphemes typed so far
+    Progress { id: u32, typed: usize },
+    Finished { id: u32, time: f64, wpm: f64 },
+    Leave { id: u32 },
+}
+
+impl Message {
+    pub fn encode(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_default();
+        line.push('\n');
+        line
+    }
+
+    // Lines that aren't a known message are skipped
+    pub fn decode(line: &str) -> Option<Self> {
+        serde_json::from_str(line.trim()).ok()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RaceResult {
+    pub id: u32,
+    // Seconds after the start
+    pub time: f64,
+    pub wpm: f64,
+}
+
+// Fastest first
+pub fn standings(results: &[RaceResult]) -> Vec<RaceResult> {
+    let mut standings = results.to_vec();
+    standings.sort_by(|a, b| a.time.total_cmp(&b.time));
+    standings
+}
+
+// Splits "host:port" into its parts, using the default port when there's none
+pub fn parse_address(address: &str) -> Option<(String, u16)> {
+    let address = address.trim();
+    if address.is_empty() {
+        return None;
+    }
+
+    match address.rsplit_once(':') {
+        // IPv6 addresses without a port have colons too, but no brackets
+        Some((host, port)) if !host.contains(':') || host.ends_with(']') => {
+            let host = host.trim_start_matches('[').trim_end_matches(']');
+            Some((host.to_string(), port.parse().ok()?))
+        }
+        _ => Some((address.to_string(), DEFAULT_PORT)),
+    }
+}
+
+// Where others on the network can reach this computer, falling back to its name when
+// there's no network. Connecting a UDP socket only looks up the address traffic would
+// leave from, without sending anything, so documentation addresses work as targets.
+pub fn local_addresses(port: u16) -> Vec<String> {
+    let addresses: Vec<String> = [("0.0.0.0:0", "192.0.2.1:9"), ("[::]:0", "[2001:db8::1]:9")]
+        .into_iter()
+        .filter_map(|(local, target)| {
+            let socket = UdpSocket::bind(local).ok()?;
+            socket.connect(target).ok()?;
+            socket.local_addr().ok().map(|address| address.ip())
+        })
+        .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+        .map(|ip| format_address(ip, port))
+        .collect();
+
+    if addresses.is_empty() {
+        vec![format!("{}:{}", glib::host_name(), port)]
+    } else {
+        addresses
+    }
+}
+
+// Addresses as typed to join, with brackets around IPv6 ones
+fn format_address(ip: IpAddr, port: u16) -> String {
+    SocketAddr::new(ip, port).to_string()
+}
+
+type MessageHandler = Rc<dyn Fn(Message)>;
+
+// Splits what arrives on a connection into lines
+#[derive(Default)]
+struct LineReader {
+    buffer: Vec<u8>,
+}
+
+impl LineReader {
+    // The lines completed by `bytes`, or `None` once a line is longer than
+    // `MAX_LINE_LENGTH`
+    fn push(&mut self, bytes: &[u8]) -> Option<Vec<String>> {
+        self.buffer.extend_from_slice(bytes);
+
+        let mut lines = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            if line.len() > MAX_LINE_LENGTH {
+                return None;
+            }
+            lines.push(String::from_utf8_lossy(&line).into_owned());
+        }
+
+        (self.buffer.len() <= MAX_LINE_LENGTH).then_some(lines)
+    }
+}
+
+// A connection to a racer or to the host. Messages are written in the background, so
+// one that stops reading them can't hold up the main loop.
+struct Peer {
+    connection: gio::SocketConnection,
+    queue: RefCell<VecDeque<String>>,
+    writing: Cell<bool>,
+}
+
+impl Peer {
+    fn new(connection: &gio::SocketConnection) -> Rc<Self> {
+        Rc::new(Self {
+            connection: connection.clone(),
+            queue: RefCell::default(),
+            writing: Cell::new(false),
+        })
+    }
+
+    fn send(self: &Rc<Self>, message: &Message) {
+        if self.queue.borrow().len() >= MAX_QUEUED_MESSAGES {
+            glib::g_warning!("mecalin", "Dropping a racer that stopped reading messages");
+            self.close();
+            return;
+        }
+
+        self.queue.borrow_mut().push_back(message.encode());
+        if !self.writing.replace(true) {
+            glib::spawn_future_local(self.clone().write_queue());
+        }
+    }
+
+    async fn write_queue(self: Rc<Self>) {
+        let output = self.connection.output_stream();
+        loop {
+            let Some(line) = self.queue.borrow_mut().pop_front() else {
+                break;
+            };
+            let error = match output
+                .write_all_future(line.into_bytes(), glib::Priority::DEFAULT)
+                .await
+            {
+                Ok((_, _, error)) => error,
+                Err((_, error)) => Some(error),
+            };
+            if let Some(error) = error {
+                glib::g_warning!("mecalin", "Failed to send race message: {}", error);
+                self.queue.borrow_mut().clear();
+                break;
+            }
+        }
+        self.writing.set(false);
+    }
+
+    fn close(&self) {
+        self.connection.close(None::<&gio::Cancellable>).ok();
+    }
+}
+
+// Reads messages until the connection is closed
+fn read_messages(
+    connection: &gio::SocketConnection,
+    on_message: impl Fn(Message) + 'static,
+    on_closed: impl FnOnce() + 'static,
+) {
+    let connection = connection.clone();
+    let input = connection.input_stream();
+    glib::spawn_future_local(async move {
+        let mut reader = LineReader::default();
+        while let Ok(bytes) = input
+            .read_bytes_future(READ_SIZE, glib::Priority::DEFAULT)
+            .await
+        {
+            // Nothing is read once the other side closes the connection
+            if bytes.is_empty() {
+                break;
+            }
+            let Some(lines) = reader.push(&bytes) else {
+                glib::g_warning!("mecalin", "Dropping a connection sending too long lines");
+                connection.close(None::<&gio::Cancellable>).ok();
+                break;
+            };
+            for line in lines {
+                if let Some(message) = Message::decode(&line) {
+                    on_message(message);
+                }
+            }
+        }
+        on_closed();
+    });
+}
+
+#[derive(Default)]
+struct HostState {
+    racers: RefCell<Vec<Racer>>,
+    // Racers that joined, by id
+    peers: RefCell<Vec<(u32, Rc<Peer>)>>,
+    next_id: Cell<u32>,
+}
+
+impl HostState {
+    // Sends to every racer, the host included
+    fn broadcast(&self, message: &Message, on_message: &MessageHandler) {
+        for (_, peer) in self.peers.borrow().iter() {
+            peer.send(message);
+        }
+        on_message(message.clone());
+    }
+
+    fn has_joined(&self, id: u32) -> bool {
+        self.peers.borrow().iter().any(|(other, _)| *other == id)
+    }
+
+    fn lobby(&self) -> Message {
+        Message::Lobby {
+            racers: self.racers.borrow().clone(),
+        }
+    }
+
+    fn remove(&self, id: u32, on_message: &MessageHandler) {
+        self.peers.borrow_mut().retain(|(other, _)| *other != id);
+        self.racers.borrow_mut().retain(|racer| racer.id != id);
+        self.broadcast(&Message::Leave { id }, on_message);
+        self.broadcast(&self.lobby(), on_message);
+    }
+}
+
+// Hosts a race others join over TCP
+pub struct RaceHost {
+    service: gio::SocketService,
+    port: u16,
+    state: Rc<HostState>,
+    on_message: MessageHandler,
+}
+
+impl RaceHost {
+    // Listens on every interface, on any free port if `port` is 0
+    pub fn start(
+        port: u16,
+        name: &str,
+        on_message: impl Fn(Message) + 'static,
+    ) -> Result<Self, glib::Error> {
+        let service = gio::SocketService::new();
+        let port = match port {
+            0 => service.add_any_inet_port(None::<&glib::Object>)?,
+            port => {
+                service.add_inet_port(port, None::<&glib::Object>)?;
+                port
+            }
+        };
+
+        let state = Rc::new(HostState::default());
+        state.racers.borrow_mut().push(Racer {
+            id: HOST_ID,
+            name: name.to_string(),
+        });
+        state.next_id.set(HOST_ID + 1);
+        let on_message: MessageHandler = Rc::new(on_message);
+
+        let weak_state = Rc::downgrade(&state);
+        let handler = on_message.clone();
+        service.connect_incoming(move |_, connection, _| {
+            if let Some(state) = weak_state.upgrade() {
+                accept(&state, connection, &handler);
+            }
+            false
+        });
+        service.start();
+
+        let host = Self {
+            service,
+            port,
+            state,
+            on_message,
+        };
+        host.state.broadcast(&host.state.lobby(), &host.on_message);
+        Ok(host)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn send(&self, message: Message) {
+        self.state.broadcast(&message, &self.on_message);
+    }
+
+    pub fn stop(&self) {
+        self.service.stop();
+        self.service.close();
+        for (_, peer) in self.state.peers.take() {
+            peer.close();
+        }
+    }
+}
+
+// Waits for a new connection to join, then passes its messages on to everyone
+fn accept(state: &Rc<HostState>, connection: &gio::SocketConnection, on_message: &MessageHandler) {
+    let id = state.next_id.get();
+    state.next_id.set(id + 1);
+
+    let weak_state = Rc::downgrade(state);
+    let handler = on_message.clone();
+    let peer = Peer::new(connection);
+    let on_peer_message = move |message: Message| {
+        let Some(state) = weak_state.upgrade() else {
+            return;
+        };
+
+        // Racers can only speak for themselves, and only once they joined
+        match message {
+            Message::Join { name, version } if !state.has_joined(id) => {
+                if version != PROTOCOL_VERSION {
+                    peer.close();
+                    return;
+                }
+                state.racers.borrow_mut().push(Racer { id, name });
+                state.peers.borrow_mut().push((id, peer.clone()));
+                peer.send(&Message::Welcome { id });
+                state.broadcast(&state.lobby(), &handler);
+            }
+            Message::Progress { typed, .. } if state.has_joined(id) => {
+                state.broadcast(&Message::Progress { id, typed }, &handler);
+            }
+            Message::Finished { time, wpm, .. } if state.has_joined(id) => {
+                state.broadcast(&Message::Finished { id, time, wpm }, &handler);
+            }
+            _ => {}
+        }
+    };
+
+    let weak_state = Rc::downgrade(state);
+    let handler = on_message.clone();
+    read_messages(connection, on_peer_message, move || {
+        if let Some(state) = weak_state.upgrade().filter(|state| state.has_joined(id)) {
+            state.remove(id, &handler);
+        }
+    });
+}
+
+// Takes part in a race hosted by someone else
+pub struct RaceClient {
+    peer: Rc<Peer>,
+}
+
+impl RaceClient {
+    // Connects to a host, and hands its messages over until the connection is lost, when
+    // a `Leave` from the host arrives
+    pub async fn connect(
+        host: &str,
+        port: u16,
+        name: &str,
+        on_message: impl Fn(Message) + 'static,
+    ) -> Result<Self, glib::Error> {
+        let client = gio::SocketClient::new();
+        let connection = client.connect_to_host_future(host, port).await?;
+
+        let on_message = Rc::new(on_message);
+        let handler = on_message.clone();
+        read_messages(
+            &connection,
+            move |message| handler(message),
+            move || on_message(Message::Leave { id: HOST_ID }),
+        );
+
+        let peer = Peer::new(&connection);
+        peer.send(&Message::Join {
+            name: name.to_string(),
+            version: PROTOCOL_VERSION,
+        });
+        Ok(Self { peer })
+    }
+
+    pub fn send(&self, message: Message) {
+        self.peer.send(&message);
+    }
+
+    pub fn stop(&self) {
+        self.peer.close();
+    }
+}
+
+// This side of a race, whether hosting or joining
+pub enum RaceSession {
+    Host(RaceHost),
+    Client(RaceClient),
+}
+
+impl RaceSession {
+    pub fn is_host(&self) -> bool {
+        matches!(self, RaceSession::Host(_))
+    }
+
+    pub fn send(&self, message: Message) {
+        match self {
+            RaceSession::Host(host) => host.send(message),
+            RaceSession::Client(client) => client.send(message),
+        }
+    }
+
+    pub fn stop(&self) {
+        match self {
+            RaceSession::Host(host) => host.stop(),
+            RaceSession::Client(client) => client.stop(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_message_round_trip() {
+        let messages = [
+            Message::Join {
+                name: "Ada".to_string(),
+                version: PROTOCOL_VERSION,
+            },
+            Message::Lobby {
+                racers: vec![Racer {
+                    id: HOST_ID,
+                    name: "Grace".to_string(),
+                }],
+            },
+            Message::Start {
+                language: "es".to_string(),
+                seed: u64::MAX,
+            },
+            Message::Progress { id: 2, typed: 42 },
+            Message::Finished {
+                id: 1,
+                time: 31.5,
+                wpm: 64.2,
+            },
+        ];
+        for message in messages {
+            let line = message.encode();
+            assert!(line.ends_with('\n') && !line.trim_end().contains('\n'));
+            assert_eq!(Message::decode(&line), Some(message));
+        }
+
+        assert_eq!(
+            Message::decode(r#"{"type":"progress","id":3,"typed":7}"#),
+            Some(Message::Progress { id: 3, typed: 7 })
+        );
+        assert_eq!(Message::decode("hello"), None);
+        assert_eq!(Message::decode(r#"{"type":"cheat"}"#), None);
+    }
+
+    // Collects the messages a racer gets
+    fn inbox() -> (Rc<RefCell<Vec<Message>>>, impl Fn(Message) + 'static) {
+        let messages = Rc::new(RefCell::new(Vec::new()));
+        let handler = {
+            let messages = messages.clone();
+            move |message| messages.borrow_mut().push(message)
+        };
+        (messages, handler)
+    }
+
+    fn has_lobby_of(messages: &RefCell<Vec<Message>>, count: usize) -> bool {
+        messages
+            .borrow()
+            .iter()
+            .any(|message| matches!(message, Message::Lobby { racers } if racers.len() == count))
+    }
+
+    // Runs the main context until `done`, failing after a few seconds
+    fn run_until(context: &glib::MainContext, done: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done() {
+            assert!(Instant::now() < deadline, "timed out waiting for messages");
+            context.iteration(false);
+        }
+    }
+
+    #[test]
+    fn test_race_over_loopback() {
+        let context = glib::MainContext::new();
+        context
+            .with_thread_default(|| {
+                let (host_messages, handler) = inbox();
+                let host = RaceHost::start(0, "Grace", handler).unwrap();
+                assert!(has_lobby_of(&host_messages, 1));
+
+                let connect = |name: &str| {
+                    let (messages, handler) = inbox();
+                    let client = context
+                        .block_on(RaceClient::connect("127.0.0.1", host.port(), name, handler))
+                        .unwrap();
+                    (client, messages)
+                };
+
+                let (ada, ada_messages) = connect("Ada");
+                run_until(&context, || {
+                    ada_messages.borrow().contains(&Message::Welcome { id: 1 })
+                        && has_lobby_of(&ada_messages, 2)
+                });
+                let (_linus, linus_messages) = connect("Linus");
+                run_until(&context, || {
+                    linus_messages
+                        .borrow()
+                        .contains(&Message::Welcome { id: 2 })
+                        && has_lobby_of(&ada_messages, 3)
+                });
+
+                let start = Message::Start {
+                    language: "en".to_string(),
+                    seed: 7,
+                };
+                host.send(start.clone());
+                run_until(&context, || {
+                    ada_messages.borrow().contains(&start)
+                        && linus_messages.borrow().contains(&start)
+                });
+
+                // Joining twice changes nothing, and progress is passed on under the
+                // sender's own id
+                ada.send(Message::Join {
+                    name: "Eve".to_string(),
+                    version: PROTOCOL_VERSION,
+                });
+                ada.send(Message::Progress { id: 2, typed: 5 });
+                let progress = Message::Progress { id: 1, typed: 5 };
+                run_until(&context, || {
+                    host_messages.borrow().contains(&progress)
+                        && linus_messages.borrow().contains(&progress)
+                });
+                assert!(!has_lobby_of(&host_messages, 4));
+
+                ada.stop();
+                run_until(&context, || {
+                    host_messages.borrow().contains(&Message::Leave { id: 1 })
+                        && linus_messages.borrow().contains(&Message::Leave { id: 1 })
+                });
+
+                host.stop();
+                run_until(&context, || {
+                    linus_messages
+                        .borrow()
+                        .contains(&Message::Leave { id: HOST_ID })
+                });
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_line_reader() {
+        let mut reader = LineReader::default();
+        assert_eq!(reader.push(b"{\"type\":"), Some(vec![]));
+        assert_eq!(
+            reader.push(b"\"leave\",\"id\":1}\nnext\nhalf"),
+            Some(vec![
+                "{\"type\":\"leave\",\"id\":1}\n".to_string(),
+                "next\n".to_string()
+            ])
+        );
+        assert_eq!(
+            reader.push(b" line\n"),
+            Some(vec!["half line\n".to_string()])
+        );
+
+        // Without an end, a line can't grow forever
+        let mut reader = LineReader::default();
+        let chunk = [b'a'; READ_SIZE];
+        let mut read = 0;
+        while reader.push(&chunk).is_some() {
+            read += chunk.len();
+            assert!(read <= MAX_LINE_LENGTH);
+        }
+    }
+
+    #[test]
+    fn test_standings() {
+        let result = |id, time| RaceResult { id, time, wpm: 0.0 };
+        let standings = standings(&[result(0, 40.0), result(1, 25.5), result(2, 33.0)]);
+        let ids: Vec<u32> = standings.iter().map(|result| result.id).collect();
+        assert_eq!(ids, [1, 2, 0]);
+    }
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address(" 192.168.1.20 "),
+            Some(("192.168.1.20".to_string(), DEFAULT_PORT))
+        );
+        assert_eq!(
+            parse_address("office-pc:8000"),
+            Some(("office-pc".to_string(), 8000))
+        );
+        assert_eq!(parse_address("[::1]:8000"), Some(("::1".to_string(), 8000)));
+        assert_eq!(
+            parse_address("fe80::1"),
+            Some(("fe80::1".to_string(), DEFAULT_PORT))
+        );
+        assert_eq!(parse_address("pc:port"), None);
+
+        // Local addresses can be typed back in as they are shown
+        for ip in ["192.168.1.20", "fe80::1"] {
+            let ip: IpAddr = ip.parse().unwrap();
+            assert_eq!(
+                parse_address(&format_address(ip, 8000)),
+                Some((ip.to_string(), 8000))
+            );
+        }
+        assert_eq!(parse_address(""), None);
+    }
+}
@@ -0,0 +1,507 @@
+use gettextrs::gettext;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use i18n_format::i18n_fmt;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::game_engine::GameLoop;
+use crate::game_setup::game_language;
+use crate::high_scores::profile_name;
+use crate::lan_race::{
+    local_addresses, parse_address, standings, Message, RaceClient, RaceHost, RaceResult,
+    RaceSession, Racer, DEFAULT_PORT, HOST_ID,
+};
+use crate::race_game::{format_time, race_passage, Track, COUNTDOWN_SECONDS};
+use crate::speed_test_text_view::SpeedTestTextView;
+use crate::text_generation::{random_seed, Language};
+use crate::text_utils::calculate_wpm;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/lan_race_game.ui")]
+    pub struct LanRaceGame {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub host_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub address_row: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub join_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub error_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub lobby_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub racers_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub leave_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub start_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub tracks_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub countdown_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub text_view: TemplateChild<SpeedTestTextView>,
+        #[template_child]
+        pub placement_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub standings_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub lobby_button: TemplateChild<gtk::Button>,
+
+        pub session: RefCell<Option<RaceSession>>,
+        // Given by the host once joined
+        pub id: Cell<Option<u32>>,
+        pub racers: RefCell<Vec<Racer>>,
+        // Each racer's track, by their id
+        pub tracks: RefCell<Vec<(u32, Track)>>,
+        pub results: RefCell<Vec<RaceResult>>,
+        pub countdown: Cell<f64>,
+        pub elapsed: Cell<f64>,
+        pub running: Cell<bool>,
+        pub game_loop: GameLoop,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LanRaceGame {
+        const NAME: &'static str = "LanRaceGame";
+        type Type = super::LanRaceGame;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            SpeedTestTextView::ensure_type();
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for LanRaceGame {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_signals();
+        }
+
+        fn dispose(&self) {
+            if let Some(session) = self.session.take() {
+                session.stop();
+            }
+        }
+    }
+    impl WidgetImpl for LanRaceGame {}
+    impl BoxImpl for LanRaceGame {}
+}
+
+glib::wrapper! {
+    pub struct LanRaceGame(ObjectSubclass<imp::LanRaceGame>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+// Races other instances on the local network. Nobody can be kept waiting, so there's no
+// pausing here
+impl LanRaceGame {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn setup_signals(&self) {
+        let imp = self.imp();
+
+        let obj = self.downgrade();
+        imp.host_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.host();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.join_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.join();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.address_row.connect_apply(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.join();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.leave_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.reset();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.start_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.send(Message::Start {
+                    language: game_language().to_string(),
+                    seed: random_seed(),
+                });
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.lobby_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.show_lobby();
+            }
+        });
+
+        imp.text_view.connect_closure(
+            "typed-text-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = game)]
+                self,
+                move |_text_view: SpeedTestTextView| {
+                    game.update_player();
+                }
+            ),
+        );
+    }
+
+    // Leaves any race, and goes back to hosting or joining one
+    pub fn reset(&self) {
+        let imp = self.imp();
+        imp.running.set(false);
+        imp.text_view.set_running(false);
+        imp.text_view.set_accepts_input(false);
+        if let Some(session) = imp.session.take() {
+            session.stop();
+        }
+        imp.id.set(None);
+        imp.racers.borrow_mut().clear();
+        imp.error_label.set_visible(false);
+        imp.host_button.set_sensitive(true);
+        imp.join_button.set_sensitive(true);
+        imp.stack.set_visible_child_name("connect");
+    }
+
+    fn show_error(&self, message: &str) {
+        let imp = self.imp();
+        imp.error_label.set_text(message);
+        imp.error_label.set_visible(true);
+    }
+
+    fn message_handler(&self) -> impl Fn(Message) + 'static {
+        let obj = self.downgrade();
+        move |message| {
+            if let Some(obj) = obj.upgrade() {
+                obj.handle_message(message);
+            }
+        }
+    }
+
+    fn host(&self) {
+        let imp = self.imp();
+        imp.id.set(Some(HOST_ID));
+        match RaceHost::start(DEFAULT_PORT, &profile_name(), self.message_handler()) {
+            Ok(host) => {
+                imp.session.replace(Some(RaceSession::Host(host)));
+                let addresses = local_addresses(DEFAULT_PORT).join(", ");
+                // Translators: The `{}` block will be replaced with network addresses. Do not
+                // translate it!
+                imp.lobby_label
+                    .set_text(&i18n_fmt! { i18n_fmt("Others can join at {}", addresses) });
+                self.show_lobby();
+            }
+            Err(error) => {
+                imp.id.set(None);
+                // Translators: The `{}` block will be replaced with an error message. Do not
+                // translate it!
+                self.show_error(&i18n_fmt! { i18n_fmt("Could not host a race: {}", error) });
+            }
+        }
+    }
+
+    fn join(&self) {
+        let imp = self.imp();
+        let Some((host, port)) = parse_address(&imp.address_row.text()) else {
+            self.show_error(&gettext("Enter the address of the host, like 192.168.1.20"));
+            return;
+        };
+
+        imp.error_label.set_visible(false);
+        imp.host_button.set_sensitive(false);
+        imp.join_button.set_sensitive(false);
+
+        let handler = self.message_handler();
+        glib::spawn_future_local(glib::clone!(
+            #[weak(rename_to = game)]
+            self,
+            async move {
+                let result = RaceClient::connect(&host, port, &profile_name(), handler).await;
+                let imp = game.imp();
+                imp.host_button.set_sensitive(true);
+                imp.join_button.set_sensitive(true);
+
+                match result {
+                    Ok(client) => {
+                        imp.session.replace(Some(RaceSession::Client(client)));
+                        imp.lobby_label
+                            .set_text(&gettext("Waiting for the host to start the race"));
+                        game.show_lobby();
+                    }
+                    Err(error) => {
+                        // Translators: The `{}` block will be replaced with an error message.
+                        // Do not translate it!
+                        game.show_error(
+                            &i18n_fmt! { i18n_fmt("Could not join the race: {}", error) },
+                        );
+                    }
+                }
+            }
+        ));
+    }
+
+    fn send(&self, message: Message) {
+        if let Some(session) = self.imp().session.borrow().as_ref() {
+            session.send(message);
+        }
+    }
+
+    fn is_host(&self) -> bool {
+        self.imp()
+            .session
+            .borrow()
+            .as_ref()
+            .is_some_and(RaceSession::is_host)
+    }
+
+    fn is_client(&self) -> bool {
+        self.imp()
+            .session
+            .borrow()
+            .as_ref()
+            .is_some_and(|session| !session.is_host())
+    }
+
+    fn show_lobby(&self) {
+        let imp = self.imp();
+        imp.start_button.set_visible(self.is_host());
+        imp.stack.set_visible_child_name("lobby");
+        if self.is_host() {
+            imp.start_button.grab_focus();
+        }
+    }
+
+    fn handle_message(&self, message: Message) {
+        let imp = self.imp();
+        match message {
+            Message::Welcome { id } => imp.id.set(Some(id)),
+            Message::Lobby { racers } => {
+                imp.racers.replace(racers);
+                self.update_racers();
+            }
+            Message::Start { language, seed } => {
+                let language = Language::from_str(&language).unwrap_or_default();
+                self.start_race(language, seed);
+            }
+            Message::Progress { id, typed } => {
+                let length = imp.text_view.original_grapheme_count().max(1) as f64;
+                if let Some((_, track)) = imp
+                    .tracks
+                    .borrow()
+                    .iter()
+                    .find(|(track_id, _)| *track_id == id)
+                {
+                    track.update(typed as f64 / length, None);
+                }
+            }
+            Message::Finished { id, time, wpm } => {
+                let mut results = imp.results.borrow_mut();
+                if !results.iter().any(|result| result.id == id) {
+                    results.push(RaceResult { id, time, wpm });
+                }
+                drop(results);
+                if let Some((_, track)) = imp
+                    .tracks
+                    .borrow()
+                    .iter()
+                    .find(|(track_id, _)| *track_id == id)
+                {
+                    track.update(1.0, Some(time));
+                }
+                self.update_standings();
+            }
+            // Only the host closing the connection, rather than us leaving
+            Message::Leave { id } if id == HOST_ID && self.is_client() => {
+                self.reset();
+                self.show_error(&gettext("The connection to the host was lost"));
+            }
+            Message::Join { .. } | Message::Leave { .. } => {}
+        }
+    }
+
+    fn racer_name(&self, id: u32) -> String {
+        self.imp()
+            .racers
+            .borrow()
+            .iter()
+            .find(|racer| racer.id == id)
+            .map(|racer| racer.name.clone())
+            .unwrap_or_default()
+    }
+
+    fn update_racers(&self) {
+        let imp = self.imp();
+        imp.racers_list.remove_all();
+        for racer in imp.racers.borrow().iter() {
+            let row = adw::ActionRow::builder().title(&racer.name).build();
+            if Some(racer.id) == imp.id.get() {
+                row.set_subtitle(&gettext("You"));
+            }
+            imp.racers_list.append(&row);
+        }
+    }
+
+    fn start_race(&self, language: Language, seed: u64) {
+        let imp = self.imp();
+
+        // Custom word lists and vocabulary sizes aren't shared, so everyone gets all words
+        let passage = race_passage(language, None, seed);
+        imp.text_view.reset();
+        imp.text_view.set_original_text(&passage);
+        imp.text_view.set_accepts_input(false);
+
+        imp.tracks_list.remove_all();
+        let tracks = imp
+            .racers
+            .borrow()
+            .iter()
+            .map(|racer| (racer.id, Track::new(&imp.tracks_list, &racer.name)))
+            .collect();
+        imp.tracks.replace(tracks);
+        imp.results.borrow_mut().clear();
+
+        imp.countdown.set(COUNTDOWN_SECONDS);
+        imp.elapsed.set(0.0);
+        imp.running.set(true);
+        imp.countdown_label.set_visible(true);
+        imp.stack.set_visible_child_name("race");
+
+        let obj = self.downgrade();
+        imp.game_loop.start(self, move |dt| match obj.upgrade() {
+            Some(obj) => obj.update_race(dt),
+            None => glib::ControlFlow::Break,
+        });
+    }
+
+    fn update_race(&self, dt: f64) -> glib::ControlFlow {
+        let imp = self.imp();
+        if !imp.running.get() {
+            return glib::ControlFlow::Break;
+        }
+
+        let countdown = imp.countdown.get();
+        if countdown > 0.0 {
+            let countdown = countdown - dt;
+            imp.countdown.set(countdown);
+            if countdown > 0.0 {
+                imp.countdown_label
+                    .set_text(&(countdown.ceil() as u32).to_string());
+            } else {
+                imp.countdown_label.set_visible(false);
+                imp.text_view.set_accepts_input(true);
+                imp.text_view.set_running(true);
+                imp.text_view.grab_focus();
+            }
+        } else {
+            imp.elapsed.set(imp.elapsed.get() + dt);
+        }
+
+        glib::ControlFlow::Continue
+    }
+
+    fn update_player(&self) {
+        let imp = self.imp();
+        let Some(id) = imp.id.get() else {
+            return;
+        };
+        if !imp.running.get() {
+            return;
+        }
+
+        // Mistakes have to be fixed before crossing the line
+        let typed = imp.text_view.correct_grapheme_count();
+        self.send(Message::Progress { id, typed });
+
+        if typed >= imp.text_view.original_grapheme_count() {
+            imp.running.set(false);
+            imp.text_view.set_running(false);
+            imp.text_view.set_accepts_input(false);
+
+            let time = imp.elapsed.get();
+            let wpm = calculate_wpm(
+                Duration::from_secs_f64(time),
+                &imp.text_view.original_text(),
+                &imp.text_view.typed_text(),
+            );
+            imp.stack.set_visible_child_name("results");
+            imp.lobby_button.grab_focus();
+            self.send(Message::Finished { id, time, wpm });
+        }
+    }
+
+    // Finishers in order, followed by those still typing
+    fn update_standings(&self) {
+        let imp = self.imp();
+        let standings = standings(&imp.results.borrow());
+        let racers = imp.racers.borrow().len();
+
+        if let Some(place) = standings
+            .iter()
+            .position(|result| Some(result.id) == imp.id.get())
+            .map(|index| index + 1)
+        {
+            if place == 1 {
+                imp.placement_label.set_text(&gettext("You Won!"));
+            } else {
+                imp.placement_label
+                    .set_text(&i18n_fmt! { i18n_fmt("Place {} of {}", place, racers) });
+            }
+        }
+
+        imp.standings_list.remove_all();
+        for (index, result) in standings.iter().enumerate() {
+            let wpm = result.wpm.floor() as u32;
+            // Translators: The first `{}` block will be replaced with a typing speed, and the
+            // second with the time taken. Do not translate them!
+            let subtitle = i18n_fmt! {
+                i18n_fmt("{} WPM · {}", wpm, format_time(result.time))
+            };
+            let row = adw::ActionRow::builder()
+                .title(format!("{}. {}", index + 1, self.racer_name(result.id)))
+                .subtitle(subtitle)
+                .build();
+            imp.standings_list.append(&row);
+        }
+
+        for racer in imp.racers.borrow().iter() {
+            if standings.iter().all(|result| result.id != racer.id) {
+                let row = adw::ActionRow::builder()
+                    .title(&racer.name)
+                    .subtitle(gettext("Still racing…"))
+                    .build();
+                imp.standings_list.append(&row);
+            }
+        }
+    }
+}
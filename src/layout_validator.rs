@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::course_validator::Severity;
+use crate::keyboard_widget::KeyboardLayout;
+
+// Fingers the hand widget knows how to highlight
+const FINGERS: &[&str] = &[
+    "left_pinky",
+    "left_ring",
+    "left_middle",
+    "left_index",
+    "left_thumb",
+    "right_index",
+    "right_middle",
+    "right_ring",
+    "right_pinky",
+    "right_thumb",
+    "both_thumbs",
+];
+
+// Modifier keys drawn around the character rows of the keyboard widget
+const MODIFIERS: &[&str] = &[
+    "tab",
+    "caps_lock",
+    "shift_left",
+    "shift_right",
+    "ctrl_left",
+    "ctrl_right",
+    "super_left",
+    "super_right",
+    "menu",
+    "alt_left",
+    "alt_right",
+    "enter",
+    "backspace",
+];
+
+const EXPECTED_ROWS: usize = 4;
+const EXPECTED_NUMPAD_ROWS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayoutIssue {
+    EmptyBase { row: usize, column: usize },
+    UnknownFinger { key: String, finger: String },
+    UnexpectedRowCount { found: usize },
+    UnexpectedNumpadRowCount { found: usize },
+    DuplicateCharacter { character: char },
+    MissingModifier { name: String },
+}
+
+impl LayoutIssue {
+    // Errors leave keys that can't be typed or highlighted, warnings only affect drawing
+    pub fn severity(&self) -> Severity {
+        match self {
+            LayoutIssue::EmptyBase { .. } | LayoutIssue::UnknownFinger { .. } => Severity::Error,
+            LayoutIssue::UnexpectedRowCount { .. }
+            | LayoutIssue::UnexpectedNumpadRowCount { .. }
+            | LayoutIssue::DuplicateCharacter { .. }
+            | LayoutIssue::MissingModifier { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for LayoutIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+
+        match self {
+            LayoutIssue::EmptyBase { row, column } => {
+                write!(
+                    f,
+                    "{prefix}: row {row}, key {column}: key has no base character"
+                )
+            }
+            LayoutIssue::UnknownFinger { key, finger } => {
+                write!(f, "{prefix}: key {key:?}: unknown finger {finger:?}")
+            }
+            LayoutIssue::UnexpectedRowCount { found } => write!(
+                f,
+                "{prefix}: layout has {found} rows, the keyboard widget draws {EXPECTED_ROWS}"
+            ),
+            LayoutIssue::UnexpectedNumpadRowCount { found } => write!(
+                f,
+                "{prefix}: numpad has {found} rows, the keyboard widget draws {EXPECTED_NUMPAD_ROWS}"
+            ),
+            LayoutIssue::DuplicateCharacter { character } => write!(
+                f,
+                "{prefix}: {character:?} is the base character of more than one key"
+            ),
+            LayoutIssue::MissingModifier { name } => {
+                write!(f, "{prefix}: modifier key {name:?} is missing")
+            }
+        }
+    }
+}
+
+pub fn validate_layout(layout: &KeyboardLayout) -> Vec<LayoutIssue> {
+    let mut issues = Vec::new();
+
+    if layout.keys.len() != EXPECTED_ROWS {
+        issues.push(LayoutIssue::UnexpectedRowCount {
+            found: layout.keys.len(),
+        });
+    }
+
+    // The numpad is optional, but when present it has to fit the drawn block
+    if !layout.numpad.is_empty() && layout.numpad.len() != EXPECTED_NUMPAD_ROWS {
+        issues.push(LayoutIssue::UnexpectedNumpadRowCount {
+            found: layout.numpad.len(),
+        });
+    }
+
+    let mut seen = HashSet::new();
+    for (row, keys) in layout.keys.iter().enumerate() {
+        for (column, key) in keys.iter().enumerate() {
+            match key.base.chars().next() {
+                None => issues.push(LayoutIssue::EmptyBase { row, column }),
+                Some(ch) => {
+                    let lower = ch.to_lowercase().next().unwrap_or(ch);
+                    if !seen.insert(lower) {
+                        issues.push(LayoutIssue::DuplicateCharacter { character: ch });
+                    }
+                }
+            }
+        }
+    }
+
+    let keys = layout
+        .keys
+        .iter()
+        .chain(&layout.numpad)
+        .flatten()
+        .map(|key| (key.base.clone(), &key.finger))
+        .chain(std::iter::once((
+            layout.space.base.clone(),
+            &layout.space.finger,
+        )))
+        .chain(
+            layout
+                .modifiers
+                .iter()
+                .map(|(name, modifier)| (name.clone(), &modifier.finger)),
+        );
+    for (key, finger) in keys {
+        if !FINGERS.contains(&finger.as_str()) {
+            issues.push(LayoutIssue::UnknownFinger {
+                key,
+                finger: finger.clone(),
+            });
+        }
+    }
+
+    for name in MODIFIERS {
+        if !layout.modifiers.contains_key(*name) {
+            issues.push(LayoutIssue::MissingModifier {
+                name: name.to_string(),
+            });
+        }
+    }
+
+    issues
+}
+
+pub fn validate_layout_file(path: &Path) -> Result<Vec<LayoutIssue>, Box<dyn std::error::Error>> {
+    let json = std::fs::read_to_string(path)?;
+    let layout: KeyboardLayout = serde_json::from_str(&json)?;
+    Ok(validate_layout(&layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::course::BUNDLED_LANGUAGES;
+
+    #[test]
+    fn test_bundled_layouts_have_no_issues() {
+        for language in BUNDLED_LANGUAGES {
+            let layout = KeyboardLayout::load_from_json(language).unwrap();
+            let issues: Vec<String> = validate_layout(&layout)
+                .iter()
+                .map(|issue| issue.to_string())
+                .collect();
+            assert!(issues.is_empty(), "{language}: {issues:#?}");
+        }
+    }
+
+    #[test]
+    fn test_broken_layout() {
+        let layout: KeyboardLayout = serde_json::from_str(
+            r#"{
+                "name": "Broken",
+                "keys": [[
+                    {"base": "a", "finger": "left_pinky"},
+                    {"base": "", "finger": "left_ring"},
+                    {"base": "A", "finger": "left_toe"}
+                ]],
+                "numpad": [[
+                    {"base": "7", "finger": "right_index"}
+                ]],
+                "space": {"base": " ", "finger": "both_thumbs"}
+            }"#,
+        )
+        .unwrap();
+        let issues = validate_layout(&layout);
+
+        assert!(issues.contains(&LayoutIssue::UnexpectedRowCount { found: 1 }));
+        assert!(issues.contains(&LayoutIssue::UnexpectedNumpadRowCount { found: 1 }));
+        assert!(issues.contains(&LayoutIssue::EmptyBase { row: 0, column: 1 }));
+        assert!(issues.contains(&LayoutIssue::DuplicateCharacter { character: 'A' }));
+        assert!(issues.contains(&LayoutIssue::UnknownFinger {
+            key: "A".to_string(),
+            finger: "left_toe".to_string()
+        }));
+        assert!(issues.contains(&LayoutIssue::MissingModifier {
+            name: "enter".to_string()
+        }));
+    }
+}
@@ -0,0 +1,173 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use i18n_format::i18n_fmt;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use std::cell::RefCell;
+
+use crate::high_scores::{self, difficulty_ui_string, Game, HighScore, HighScoreTable};
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/leaderboard_view.ui")]
+    pub struct LeaderboardView {
+        #[template_child]
+        pub category_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub empty_status: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub scores_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub scores_list: TemplateChild<gtk::ListBox>,
+
+        pub table: RefCell<HighScoreTable>,
+        // Game and difficulty of each entry in the category dropdown
+        pub categories: RefCell<Vec<(Game, String)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for LeaderboardView {
+        const NAME: &'static str = "MecalinLeaderboardView";
+        type Type = super::LeaderboardView;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for LeaderboardView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            self.category_dropdown.connect_selected_notify(glib::clone!(
+                #[weak]
+                obj,
+                move |_| {
+                    obj.show_scores();
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for LeaderboardView {}
+    impl BoxImpl for LeaderboardView {}
+}
+
+glib::wrapper! {
+    pub struct LeaderboardView(ObjectSubclass<imp::LeaderboardView>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl LeaderboardView {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    // Reads the scores again, since games may have been played since the last time
+    pub fn refresh(&self) {
+        let imp = self.imp();
+        let table = HighScoreTable::load(&high_scores::high_scores_path()).unwrap_or_default();
+        let categories = table.categories();
+        imp.table.replace(table);
+
+        let names: Vec<String> = categories
+            .iter()
+            .map(|(game, difficulty)| {
+                // Translators: The first `{}` block will be replaced with the name of a game,
+                // and the second with its difficulty. Do not translate them!
+                i18n_fmt! { i18n_fmt("{} · {}", game.ui_string(), difficulty_ui_string(difficulty)) }
+            })
+            .collect();
+        let name_strs: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        let previous = imp
+            .categories
+            .borrow()
+            .get(imp.category_dropdown.selected() as usize)
+            .cloned();
+        let selected = previous
+            .and_then(|previous| categories.iter().position(|c| *c == previous))
+            .unwrap_or(0);
+        imp.categories.replace(categories);
+
+        imp.category_dropdown
+            .set_model(Some(&gtk::StringList::new(&name_strs)));
+        imp.category_dropdown.set_selected(selected as u32);
+        self.show_scores();
+    }
+
+    fn show_scores(&self) {
+        let imp = self.imp();
+        imp.scores_list.remove_all();
+
+        let categories = imp.categories.borrow();
+        let category = categories.get(imp.category_dropdown.selected() as usize);
+
+        imp.category_dropdown.set_visible(category.is_some());
+        imp.empty_status.set_visible(category.is_none());
+        imp.scores_window.set_visible(category.is_some());
+
+        let Some((game, difficulty)) = category else {
+            return;
+        };
+
+        let table = imp.table.borrow();
+        for (rank, high_score) in table.top(*game, difficulty).into_iter().enumerate() {
+            imp.scores_list.append(&score_row(rank + 1, high_score));
+        }
+    }
+}
+
+fn score_row(rank: usize, high_score: &HighScore) -> adw::ActionRow {
+    let date = glib::DateTime::from_unix_local(high_score.timestamp)
+        .and_then(|date| date.format("%x"))
+        .map(|date| date.to_string())
+        .unwrap_or_default();
+    let accuracy = (high_score.accuracy * 100.0).floor() as u32;
+
+    // Translators: The `{}` blocks will be replaced with the level reached, the keys typed
+    // per minute, the accuracy percentage and the date of the game. Do not translate them!
+    let subtitle = i18n_fmt! {
+        i18n_fmt(
+            "Level {} · {} keys/min · {}% accuracy · {}",
+            high_score.level,
+            high_score.keys_per_minute.round() as u32,
+            accuracy,
+            date
+        )
+    };
+
+    let row = adw::ActionRow::builder()
+        .title(&high_score.profile)
+        .subtitle(&subtitle)
+        .build();
+
+    let rank_label = gtk::Label::new(Some(&rank.to_string()));
+    rank_label.set_width_chars(2);
+    rank_label.add_css_class("title-4");
+    rank_label.add_css_class("dim-label");
+    row.add_prefix(&rank_label);
+
+    let score_label = gtk::Label::new(Some(&high_score.score.to_string()));
+    score_label.add_css_class("title-3");
+    score_label.set_tooltip_text(Some(&gettext("Score")));
+    row.add_suffix(&score_label);
+
+    row
+}
+
+impl Default for LeaderboardView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
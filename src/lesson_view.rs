@@ -1,14 +1,24 @@
 use gettextrs::gettext;
+use gtk::gdk;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use i18n_format::i18n_fmt;
 use std::cell::{Cell, RefCell};
+use std::str::FromStr;
 
-use crate::course::Lesson;
+use crate::announcements::{self, next_key_message};
+use crate::course::{Lesson, LessonStep};
 use crate::hand_widget::HandWidget;
 use crate::keyboard_widget::KeyboardWidget;
+use crate::pacing::{paced_bpm, rhythm_score, Metronome};
+use crate::sounds::{self, Sound};
+use crate::text_generation::{random_seed, restricted, Language};
 use crate::typing_row::TypingRow;
 
+// Size of the generated exercise at the end of each lesson
+const EXTRA_PRACTICE_WORD_COUNT: usize = 5;
+const EXTRA_PRACTICE_REPETITIONS: u32 = 2;
+
 mod imp {
     use super::*;
 
@@ -27,6 +37,8 @@ mod imp {
         #[template_child]
         pub typing_row: TemplateChild<TypingRow>,
         #[template_child]
+        pub rhythm_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub keyboard_container: TemplateChild<gtk::Box>,
         #[template_child]
         pub hand_widget: TemplateChild<HandWidget>,
@@ -41,6 +53,13 @@ mod imp {
         pub current_repetition: Cell<u32>,
         pub course: RefCell<Option<crate::course::Course>>,
         pub has_mistake: Cell<bool>,
+        // Generated each time the step after the last one of a lesson is loaded
+        pub extra_practice_step: RefCell<Option<LessonStep>>,
+        // Ticks while the lesson is on screen, if pacing
+        pub metronome: Metronome,
+        pub bpm: Cell<Option<u32>>,
+        // A lesson or step was just loaded, and will be announced once it's all set up
+        pub step_announcement_pending: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -51,6 +70,32 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             klass.bind_template();
+
+            // Moving through the course without leaving the keyboard, for screen reader users
+            klass.add_binding(
+                gdk::Key::Page_Down,
+                gdk::ModifierType::ALT_MASK,
+                |lesson_view| {
+                    lesson_view.go_to_adjacent_lesson(true);
+                    glib::Propagation::Stop
+                },
+            );
+            klass.add_binding(
+                gdk::Key::Page_Up,
+                gdk::ModifierType::ALT_MASK,
+                |lesson_view| {
+                    lesson_view.go_to_adjacent_lesson(false);
+                    glib::Propagation::Stop
+                },
+            );
+            klass.add_binding(
+                gdk::Key::r,
+                gdk::ModifierType::CONTROL_MASK,
+                |lesson_view| {
+                    lesson_view.announce_step();
+                    glib::Propagation::Stop
+                },
+            );
         }
 
         fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
@@ -81,7 +126,30 @@ mod imp {
 
     impl WidgetImpl for LessonView {
         fn grab_focus(&self) -> bool {
-            self.typing_row.grab_focus()
+            // Introductions are only left with the continue button
+            if self.continue_button.is_visible() {
+                self.continue_button.grab_focus()
+            } else {
+                self.typing_row.grab_focus()
+            }
+        }
+
+        fn map(&self) {
+            self.parent_map();
+
+            // Pacing may have been changed in the preferences meanwhile
+            self.bpm.set(paced_bpm());
+            self.rhythm_label.set_visible(false);
+            if let Some(bpm) = self.bpm.get() {
+                self.metronome.start(bpm);
+            }
+
+            self.obj().queue_step_announcement();
+        }
+
+        fn unmap(&self) {
+            self.metronome.stop();
+            self.parent_unmap();
         }
     }
 
@@ -107,6 +175,10 @@ impl imp::LessonView {
                 self.obj(),
                 move |_: TypingRow, at_beginning: bool| {
                     let imp = lesson_view.imp();
+                    lesson_view.announce_if_shown(
+                        &gettext("Mistake"),
+                        gtk::AccessibleAnnouncementPriority::High,
+                    );
                     if !at_beginning {
                         imp.has_mistake.set(true);
                     } else {
@@ -138,9 +210,20 @@ impl imp::LessonView {
                     let imp = lesson_view.imp();
                     let next_char = next_char_str.chars().next();
                     imp.keyboard_widget.set_current_key(next_char);
-                    let finger =
-                        next_char.and_then(|ch| imp.keyboard_widget.get_finger_for_char(ch));
-                    imp.hand_widget.set_current_finger(finger);
+                    let key_stroke = next_char.and_then(|ch| imp.keyboard_widget.key_stroke(ch));
+
+                    // A new step is announced along with its first key
+                    if let Some(ch) = next_char.filter(|_| !imp.step_announcement_pending.get()) {
+                        let finger = key_stroke
+                            .as_ref()
+                            .map(|key_stroke| key_stroke.finger.as_str());
+                        lesson_view.announce_if_shown(
+                            &next_key_message(ch, finger),
+                            gtk::AccessibleAnnouncementPriority::Medium,
+                        );
+                    }
+
+                    imp.hand_widget.set_key_stroke(key_stroke);
                 }
             ),
         );
@@ -157,8 +240,8 @@ impl imp::LessonView {
 
                     // Update hand widget for the next character in the sequence
                     if let Some(current_key) = *imp.keyboard_widget.imp().current_key.borrow() {
-                        let finger = imp.keyboard_widget.get_finger_for_char(current_key);
-                        imp.hand_widget.set_current_finger(finger);
+                        let key_stroke = imp.keyboard_widget.key_stroke(current_key);
+                        imp.hand_widget.set_key_stroke(key_stroke);
                     }
                 }
             ),
@@ -252,6 +335,7 @@ impl LessonView {
             imp.step_description.set_visible(false);
             imp.continue_button.set_visible(true);
             imp.text_container.set_visible(false);
+            imp.continue_button.grab_focus();
         } else {
             // Regular lesson - handle first step
             // Set the first step's text as target text
@@ -266,6 +350,7 @@ impl LessonView {
                     );
                     imp.continue_button.set_visible(true);
                     imp.text_container.set_visible(false);
+                    imp.continue_button.grab_focus();
                 } else {
                     imp.step_description.set_visible(false);
                     imp.continue_button.set_visible(false);
@@ -296,6 +381,7 @@ impl LessonView {
             }
         }
 
+        self.queue_step_announcement();
         imp.typing_row.clear();
         imp.has_mistake.set(false);
     }
@@ -308,53 +394,218 @@ impl LessonView {
         imp.current_repetition.set(0);
         imp.has_mistake.set(false);
 
+        let extra_practice_step = self.generate_extra_practice_step(step_index as usize);
+        imp.extra_practice_step.replace(extra_practice_step);
+
+        let Some(step) = self.current_step() else {
+            return;
+        };
+        self.queue_step_announcement();
+
+        if step.introduction {
+            // Introduction step - show description and continue button, hide text views
+            imp.step_description.set_visible(true);
+            imp.step_description
+                .set_text(step.description.as_deref().unwrap_or(&step.text));
+            imp.continue_button.set_visible(true);
+            imp.text_container.set_visible(false);
+            imp.continue_button.grab_focus();
+        } else {
+            // Regular step - show description if available, show text views
+            if let Some(description) = &step.description {
+                imp.step_description.set_visible(true);
+                imp.step_description.set_text(description);
+            } else {
+                imp.step_description.set_visible(false);
+            }
+            imp.continue_button.set_visible(false);
+            imp.text_container.set_visible(true);
+            imp.typing_row.set_target_text(&step.text);
+            imp.typing_row.clear();
+            self.update_repetition_label();
+
+            // Focus the text view for immediate typing
+            imp.typing_row.grab_focus();
+        }
+
+        // Update keyboard for this step
+        let mut target_keys = std::collections::HashSet::new();
+        for ch in step.text.chars() {
+            if !ch.is_control() {
+                target_keys.insert(ch.to_lowercase().next().unwrap_or(ch));
+            }
+        }
+
+        imp.keyboard_widget.set_visible_keys(Some(target_keys));
+
+        // Set initial key/finger highlight
+        let first_char = step.text.chars().next();
+        imp.keyboard_widget.set_current_key(first_char);
+
+        let key_stroke = first_char.and_then(|ch| imp.keyboard_widget.key_stroke(ch));
+        imp.hand_widget.set_key_stroke(key_stroke);
+    }
+
+    // The step being typed, which is either one of the lesson or the extra practice
+    fn current_step(&self) -> Option<LessonStep> {
+        let imp = self.imp();
+        let step_index = self.current_step_index() as usize;
+
         let current_lesson_boxed = imp.current_lesson.borrow();
-        if let Some(boxed) = current_lesson_boxed.as_ref() {
-            if let Ok(lesson) = boxed.try_borrow::<Lesson>() {
-                if let Some(step) = lesson.steps.get(step_index as usize) {
-                    if step.introduction {
-                        // Introduction step - show description and continue button, hide text views
-                        imp.step_description.set_visible(true);
-                        imp.step_description
-                            .set_text(step.description.as_deref().unwrap_or(&step.text));
-                        imp.continue_button.set_visible(true);
-                        imp.text_container.set_visible(false);
-                    } else {
-                        // Regular step - show description if available, show text views
-                        if let Some(description) = &step.description {
-                            imp.step_description.set_visible(true);
-                            imp.step_description.set_text(description);
-                        } else {
-                            imp.step_description.set_visible(false);
-                        }
-                        imp.continue_button.set_visible(false);
-                        imp.text_container.set_visible(true);
-                        imp.typing_row.set_target_text(&step.text);
-                        imp.typing_row.clear();
-                        self.update_repetition_label();
-
-                        // Focus the text view for immediate typing
-                        imp.typing_row.grab_focus();
-                    }
+        let lesson = current_lesson_boxed.as_ref()?.try_borrow::<Lesson>().ok()?;
 
-                    // Update keyboard for this step
-                    let mut target_keys = std::collections::HashSet::new();
-                    for ch in step.text.chars() {
-                        if !ch.is_control() {
-                            target_keys.insert(ch.to_lowercase().next().unwrap_or(ch));
-                        }
-                    }
+        match lesson.steps.get(step_index) {
+            Some(step) => Some(step.clone()),
+            None if step_index == lesson.steps.len() => imp.extra_practice_step.borrow().clone(),
+            None => None,
+        }
+    }
 
-                    imp.keyboard_widget.set_visible_keys(Some(target_keys));
+    fn has_extra_practice(&self, lesson: &Lesson) -> bool {
+        let extra_practice_enabled = self
+            .imp()
+            .settings
+            .borrow()
+            .as_ref()
+            .is_some_and(|settings| settings.boolean("extra-practice"));
 
-                    // Set initial key/finger highlight
-                    let first_char = step.text.chars().next();
-                    imp.keyboard_widget.set_current_key(first_char);
+        extra_practice_enabled && !lesson.introduction
+    }
 
-                    let finger =
-                        first_char.and_then(|ch| imp.keyboard_widget.get_finger_for_char(ch));
-                    imp.hand_widget.set_current_finger(finger);
-                }
+    // A new exercise after the last step of the lesson, made of the keys learned so far,
+    // so practice isn't the same fixed text every time
+    fn generate_extra_practice_step(&self, step_index: usize) -> Option<LessonStep> {
+        let imp = self.imp();
+        let current_lesson_boxed = imp.current_lesson.borrow();
+        let lesson = current_lesson_boxed.as_ref()?.try_borrow::<Lesson>().ok()?;
+        if step_index != lesson.steps.len() || !self.has_extra_practice(&lesson) {
+            return None;
+        }
+
+        let allowed = imp.course.borrow().as_ref()?.learned_characters(lesson.id);
+        let language =
+            Language::from_str(crate::utils::language_from_locale()).unwrap_or(Language::English);
+        let text = restricted(language, &allowed, EXTRA_PRACTICE_WORD_COUNT, random_seed());
+        if text.is_empty() {
+            return None;
+        }
+
+        Some(LessonStep {
+            id: lesson
+                .steps
+                .iter()
+                .map(|step| step.id + 1)
+                .max()
+                .unwrap_or(0),
+            text,
+            description: Some(gettext(
+                "Extra practice with the keys you have learned so far",
+            )),
+            repetitions: EXTRA_PRACTICE_REPETITIONS,
+            introduction: false,
+        })
+    }
+
+    // Announcements made while the lesson is in the background would only be confusing
+    fn announce_if_shown(&self, message: &str, priority: gtk::AccessibleAnnouncementPriority) {
+        if self.is_mapped() {
+            announcements::announce(self, message, priority);
+        }
+    }
+
+    // Waits for the lesson or step to be set up, which takes a few changes in a row
+    fn queue_step_announcement(&self) {
+        let imp = self.imp();
+        if imp.step_announcement_pending.replace(true) {
+            return;
+        }
+
+        glib::idle_add_local_once(glib::clone!(
+            #[weak(rename_to = lesson_view)]
+            self,
+            move || {
+                lesson_view.imp().step_announcement_pending.set(false);
+                lesson_view.announce_step();
+            }
+        ));
+    }
+
+    // Where the lesson is at: what a new lesson is about, and what to type next
+    fn announce_step(&self) {
+        let imp = self.imp();
+        let mut parts = Vec::new();
+
+        let total_steps = {
+            let current_lesson_boxed = imp.current_lesson.borrow();
+            let Some(lesson) = current_lesson_boxed
+                .as_ref()
+                .and_then(|boxed| boxed.try_borrow::<Lesson>().ok())
+            else {
+                return;
+            };
+
+            if lesson.introduction || self.current_step_index() == 0 {
+                parts.push(lesson.title.clone());
+                parts.push(lesson.description.clone());
+            }
+            lesson.steps.len() + usize::from(self.has_extra_practice(&lesson))
+        };
+
+        if imp.step_description.is_visible() {
+            parts.push(imp.step_description.text().to_string());
+        }
+
+        if imp.text_container.is_visible() {
+            if let Some(step) = self.current_step() {
+                let step_number = self.current_step_index() + 1;
+                // Translators: the step of the lesson, the number of steps and the text to type
+                parts.push(
+                    i18n_fmt! { i18n_fmt("Step {} of {}: {}", step_number, total_steps, step.text) },
+                );
+            }
+            if let Some(ch) = imp.typing_row.next_char() {
+                let finger = imp.keyboard_widget.get_finger_for_char(ch);
+                parts.push(next_key_message(ch, finger.as_deref()));
+            }
+        } else if imp.continue_button.is_visible() {
+            parts.push(gettext("Press Enter to continue"));
+        }
+
+        self.announce_if_shown(
+            &parts.join("\n"),
+            gtk::AccessibleAnnouncementPriority::Medium,
+        );
+    }
+
+    fn go_to_adjacent_lesson(&self, forward: bool) {
+        let imp = self.imp();
+        let Some(current_lesson_id) = self
+            .current_lesson()
+            .and_then(|boxed| boxed.try_borrow::<Lesson>().ok().map(|lesson| lesson.id))
+        else {
+            return;
+        };
+
+        let lesson_id = if forward {
+            current_lesson_id.checked_add(1)
+        } else {
+            current_lesson_id.checked_sub(1)
+        };
+        let lesson = lesson_id.and_then(|lesson_id| {
+            let course = imp.course.borrow();
+            course.as_ref()?.get_lesson(lesson_id).cloned()
+        });
+
+        match lesson {
+            Some(lesson) => self.set_lesson(&lesson),
+            None => {
+                self.error_bell();
+                let message = if forward {
+                    gettext("This is the last lesson")
+                } else {
+                    gettext("This is the first lesson")
+                };
+                self.announce_if_shown(&message, gtk::AccessibleAnnouncementPriority::Medium);
             }
         }
     }
@@ -374,25 +625,36 @@ impl LessonView {
         let imp = self.imp();
         let current_repetition = imp.current_repetition.get();
 
-        let current_lesson_boxed = imp.current_lesson.borrow();
-        if let Some(boxed) = current_lesson_boxed.as_ref() {
-            if let Ok(lesson) = boxed.try_borrow::<Lesson>() {
-                let step_index = self.current_step_index() as usize;
-                if let Some(step) = lesson.steps.get(step_index) {
-                    let label_text =
-                        i18n_fmt! { i18n_fmt("{}/{} Good", current_repetition, step.repetitions) };
-                    imp.typing_row.set_repetition_text(&label_text);
-                }
-            }
+        if let Some(step) = self.current_step() {
+            let label_text =
+                i18n_fmt! { i18n_fmt("{}/{} Good", current_repetition, step.repetitions) };
+            imp.typing_row.set_repetition_text(&label_text);
         }
     }
 
     fn handle_step_completion(&self) {
         let imp = self.imp();
 
+        if let Some(rhythm) = imp
+            .bpm
+            .get()
+            .and_then(|bpm| rhythm_score(&imp.typing_row.keystrokes(), &[], bpm))
+        {
+            let rhythm = (rhythm * 100.).floor();
+            // Translators: The `{}` block will be replaced with a percentage number. Do not
+            // translate it!
+            imp.rhythm_label
+                .set_text(&i18n_fmt! { i18n_fmt("Rhythm: {}%", rhythm) });
+            imp.rhythm_label.set_visible(true);
+        }
+
         // Check if there was a mistake during this attempt
         if imp.has_mistake.get() {
             // Restart the step - reset repetition count and clear text
+            self.announce_if_shown(
+                &gettext("Finished with mistakes, starting the step over"),
+                gtk::AccessibleAnnouncementPriority::Medium,
+            );
             self.reset_repetition_count();
             imp.has_mistake.set(false);
             imp.typing_row.clear();
@@ -403,24 +665,23 @@ impl LessonView {
         let current_repetition = imp.current_repetition.get() + 1;
         imp.current_repetition.set(current_repetition);
 
-        let current_lesson_boxed = imp.current_lesson.borrow();
-        if let Some(boxed) = current_lesson_boxed.as_ref() {
-            if let Ok(lesson) = boxed.try_borrow::<Lesson>() {
-                let step_index = self.current_step_index() as usize;
-                if let Some(step) = lesson.steps.get(step_index) {
-                    self.update_repetition_label();
+        if let Some(step) = self.current_step() {
+            self.update_repetition_label();
 
-                    if current_repetition >= step.repetitions {
-                        // Required repetitions completed, advance to next step
-                        self.advance_to_next_step();
-                    } else {
-                        // Need more repetitions, clear text for next attempt
-                        imp.typing_row.clear();
+            if current_repetition >= step.repetitions {
+                // Required repetitions completed, advance to next step
+                sounds::play(Sound::StepCompleted);
+                self.advance_to_next_step();
+            } else {
+                // Need more repetitions, clear text for next attempt
+                self.announce_if_shown(
+                    &i18n_fmt! { i18n_fmt("{} of {} repetitions done", current_repetition, step.repetitions) },
+                    gtk::AccessibleAnnouncementPriority::Medium,
+                );
+                imp.typing_row.clear();
 
-                        // Focus the text view for next repetition
-                        imp.typing_row.grab_focus();
-                    }
-                }
+                // Focus the text view for next repetition
+                imp.typing_row.grab_focus();
             }
         }
     }
@@ -471,6 +732,10 @@ impl LessonView {
                 // All lessons completed
                 imp.lesson_description
                     .set_text(&gettext("Course completed! Congratulations!"));
+                self.announce_if_shown(
+                    &imp.lesson_description.text(),
+                    gtk::AccessibleAnnouncementPriority::Medium,
+                );
                 imp.step_description.set_visible(false);
                 imp.continue_button.set_visible(false);
                 imp.text_container.set_visible(false);
@@ -486,7 +751,7 @@ impl LessonView {
                     (
                         lesson.id,
                         self.current_step_index() as usize,
-                        lesson.steps.len(),
+                        lesson.steps.len() + usize::from(self.has_extra_practice(&lesson)),
                     )
                 } else {
                     return;
@@ -525,6 +790,10 @@ impl LessonView {
                     imp.typing_row
                         .set_target_text(&gettext("Lesson completed! Well done!"));
                 }
+                self.announce_if_shown(
+                    &imp.typing_row.imp().target_label.text(),
+                    gtk::AccessibleAnnouncementPriority::Medium,
+                );
                 imp.typing_row.clear();
             }
         }
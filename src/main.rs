@@ -1,11 +1,32 @@
+mod announcements;
 mod application;
+mod cli;
+mod code_snippets;
 mod course;
+mod course_validator;
+mod custom_word_lists;
+mod dictation_view;
 mod falling_keys_game;
+mod game_engine;
+mod game_setup;
 mod hand_widget;
+mod high_scores;
 mod keyboard_widget;
+mod lan_race;
+mod lan_race_game;
+mod layout_validator;
+mod leaderboard_view;
 mod lesson_view;
+mod number_drill_view;
+mod pacing;
+mod pause;
+mod pause_overlay;
 mod preferences_view;
+mod quotes;
+mod race_game;
 mod scrolling_lanes_game;
+mod sounds;
+mod speech;
 mod speed_test_results_view;
 mod speed_test_text_view;
 mod speed_test_view;
@@ -19,6 +40,7 @@ mod config {
 }
 mod utils;
 mod window;
+mod word_defense_game;
 
 use anyhow::Result;
 use gettextrs::{bind_textdomain_codeset, bindtextdomain, setlocale, textdomain, LocaleCategory};
@@ -44,6 +66,11 @@ fn run_application() -> Result<()> {
 }
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
     if let Err(e) = run_application() {
         eprintln!("Application initialization failed: {e}");
     }
@@ -0,0 +1,265 @@
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use i18n_format::i18n_fmt;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::hand_widget::HandWidget;
+use crate::keyboard_widget::KeyboardWidget;
+use crate::text_generation::{number_drill, random_seed, Language, NumberDrill};
+use crate::typing_row::TypingRow;
+
+// Order of the entries in the drill type dropdown
+const DRILL_TYPES: &[NumberDrill] = &[
+    NumberDrill::Numbers,
+    NumberDrill::Amounts,
+    NumberDrill::Dates,
+];
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/number_drill_view.ui")]
+    pub struct NumberDrillView {
+        #[template_child]
+        pub settings_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub drill_type_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub numpad_check: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub text_container: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub typing_row: TemplateChild<TypingRow>,
+        #[template_child]
+        pub results_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub results_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub restart_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub keyboard_container: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub hand_widget: TemplateChild<HandWidget>,
+        #[template_child]
+        pub keyboard_widget: TemplateChild<KeyboardWidget>,
+
+        pub lines: RefCell<Vec<String>>,
+        pub current_line: Cell<usize>,
+        pub mistakes: Cell<u32>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for NumberDrillView {
+        const NAME: &'static str = "NumberDrillView";
+        type Type = super::NumberDrillView;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for NumberDrillView {
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            let settings = gio::Settings::new("io.github.nacho.mecalin");
+            settings
+                .bind("show-hand-widget", &*self.hand_widget, "visible")
+                .build();
+            settings
+                .bind("show-keyboard-widget", &*self.keyboard_widget, "visible")
+                .build();
+
+            obj.setup_signals();
+            obj.start_drill();
+        }
+    }
+
+    impl WidgetImpl for NumberDrillView {
+        fn grab_focus(&self) -> bool {
+            self.typing_row.grab_focus()
+        }
+    }
+
+    impl BoxImpl for NumberDrillView {}
+}
+
+glib::wrapper! {
+    pub struct NumberDrillView(ObjectSubclass<imp::NumberDrillView>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl NumberDrillView {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn get_drill_type(&self) -> NumberDrill {
+        DRILL_TYPES
+            .get(self.imp().drill_type_dropdown.selected() as usize)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn start_drill(&self) {
+        let imp = self.imp();
+        let lang_code = crate::utils::language_from_locale();
+        let language = Language::from_str(lang_code).unwrap_or(Language::English);
+
+        let use_numpad = imp.numpad_check.is_active();
+        imp.keyboard_widget.set_show_numpad(use_numpad);
+
+        // Amounts are typed with whatever the numpad decimal key produces
+        let decimal_separator = imp
+            .keyboard_widget
+            .numpad_decimal_separator()
+            .filter(|_| use_numpad)
+            .unwrap_or_else(|| ".".to_string());
+
+        let lines = number_drill(
+            language,
+            self.get_drill_type(),
+            &decimal_separator,
+            random_seed(),
+        );
+
+        let target_keys: HashSet<char> = lines.iter().flat_map(|line| line.chars()).collect();
+        imp.keyboard_widget.set_visible_keys(Some(target_keys));
+
+        *imp.lines.borrow_mut() = lines;
+        imp.current_line.set(0);
+        imp.mistakes.set(0);
+
+        imp.results_box.set_visible(false);
+        imp.text_container.set_visible(true);
+        self.load_line();
+    }
+
+    fn load_line(&self) {
+        let imp = self.imp();
+        let lines = imp.lines.borrow();
+        let current_line = imp.current_line.get();
+
+        if let Some(line) = lines.get(current_line) {
+            imp.typing_row.set_target_text(line);
+            imp.typing_row.clear();
+
+            let label_text = i18n_fmt! { i18n_fmt("{}/{} Lines", current_line, lines.len()) };
+            imp.typing_row.set_repetition_text(&label_text);
+
+            self.highlight_char(line.chars().next());
+            imp.typing_row.grab_focus();
+        }
+    }
+
+    fn highlight_char(&self, next_char: Option<char>) {
+        let imp = self.imp();
+        imp.keyboard_widget.set_current_key(next_char);
+        let key_stroke = next_char.and_then(|ch| imp.keyboard_widget.key_stroke(ch));
+        imp.hand_widget.set_key_stroke(key_stroke);
+    }
+
+    fn handle_line_completion(&self) {
+        let imp = self.imp();
+        let next_line = imp.current_line.get() + 1;
+        imp.current_line.set(next_line);
+
+        if next_line < imp.lines.borrow().len() {
+            self.load_line();
+            return;
+        }
+
+        let line_count = imp.lines.borrow().len();
+        let mistakes = imp.mistakes.get();
+        let results_text =
+            i18n_fmt! { i18n_fmt("{} lines typed with {} mistakes", line_count, mistakes) };
+        imp.results_label.set_text(&results_text);
+
+        self.highlight_char(None);
+        imp.text_container.set_visible(false);
+        imp.results_box.set_visible(true);
+        imp.restart_button.grab_focus();
+    }
+
+    fn setup_signals(&self) {
+        let imp = self.imp();
+
+        imp.typing_row.connect_closure(
+            "mistake-made",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = view)]
+                self,
+                move |_: TypingRow, _at_beginning: bool| {
+                    let imp = view.imp();
+                    imp.mistakes.set(imp.mistakes.get() + 1);
+                }
+            ),
+        );
+
+        imp.typing_row.connect_closure(
+            "step-completed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = view)]
+                self,
+                move |_: TypingRow| {
+                    view.handle_line_completion();
+                }
+            ),
+        );
+
+        imp.typing_row.connect_closure(
+            "next-char-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = view)]
+                self,
+                move |_: TypingRow, next_char_str: String| {
+                    view.highlight_char(next_char_str.chars().next());
+                }
+            ),
+        );
+
+        imp.drill_type_dropdown
+            .connect_selected_notify(glib::clone!(
+                #[weak(rename_to = view)]
+                self,
+                move |_| {
+                    view.start_drill();
+                }
+            ));
+
+        imp.numpad_check.connect_toggled(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.start_drill();
+            }
+        ));
+
+        imp.restart_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.start_drill();
+            }
+        ));
+    }
+}
+
+impl Default for NumberDrillView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,185 @@
+use gtk::prelude::*;
+use gtk::{gio, glib};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+pub const MIN_BPM: u32 = 30;
+pub const MAX_BPM: u32 = 600;
+
+// Words are counted as five characters, typed one per beat
+const CHARACTERS_PER_WORD: f64 = 5.0;
+
+pub fn beat_interval(bpm: u32) -> Duration {
+    Duration::from_secs_f64(60.0 / bpm.clamp(MIN_BPM, MAX_BPM) as f64)
+}
+
+pub fn bpm_to_wpm(bpm: u32) -> f64 {
+    bpm as f64 / CHARACTERS_PER_WORD
+}
+
+// How many characters should be typed after `elapsed` to keep up with the beat
+pub fn paced_position(elapsed: Duration, bpm: u32) -> usize {
+    (elapsed.as_secs_f64() / beat_interval(bpm).as_secs_f64()) as usize
+}
+
+// Time from `elapsed` until the next beat, or zero when it's right on one
+pub fn until_next_beat(elapsed: Duration, bpm: u32) -> Duration {
+    let beat = beat_interval(bpm);
+    let into_beat = Duration::from_nanos((elapsed.as_nanos() % beat.as_nanos()) as u64);
+    if into_beat.is_zero() {
+        Duration::ZERO
+    } else {
+        beat - into_beat
+    }
+}
+
+// How evenly keystrokes followed the beat, from 0 to 1. Each gap between two keystrokes
+// loses points the further it is from a beat, and gaps twice as long or more count as
+// missing the beat entirely. Gaps with one of the `pauses` in them are left out.
+pub fn rhythm_score(keystrokes: &[Instant], pauses: &[Instant], bpm: u32) -> Option<f64> {
+    let beat = beat_interval(bpm).as_secs_f64();
+    let deviations: Vec<f64> = keystrokes
+        .windows(2)
+        .filter(|pair| {
+            !pauses
+                .iter()
+                .any(|pause| (pair[0]..pair[1]).contains(pause))
+        })
+        .map(|pair| {
+            let gap = pair[1].saturating_duration_since(pair[0]).as_secs_f64();
+            ((gap - beat).abs() / beat).min(1.0)
+        })
+        .collect();
+    if deviations.is_empty() {
+        return None;
+    }
+
+    let average = deviations.iter().sum::<f64>() / deviations.len() as f64;
+    Some(1.0 - average)
+}
+
+// The tempo to type at, if pacing is on
+pub fn paced_bpm() -> Option<u32> {
+    let settings = gio::Settings::new("io.github.nacho.mecalin");
+    settings
+        .boolean("pacing")
+        .then(|| settings.uint("pacing-bpm"))
+}
+
+// Clicks on every beat until stopped
+pub struct Metronome {
+    source_id: Rc<RefCell<Option<glib::SourceId>>>,
+    click: gtk::MediaFile,
+}
+
+impl Default for Metronome {
+    fn default() -> Self {
+        Self {
+            source_id: Rc::new(RefCell::new(None)),
+            click: gtk::MediaFile::for_resource("/io/github/nacho/mecalin/sounds/beat.wav"),
+        }
+    }
+}
+
+impl Metronome {
+    // Starts over with a click right away
+    pub fn start(&self, bpm: u32) {
+        self.start_at(bpm, Duration::ZERO);
+    }
+
+    // Picks up the beat of a metronome that ran for `elapsed`, clicking on its next beat
+    pub fn start_at(&self, bpm: u32, elapsed: Duration) {
+        self.stop();
+
+        let settings = gio::Settings::new("io.github.nacho.mecalin");
+        self.click.set_volume(settings.double("sound-volume"));
+
+        let click = self.click.clone();
+        let tick = move || {
+            if click.is_seekable() {
+                click.seek(0);
+            }
+            click.play();
+        };
+        let source_id = self.source_id.clone();
+        let first_beat = glib::timeout_add_local_once(until_next_beat(elapsed, bpm), move || {
+            tick();
+            let beats = glib::timeout_add_local(beat_interval(bpm), move || {
+                tick();
+                glib::ControlFlow::Continue
+            });
+            source_id.replace(Some(beats));
+        });
+        self.source_id.replace(Some(first_beat));
+    }
+
+    pub fn stop(&self) {
+        if let Some(source_id) = self.source_id.take() {
+            source_id.remove();
+        }
+    }
+}
+
+impl Drop for Metronome {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tempo() {
+        assert_eq!(beat_interval(120), Duration::from_millis(500));
+        assert_eq!(beat_interval(1), beat_interval(MIN_BPM));
+        assert_eq!(bpm_to_wpm(150), 30.0);
+        assert_eq!(paced_position(Duration::from_millis(2600), 120), 5);
+        assert_eq!(paced_position(Duration::ZERO, 120), 0);
+    }
+
+    #[test]
+    fn test_until_next_beat() {
+        assert_eq!(until_next_beat(Duration::ZERO, 120), Duration::ZERO);
+        assert_eq!(
+            until_next_beat(Duration::from_millis(1000), 120),
+            Duration::ZERO
+        );
+        assert_eq!(
+            until_next_beat(Duration::from_millis(2600), 120),
+            Duration::from_millis(400)
+        );
+    }
+
+    #[test]
+    fn test_rhythm_score() {
+        let start = Instant::now();
+        let at = |millis: &[u64]| -> Vec<Instant> {
+            millis
+                .iter()
+                .map(|millis| start + Duration::from_millis(*millis))
+                .collect()
+        };
+
+        assert_eq!(rhythm_score(&at(&[0]), &[], 120), None);
+        assert_eq!(
+            rhythm_score(&at(&[0, 500, 1000, 1500]), &[], 120),
+            Some(1.0)
+        );
+
+        // A quarter of a beat off each time
+        let score = rhythm_score(&at(&[0, 625, 1000]), &[], 120).unwrap();
+        assert!((score - 0.75).abs() < 1e-9);
+
+        // Long gaps only count as one missed beat each
+        let score = rhythm_score(&at(&[0, 500, 5000]), &[], 120).unwrap();
+        assert!((score - 0.5).abs() < 1e-9);
+
+        // Unless the test was paused in between
+        let pauses = at(&[700]);
+        assert_eq!(rhythm_score(&at(&[0, 500, 5000]), &pauses, 120), Some(1.0));
+        assert_eq!(rhythm_score(&at(&[500, 5000]), &pauses, 120), None);
+    }
+}
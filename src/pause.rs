@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+// Views with a running game or test, so the window can stop them while they can't be played
+pub trait Pausable {
+    fn pause(&self);
+    fn resume(&self);
+    fn is_paused(&self) -> bool;
+    // Whether there's something running to pause, or a pause to resume
+    fn can_pause(&self) -> bool;
+
+    fn toggle_pause(&self) {
+        if self.is_paused() {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+}
+
+// Keeps track of how long something was paused, so it can be left out of timings
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PauseClock {
+    paused_at: Option<Instant>,
+    paused_total: Duration,
+}
+
+impl PauseClock {
+    pub fn pause(&mut self) {
+        self.pause_at(Instant::now());
+    }
+
+    pub fn resume(&mut self) {
+        self.resume_at(Instant::now());
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    // Time since `start` without the pauses
+    pub fn active_since(&self, start: Instant) -> Duration {
+        self.active_between(start, Instant::now())
+    }
+
+    fn pause_at(&mut self, now: Instant) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(now);
+        }
+    }
+
+    fn resume_at(&mut self, now: Instant) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_total += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    fn paused_duration_at(&self, now: Instant) -> Duration {
+        let current = self.paused_at.map_or(Duration::ZERO, |paused_at| {
+            now.saturating_duration_since(paused_at)
+        });
+        self.paused_total + current
+    }
+
+    fn active_between(&self, start: Instant, now: Instant) -> Duration {
+        now.saturating_duration_since(start)
+            .saturating_sub(self.paused_duration_at(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_clock() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let mut clock = PauseClock::default();
+        assert!(!clock.is_paused());
+        assert_eq!(clock.active_between(start, at(10)), Duration::from_secs(10));
+
+        clock.pause_at(at(10));
+        assert!(clock.is_paused());
+        // Time doesn't move while paused
+        assert_eq!(clock.active_between(start, at(25)), Duration::from_secs(10));
+
+        // Pausing twice keeps the first pause
+        clock.pause_at(at(20));
+        clock.resume_at(at(30));
+        assert!(!clock.is_paused());
+        assert_eq!(clock.paused_duration_at(at(30)), Duration::from_secs(20));
+        assert_eq!(clock.active_between(start, at(35)), Duration::from_secs(15));
+
+        // Resuming without a pause does nothing
+        clock.resume_at(at(40));
+        assert_eq!(clock.paused_duration_at(at(40)), Duration::from_secs(20));
+    }
+}
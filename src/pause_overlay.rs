@@ -0,0 +1,101 @@
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::pause::Pausable;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/pause_overlay.ui")]
+    pub struct PauseOverlay {
+        #[template_child]
+        pub resume_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PauseOverlay {
+        const NAME: &'static str = "MecalinPauseOverlay";
+        type Type = super::PauseOverlay;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PauseOverlay {
+        fn signals() -> &'static [glib::subclass::Signal] {
+            use std::sync::OnceLock;
+            static SIGNALS: OnceLock<Vec<glib::subclass::Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| vec![glib::subclass::Signal::builder("resume").build()])
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            self.resume_button.connect_clicked(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.obj().emit_by_name::<()>("resume", &[]);
+                }
+            ));
+        }
+    }
+
+    impl WidgetImpl for PauseOverlay {}
+    impl BoxImpl for PauseOverlay {}
+}
+
+glib::wrapper! {
+    pub struct PauseOverlay(ObjectSubclass<imp::PauseOverlay>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl PauseOverlay {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    pub fn connect_resume<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "resume",
+            false,
+            glib::closure_local!(move |overlay: PauseOverlay| f(&overlay)),
+        )
+    }
+
+    // Shown with the focus on the resume button, so Enter continues too
+    pub fn show(&self) {
+        self.set_visible(true);
+        self.imp().resume_button.grab_focus();
+    }
+}
+
+impl Default for PauseOverlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Toggles the pause of `view` with Escape, wherever the focus is inside it
+pub fn add_escape_controller<P: IsA<gtk::Widget> + Pausable>(view: &P) {
+    let key_controller = gtk::EventControllerKey::new();
+    key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+    let weak_view = view.downgrade();
+    key_controller.connect_key_pressed(move |_, key, _, _| match weak_view.upgrade() {
+        Some(view) if key == gtk::gdk::Key::Escape && view.can_pause() => {
+            view.toggle_pause();
+            glib::Propagation::Stop
+        }
+        _ => glib::Propagation::Proceed,
+    });
+    view.add_controller(key_controller);
+}
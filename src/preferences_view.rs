@@ -1,11 +1,21 @@
+use gettextrs::gettext;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use gtk::{gio, glib};
 use i18n_format::i18n_fmt;
 use libadwaita as adw;
 use libadwaita::prelude::*;
+use std::cell::{Cell, RefCell};
+use std::path::Path;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 
 use crate::course::Course;
+use crate::custom_word_lists;
+use crate::game_setup::game_language;
+use crate::pacing::bpm_to_wpm;
+use crate::sounds::Sound;
+use crate::text_generation::{language_word_list, Language};
 
 mod imp {
     use super::*;
@@ -20,7 +30,39 @@ mod imp {
         #[template_child]
         pub use_finger_colors_switch: TemplateChild<adw::SwitchRow>,
         #[template_child]
+        pub screen_reader_mode_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub sound_feedback_row: TemplateChild<adw::ExpanderRow>,
+        #[template_child]
+        pub sound_volume_adjustment: TemplateChild<gtk::Adjustment>,
+        #[template_child]
+        pub pacing_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub pacing_bpm_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
         pub lesson_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub extra_practice_switch: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub word_lists_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub word_list_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub vocabulary_combo: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub import_word_list_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub generate_word_list_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub player_name_row: TemplateChild<adw::EntryRow>,
+
+        pub word_list_names: RefCell<Vec<String>>,
+        pub word_list_rows: RefCell<Vec<adw::ActionRow>>,
+        // Set while the word list combo is rebuilt, so it doesn't change the setting
+        pub updating_word_lists: Cell<bool>,
+        // Entries of the vocabulary combo, where 0 means every word of the list
+        pub vocabulary_sizes: RefCell<Vec<u32>>,
+        pub updating_vocabulary: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -42,10 +84,17 @@ mod imp {
         fn constructed(&self) {
             self.parent_constructed();
             self.setup_settings();
+            self.setup_word_lists();
         }
     }
 
-    impl WidgetImpl for PreferencesView {}
+    impl WidgetImpl for PreferencesView {
+        // The game language may have changed since the view was last shown
+        fn map(&self) {
+            self.parent_map();
+            self.refresh_vocabulary();
+        }
+    }
     impl BoxImpl for PreferencesView {}
 
     impl PreferencesView {
@@ -70,6 +119,56 @@ mod imp {
                     "active",
                 )
                 .build();
+            settings
+                .bind(
+                    "screen-reader-mode",
+                    &*self.screen_reader_mode_switch,
+                    "active",
+                )
+                .build();
+            settings
+                .bind("extra-practice", &*self.extra_practice_switch, "active")
+                .build();
+            settings
+                .bind("player-name", &*self.player_name_row, "text")
+                .build();
+            settings
+                .bind(
+                    "sound-feedback",
+                    &*self.sound_feedback_row,
+                    "enable-expansion",
+                )
+                .build();
+            settings
+                .bind("sound-volume", &*self.sound_volume_adjustment, "value")
+                .build();
+
+            settings
+                .bind("pacing", &*self.pacing_switch, "active")
+                .build();
+            self.pacing_switch
+                .bind_property("active", &*self.pacing_bpm_row, "sensitive")
+                .sync_create()
+                .build();
+            self.pacing_bpm_row
+                .set_value(settings.uint("pacing-bpm") as f64);
+            self.update_pacing_subtitle();
+            self.pacing_bpm_row.connect_value_notify(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |row| {
+                    let settings = gio::Settings::new("io.github.nacho.mecalin");
+                    settings.set_uint("pacing-bpm", row.value() as u32).ok();
+                    imp.update_pacing_subtitle();
+                }
+            ));
+
+            // A switch for each sound, under the volume
+            for sound in Sound::iter() {
+                let row = adw::SwitchRow::builder().title(sound.ui_string()).build();
+                settings.bind(&sound.settings_key(), &row, "active").build();
+                self.sound_feedback_row.add_row(&row);
+            }
 
             // Load course and setup lesson combo
             let layout_code = crate::utils::language_from_locale();
@@ -94,6 +193,236 @@ mod imp {
                 });
             }
         }
+
+        fn update_pacing_subtitle(&self) {
+            let wpm = bpm_to_wpm(self.pacing_bpm_row.value() as u32);
+            // Translators: The `{}` block will be replaced with a typing speed. Do not
+            // translate it!
+            self.pacing_bpm_row
+                .set_subtitle(&i18n_fmt!(i18n_fmt("About {} words per minute", wpm)));
+        }
+
+        fn setup_word_lists(&self) {
+            self.word_list_combo.connect_selected_notify(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |combo| {
+                    if imp.updating_word_lists.get() {
+                        return;
+                    }
+
+                    // The first entry is the language's own word list
+                    let name = match combo.selected() {
+                        0 => String::new(),
+                        i => imp
+                            .word_list_names
+                            .borrow()
+                            .get(i as usize - 1)
+                            .cloned()
+                            .unwrap_or_default(),
+                    };
+                    let settings = gio::Settings::new("io.github.nacho.mecalin");
+                    settings.set_string("custom-word-list", &name).ok();
+                    imp.refresh_vocabulary();
+                }
+            ));
+
+            self.import_word_list_row.connect_activated(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.choose_word_list_file(
+                        &gettext("Import Word List"),
+                        custom_word_lists::import_file,
+                    );
+                }
+            ));
+
+            self.generate_word_list_row.connect_activated(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    imp.choose_word_list_file(
+                        &gettext("Generate Word List From Document"),
+                        custom_word_lists::generate_from_document,
+                    );
+                }
+            ));
+
+            self.vocabulary_combo.connect_selected_notify(glib::clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |combo| {
+                    if imp.updating_vocabulary.get() {
+                        return;
+                    }
+
+                    let size = imp
+                        .vocabulary_sizes
+                        .borrow()
+                        .get(combo.selected() as usize)
+                        .copied()
+                        .unwrap_or(0);
+                    let settings = gio::Settings::new("io.github.nacho.mecalin");
+                    settings.set_uint("vocabulary-size", size).ok();
+                }
+            ));
+
+            self.refresh_word_lists();
+        }
+
+        // Only offers the vocabulary sizes that leave out some of the words in use
+        fn refresh_vocabulary(&self) {
+            let word_count = custom_word_lists::active_word_list()
+                .map(|word_list| word_list.len())
+                .unwrap_or_else(|| {
+                    // Speed tests use the words of the system language, and games their own
+                    let system_language = Language::from_str(crate::utils::language_from_locale())
+                        .unwrap_or(Language::English);
+                    [system_language, game_language()]
+                        .into_iter()
+                        .map(|language| language_word_list(language).len())
+                        .max()
+                        .unwrap_or_default()
+                });
+            let sizes = custom_word_lists::vocabulary_sizes(word_count);
+
+            let names: Vec<String> = sizes
+                .iter()
+                .map(|size| match size {
+                    0 => gettext("All Words"),
+                    // Translators: The `{}` block will be replaced with a number of words.
+                    // Do not translate it!
+                    size => i18n_fmt!(i18n_fmt("{} Most Common Words", size)),
+                })
+                .collect();
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+            // A size as big as the list uses all of it anyway
+            let settings = gio::Settings::new("io.github.nacho.mecalin");
+            let size = settings.uint("vocabulary-size");
+            let selected = sizes.iter().position(|s| *s == size).unwrap_or(0);
+
+            self.updating_vocabulary.set(true);
+            self.vocabulary_combo
+                .set_model(Some(&gtk::StringList::new(&names)));
+            self.vocabulary_combo.set_selected(selected as u32);
+            self.updating_vocabulary.set(false);
+            self.vocabulary_sizes.replace(sizes);
+        }
+
+        fn refresh_word_lists(&self) {
+            let names = custom_word_lists::list_names(&custom_word_lists::word_lists_dir());
+            let settings = gio::Settings::new("io.github.nacho.mecalin");
+            let active = settings.string("custom-word-list");
+
+            self.updating_word_lists.set(true);
+            let default_name = gettext("Language Default");
+            let entries: Vec<&str> = std::iter::once(default_name.as_str())
+                .chain(names.iter().map(String::as_str))
+                .collect();
+            self.word_list_combo
+                .set_model(Some(&gtk::StringList::new(&entries)));
+            let selected = names
+                .iter()
+                .position(|name| *name == active)
+                .map_or(0, |i| i + 1);
+            self.word_list_combo.set_selected(selected as u32);
+            self.updating_word_lists.set(false);
+
+            for row in self.word_list_rows.take() {
+                self.word_lists_group.remove(&row);
+            }
+
+            for name in &names {
+                let row = adw::ActionRow::builder().title(name).build();
+
+                let remove_button = gtk::Button::builder()
+                    .icon_name("user-trash-symbolic")
+                    .tooltip_text(gettext("Remove Word List"))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["flat"])
+                    .build();
+                remove_button.connect_clicked(glib::clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    #[strong]
+                    name,
+                    move |_| {
+                        imp.remove_word_list(&name);
+                    }
+                ));
+                row.add_suffix(&remove_button);
+
+                self.word_lists_group.add(&row);
+                self.word_list_rows.borrow_mut().push(row);
+            }
+
+            *self.word_list_names.borrow_mut() = names;
+            self.refresh_vocabulary();
+        }
+
+        fn remove_word_list(&self, name: &str) {
+            let settings = gio::Settings::new("io.github.nacho.mecalin");
+            if settings.string("custom-word-list") == name {
+                settings.set_string("custom-word-list", "").ok();
+            }
+
+            if let Err(e) = custom_word_lists::remove(&custom_word_lists::word_lists_dir(), name) {
+                self.show_word_list_error(&gettext("Could Not Remove Word List"), &e);
+            }
+            self.refresh_word_lists();
+        }
+
+        // Lets the user pick a text file and turns it into a new, active word list
+        fn choose_word_list_file(
+            &self,
+            title: &str,
+            create_list: fn(&Path, &Path) -> std::io::Result<String>,
+        ) {
+            let filter = gtk::FileFilter::new();
+            filter.set_name(Some(&gettext("Text Files")));
+            filter.add_mime_type("text/*");
+
+            let dialog = gtk::FileDialog::builder()
+                .title(title)
+                .modal(true)
+                .default_filter(&filter)
+                .build();
+            let window = self.obj().root().and_downcast::<gtk::Window>();
+
+            dialog.open(
+                window.as_ref(),
+                gio::Cancellable::NONE,
+                glib::clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |result| {
+                        // Cancelling the dialog is reported as an error too
+                        let Some(path) = result.ok().and_then(|file| file.path()) else {
+                            return;
+                        };
+
+                        match create_list(&custom_word_lists::word_lists_dir(), &path) {
+                            Ok(name) => {
+                                let settings = gio::Settings::new("io.github.nacho.mecalin");
+                                settings.set_string("custom-word-list", &name).ok();
+                                imp.refresh_word_lists();
+                            }
+                            Err(e) => {
+                                imp.show_word_list_error(&gettext("Could Not Create Word List"), &e)
+                            }
+                        }
+                    }
+                ),
+            );
+        }
+
+        fn show_word_list_error(&self, heading: &str, error: &std::io::Error) {
+            let dialog = adw::AlertDialog::new(Some(heading), Some(&error.to_string()));
+            dialog.add_response("close", &gettext("Close"));
+            dialog.present(Some(&*self.obj()));
+        }
     }
 }
 
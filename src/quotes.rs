@@ -0,0 +1,139 @@
+use include_dir::{include_dir, Dir, File};
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::text_generation::{Language, CHUNK_GRAPHEME_COUNT};
+
+// Quote corpora live in data/quotes/{lang_code}.json. Languages without one use the
+// English corpus, so unlike word lists a file is not required for every language.
+static EMBEDDED_QUOTE_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/data/quotes");
+const FALLBACK_LANG_CODE: &str = "en";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Quote {
+    pub text: String,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct QuoteText {
+    pub text: String,
+    // Sources of the quotes in the text, in the order they appear
+    pub sources: Vec<String>,
+}
+
+impl QuoteText {
+    pub fn attribution(&self) -> String {
+        self.sources.join(" · ")
+    }
+}
+
+fn corpus_file(lang_code: &str) -> &'static File<'static> {
+    // Regional variants like "de_CH" share the corpus of their base language
+    let base_code = lang_code.split('_').next().unwrap_or(lang_code);
+
+    [lang_code, base_code, FALLBACK_LANG_CODE]
+        .iter()
+        .find_map(|code| EMBEDDED_QUOTE_DIR.get_file(format!("{code}.json")))
+        .expect("fallback quote corpus exists")
+}
+
+// The language the quotes for `lang_code` are actually written in
+pub fn corpus_lang_code(lang_code: &str) -> &'static str {
+    corpus_file(lang_code)
+        .path()
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(FALLBACK_LANG_CODE)
+}
+
+pub fn quotes_from_lang_code(lang_code: &str) -> Result<Vec<Quote>, serde_json::Error> {
+    serde_json::from_slice(corpus_file(lang_code).contents())
+}
+
+// Real sentences in random order, with enough quotes to fill a chunk of text
+pub fn quotes(language: Language, seed: u64) -> QuoteText {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut corpus = quotes_from_lang_code(&language.to_string()).unwrap_or_default();
+    corpus.shuffle(&mut rng);
+
+    let mut quote_text = QuoteText::default();
+    let mut grapheme_count = 0;
+    for quote in corpus {
+        if grapheme_count >= CHUNK_GRAPHEME_COUNT {
+            break;
+        }
+
+        grapheme_count += quote.text.graphemes(true).count() + 1;
+        quote_text.text.push_str(&quote.text);
+        quote_text.text.push(' ');
+        if !quote_text.sources.contains(&quote.source) {
+            quote_text.sources.push(quote.source);
+        }
+    }
+
+    quote_text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Smaller corpora would repeat the same quotes in every test
+    const MIN_CORPUS_CHUNKS: usize = 3;
+
+    #[test]
+    fn test_bundled_corpora_are_valid() {
+        for file in EMBEDDED_QUOTE_DIR.files() {
+            let corpus: Vec<Quote> = serde_json::from_slice(file.contents())
+                .unwrap_or_else(|e| panic!("{}: {e}", file.path().display()));
+
+            assert!(!corpus.is_empty(), "{}", file.path().display());
+            for quote in &corpus {
+                assert!(!quote.text.trim().is_empty(), "{}", file.path().display());
+                assert!(!quote.source.trim().is_empty(), "{}", quote.text);
+                assert!(!quote.text.contains('\n'), "{}", quote.text);
+            }
+
+            let grapheme_count: usize = corpus
+                .iter()
+                .map(|quote| quote.text.graphemes(true).count() + 1)
+                .sum();
+            assert!(
+                grapheme_count >= MIN_CORPUS_CHUNKS * CHUNK_GRAPHEME_COUNT,
+                "{} has only {grapheme_count} graphemes",
+                file.path().display()
+            );
+        }
+    }
+
+    #[test]
+    fn test_quotes_are_seeded() {
+        let first = quotes(Language::English, 99);
+        let second = quotes(Language::English, 99);
+
+        assert_eq!(first.text, second.text);
+        assert_eq!(first.sources, second.sources);
+        assert!(first.text.graphemes(true).count() >= CHUNK_GRAPHEME_COUNT);
+    }
+
+    #[test]
+    fn test_quote_corpus_fallback() {
+        let swiss = quotes_from_lang_code("de_CH").unwrap();
+        assert_eq!(swiss[0].source, "Franz Kafka, Die Verwandlung");
+
+        let korean = quotes_from_lang_code("ko").unwrap();
+        assert_eq!(korean[0].source, "Jane Austen, Pride and Prejudice");
+
+        assert_eq!(corpus_lang_code("de_CH"), "de");
+        assert_eq!(corpus_lang_code("ko"), "en");
+        // No Galician or Polish quotes are bundled yet
+        assert_eq!(corpus_lang_code("gl"), "en");
+        assert_eq!(corpus_lang_code("pl"), "en");
+        for code in ["de", "es", "fr", "it", "pt"] {
+            assert_eq!(corpus_lang_code(code), code);
+        }
+    }
+}
@@ -0,0 +1,612 @@
+use gettextrs::gettext;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use gtk::{gio, glib};
+use i18n_format::i18n_fmt;
+use libadwaita as adw;
+use libadwaita::prelude::*;
+use rand::Rng;
+use std::cell::{Cell, RefCell};
+use std::str::FromStr;
+use std::time::Duration;
+use strum::IntoEnumIterator;
+use strum_macros::{Display as EnumDisplay, EnumIter, EnumString};
+
+use crate::custom_word_lists::vocabulary_size;
+use crate::game_engine::GameLoop;
+use crate::game_setup::game_language;
+use crate::pause::Pausable;
+use crate::pause_overlay::{self, PauseOverlay};
+use crate::speed_test_text_view::SpeedTestTextView;
+use crate::text_generation::{advanced, random_seed, Language};
+use crate::text_utils::calculate_wpm;
+
+const OPPONENT_SLOTS: usize = 3;
+pub const COUNTDOWN_SECONDS: f64 = 3.0;
+const PASSAGE_WORDS: usize = 30;
+// How often opponents speed up or slow down
+const PACE_CHANGE_SECONDS: f64 = 1.0;
+
+// Simulated racers, from the slowest to the fastest
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumString, EnumDisplay, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum OpponentProfile {
+    Beginner,
+    Casual,
+    Steady,
+    Fast,
+    Pro,
+}
+
+impl OpponentProfile {
+    pub fn wpm(&self) -> f64 {
+        match self {
+            OpponentProfile::Beginner => 20.0,
+            OpponentProfile::Casual => 35.0,
+            OpponentProfile::Steady => 50.0,
+            OpponentProfile::Fast => 70.0,
+            OpponentProfile::Pro => 95.0,
+        }
+    }
+
+    // How far the pace strays from the average, slower racers being less regular
+    pub fn variation(&self) -> f64 {
+        match self {
+            OpponentProfile::Beginner => 0.4,
+            OpponentProfile::Casual => 0.3,
+            OpponentProfile::Steady => 0.2,
+            OpponentProfile::Fast => 0.15,
+            OpponentProfile::Pro => 0.1,
+        }
+    }
+
+    pub fn ui_string(&self) -> String {
+        let name = match self {
+            OpponentProfile::Beginner => gettext("Beginner"),
+            OpponentProfile::Casual => gettext("Casual"),
+            OpponentProfile::Steady => gettext("Steady"),
+            OpponentProfile::Fast => gettext("Fast"),
+            OpponentProfile::Pro => gettext("Pro"),
+        };
+        // Translators: The first `{}` block will be replaced with the name of an opponent,
+        // and the second with its typing speed. Do not translate them!
+        i18n_fmt! { i18n_fmt("{} · {} WPM", name, self.wpm()) }
+    }
+}
+
+// Graphemes per second typed at a speed in words per minute
+fn graphemes_per_second(wpm: f64) -> f64 {
+    wpm * 5.0 / 60.0
+}
+
+// A simulated racer typing the passage at its profile's speed
+#[derive(Clone, Debug)]
+pub struct Opponent {
+    pub profile: OpponentProfile,
+    // Graphemes typed so far
+    pub position: f64,
+    // Seconds after the start the passage was finished
+    pub finish_time: Option<f64>,
+    pace: f64,
+    until_pace_change: f64,
+}
+
+impl Opponent {
+    pub fn new(profile: OpponentProfile) -> Self {
+        Self {
+            profile,
+            position: 0.0,
+            finish_time: None,
+            pace: 1.0,
+            until_pace_change: 0.0,
+        }
+    }
+
+    // Moves `dt` seconds ahead in a race that has been going on for `elapsed` seconds
+    pub fn advance(&mut self, dt: f64, elapsed: f64, length: usize, rng: &mut impl Rng) {
+        if self.finish_time.is_some() {
+            return;
+        }
+
+        self.until_pace_change -= dt;
+        if self.until_pace_change <= 0.0 {
+            let variation = self.profile.variation();
+            self.pace = rng.gen_range(1.0 - variation..=1.0 + variation);
+            self.until_pace_change += PACE_CHANGE_SECONDS;
+        }
+
+        self.position += graphemes_per_second(self.profile.wpm()) * self.pace * dt;
+        if self.position >= length as f64 {
+            self.position = length as f64;
+            self.finish_time = Some(elapsed);
+        }
+    }
+}
+
+// Place of someone who finished after `time` seconds, counting the opponents ahead
+pub fn placement(opponents: &[Opponent], time: f64) -> usize {
+    1 + opponents
+        .iter()
+        .filter(|opponent| opponent.finish_time.is_some_and(|finish| finish < time))
+        .count()
+}
+
+// A few sentences of the advanced text, cut at a word
+pub fn race_passage(language: Language, vocabulary: Option<usize>, seed: u64) -> String {
+    advanced(language, vocabulary, seed)
+        .split_whitespace()
+        .take(PASSAGE_WORDS)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn format_time(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// A racer's row, with how far along the passage they are
+pub struct Track {
+    progress_bar: gtk::ProgressBar,
+    result_label: gtk::Label,
+}
+
+impl Track {
+    pub fn new(list: &gtk::ListBox, name: &str) -> Self {
+        let row = adw::ActionRow::builder().title(name).build();
+        let progress_bar = gtk::ProgressBar::builder()
+            .valign(gtk::Align::Center)
+            .width_request(300)
+            .build();
+        let result_label = gtk::Label::builder().width_chars(5).build();
+        result_label.add_css_class("numeric");
+        row.add_suffix(&progress_bar);
+        row.add_suffix(&result_label);
+        list.append(&row);
+
+        Self {
+            progress_bar,
+            result_label,
+        }
+    }
+
+    pub fn update(&self, fraction: f64, finish_time: Option<f64>) {
+        self.progress_bar.set_fraction(fraction.clamp(0.0, 1.0));
+        self.result_label
+            .set_text(&finish_time.map(format_time).unwrap_or_default());
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/race_game.ui")]
+    pub struct RaceGame {
+        #[template_child]
+        pub setup_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub opponents_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub start_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub tracks_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub countdown_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub text_view: TemplateChild<SpeedTestTextView>,
+        #[template_child]
+        pub pause_overlay: TemplateChild<PauseOverlay>,
+        #[template_child]
+        pub results_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub placement_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub wpm_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub setup_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub restart_button: TemplateChild<gtk::Button>,
+
+        pub opponent_rows: RefCell<Vec<adw::ComboRow>>,
+        pub opponents: RefCell<Vec<Opponent>>,
+        // The player's track comes first
+        pub(super) tracks: RefCell<Vec<Track>>,
+        pub countdown: Cell<f64>,
+        // Seconds since the start, without the pauses
+        pub elapsed: Cell<f64>,
+        pub running: Cell<bool>,
+        pub paused: Cell<bool>,
+        pub game_loop: GameLoop,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RaceGame {
+        const NAME: &'static str = "RaceGame";
+        type Type = super::RaceGame;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            SpeedTestTextView::ensure_type();
+            PauseOverlay::ensure_type();
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for RaceGame {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_game();
+        }
+    }
+    impl WidgetImpl for RaceGame {}
+    impl BoxImpl for RaceGame {}
+}
+
+glib::wrapper! {
+    pub struct RaceGame(ObjectSubclass<imp::RaceGame>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl RaceGame {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn setup_game(&self) {
+        let imp = self.imp();
+        self.setup_opponent_rows();
+
+        let obj = self.downgrade();
+        imp.start_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.start_race();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.restart_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.start_race();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.setup_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.reset();
+            }
+        });
+
+        imp.text_view.connect_closure(
+            "typed-text-changed",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = game)]
+                self,
+                move |_text_view: SpeedTestTextView| {
+                    game.update_player();
+                }
+            ),
+        );
+
+        let obj = self.downgrade();
+        imp.pause_overlay.connect_resume(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.resume();
+            }
+        });
+        pause_overlay::add_escape_controller(self);
+    }
+
+    // One row per opponent slot, each either empty or with a profile
+    fn setup_opponent_rows(&self) {
+        let imp = self.imp();
+        let settings = gio::Settings::new("io.github.nacho.mecalin");
+        let saved = settings.strv("race-opponents");
+
+        let mut names = vec![gettext("None")];
+        names.extend(OpponentProfile::iter().map(|profile| profile.ui_string()));
+        let name_strs: Vec<&str> = names.iter().map(String::as_str).collect();
+
+        let mut rows = Vec::new();
+        for slot in 0..OPPONENT_SLOTS {
+            let row = adw::ComboRow::new();
+            row.set_title(&i18n_fmt! { i18n_fmt("Opponent {}", slot + 1) });
+            row.set_model(Some(&gtk::StringList::new(&name_strs)));
+
+            // The first entry is no opponent
+            let position = saved
+                .get(slot)
+                .and_then(|name| OpponentProfile::from_str(name).ok())
+                .and_then(|profile| OpponentProfile::iter().position(|p| p == profile))
+                .map_or(0, |position| position + 1);
+            row.set_selected(position as u32);
+
+            let obj = self.downgrade();
+            row.connect_selected_notify(move |_| {
+                if let Some(obj) = obj.upgrade() {
+                    obj.save_opponents();
+                }
+            });
+            imp.opponents_list.append(&row);
+            rows.push(row);
+        }
+        imp.opponent_rows.replace(rows);
+    }
+
+    fn selected_profiles(&self) -> Vec<Option<OpponentProfile>> {
+        self.imp()
+            .opponent_rows
+            .borrow()
+            .iter()
+            .map(|row| {
+                (row.selected() as usize)
+                    .checked_sub(1)
+                    .and_then(|index| OpponentProfile::iter().nth(index))
+            })
+            .collect()
+    }
+
+    fn save_opponents(&self) {
+        let names: Vec<String> = self
+            .selected_profiles()
+            .iter()
+            .map(|profile| profile.map_or("none".to_string(), |profile| profile.to_string()))
+            .collect();
+        let settings = gio::Settings::new("io.github.nacho.mecalin");
+        settings.set_strv("race-opponents", names).ok();
+    }
+
+    fn start_game_loop(&self) {
+        let obj = self.downgrade();
+        self.imp()
+            .game_loop
+            .start(self, move |dt| match obj.upgrade() {
+                Some(obj) => obj.update_race(dt),
+                None => glib::ControlFlow::Break,
+            });
+    }
+
+    // Goes back to choosing the opponents
+    pub fn reset(&self) {
+        let imp = self.imp();
+        imp.running.set(false);
+        imp.paused.set(false);
+        imp.text_view.set_running(false);
+        imp.text_view.set_accepts_input(false);
+        imp.text_view.set_visible(false);
+        imp.tracks_list.set_visible(false);
+        imp.countdown_label.set_visible(false);
+        imp.pause_overlay.set_visible(false);
+        imp.results_box.set_visible(false);
+        imp.setup_box.set_visible(true);
+        imp.start_button.grab_focus();
+    }
+
+    fn start_race(&self) {
+        let imp = self.imp();
+
+        let passage = race_passage(game_language(), vocabulary_size(), random_seed());
+        imp.text_view.reset();
+        imp.text_view.set_original_text(&passage);
+        imp.text_view.set_accepts_input(false);
+        imp.text_view.set_visible(true);
+
+        let opponents: Vec<Opponent> = self
+            .selected_profiles()
+            .into_iter()
+            .flatten()
+            .map(Opponent::new)
+            .collect();
+
+        imp.tracks_list.remove_all();
+        let mut tracks = vec![Track::new(&imp.tracks_list, &gettext("You"))];
+        for opponent in &opponents {
+            tracks.push(Track::new(&imp.tracks_list, &opponent.profile.ui_string()));
+        }
+        imp.tracks.replace(tracks);
+        imp.opponents.replace(opponents);
+
+        imp.countdown.set(COUNTDOWN_SECONDS);
+        imp.elapsed.set(0.0);
+        imp.running.set(true);
+        imp.paused.set(false);
+
+        imp.setup_box.set_visible(false);
+        imp.results_box.set_visible(false);
+        imp.pause_overlay.set_visible(false);
+        imp.tracks_list.set_visible(true);
+        imp.countdown_label.set_visible(true);
+        self.update_tracks();
+        self.start_game_loop();
+    }
+
+    // Counts down to the start, then moves the opponents along
+    fn update_race(&self, dt: f64) -> glib::ControlFlow {
+        let imp = self.imp();
+        if !imp.running.get() {
+            return glib::ControlFlow::Break;
+        }
+        if imp.paused.get() {
+            return glib::ControlFlow::Continue;
+        }
+
+        let countdown = imp.countdown.get();
+        if countdown > 0.0 {
+            let countdown = countdown - dt;
+            imp.countdown.set(countdown);
+            if countdown > 0.0 {
+                imp.countdown_label
+                    .set_text(&(countdown.ceil() as u32).to_string());
+            } else {
+                imp.countdown_label.set_visible(false);
+                imp.text_view.set_accepts_input(true);
+                imp.text_view.set_running(true);
+                imp.text_view.grab_focus();
+            }
+            return glib::ControlFlow::Continue;
+        }
+
+        let elapsed = imp.elapsed.get() + dt;
+        imp.elapsed.set(elapsed);
+        let length = imp.text_view.original_grapheme_count();
+        let mut rng = rand::thread_rng();
+        for opponent in imp.opponents.borrow_mut().iter_mut() {
+            opponent.advance(dt, elapsed, length, &mut rng);
+        }
+        self.update_tracks();
+
+        glib::ControlFlow::Continue
+    }
+
+    fn update_tracks(&self) {
+        let imp = self.imp();
+        let length = imp.text_view.original_grapheme_count().max(1) as f64;
+        let tracks = imp.tracks.borrow();
+
+        if let Some(player_track) = tracks.first() {
+            let typed = imp.text_view.correct_grapheme_count() as f64;
+            player_track.update(typed / length, None);
+        }
+        for (track, opponent) in tracks.iter().skip(1).zip(imp.opponents.borrow().iter()) {
+            track.update(opponent.position / length, opponent.finish_time);
+        }
+    }
+
+    fn update_player(&self) {
+        let imp = self.imp();
+        if !imp.running.get() {
+            return;
+        }
+        self.update_tracks();
+
+        // Mistakes have to be fixed before crossing the line
+        if imp.text_view.correct_grapheme_count() >= imp.text_view.original_grapheme_count() {
+            self.finish_race();
+        }
+    }
+
+    fn finish_race(&self) {
+        let imp = self.imp();
+        imp.running.set(false);
+        imp.text_view.set_running(false);
+        imp.text_view.set_accepts_input(false);
+        imp.text_view.set_visible(false);
+
+        let elapsed = imp.elapsed.get();
+        if let Some(player_track) = imp.tracks.borrow().first() {
+            player_track.update(1.0, Some(elapsed));
+        }
+
+        let opponents = imp.opponents.borrow();
+        let place = placement(&opponents, elapsed);
+        let racers = opponents.len() + 1;
+        if place == 1 {
+            imp.placement_label.set_text(&gettext("You Won!"));
+        } else {
+            // Translators: The first `{}` block will be replaced with the player's place,
+            // and the second with the number of racers. Do not translate them!
+            imp.placement_label
+                .set_text(&i18n_fmt! { i18n_fmt("Place {} of {}", place, racers) });
+        }
+
+        let wpm = calculate_wpm(
+            Duration::from_secs_f64(elapsed),
+            &imp.text_view.original_text(),
+            &imp.text_view.typed_text(),
+        );
+        let wpm = wpm.floor() as u32;
+        imp.wpm_label
+            .set_text(&i18n_fmt! { i18n_fmt("{} WPM", wpm) });
+        imp.results_box.set_visible(true);
+        imp.restart_button.grab_focus();
+    }
+}
+
+// A race can be paused from the countdown on, until the player finishes
+impl Pausable for RaceGame {
+    fn pause(&self) {
+        let imp = self.imp();
+        if !self.can_pause() || self.is_paused() {
+            return;
+        }
+
+        imp.paused.set(true);
+        imp.text_view.set_running(false);
+        imp.text_view.set_accepts_input(false);
+        imp.text_view.set_visible(false);
+        imp.pause_overlay.show();
+    }
+
+    fn resume(&self) {
+        let imp = self.imp();
+        if !self.is_paused() {
+            return;
+        }
+
+        imp.paused.set(false);
+        imp.pause_overlay.set_visible(false);
+        imp.text_view.set_visible(true);
+        // Typing only starts once the countdown is over
+        if imp.countdown.get() <= 0.0 {
+            imp.text_view.set_running(true);
+            imp.text_view.set_accepts_input(true);
+            imp.text_view.grab_focus();
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.imp().paused.get()
+    }
+
+    fn can_pause(&self) -> bool {
+        self.imp().running.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_opponent_pace() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut opponent = Opponent::new(OpponentProfile::Steady);
+
+        // 50 WPM is about 250 graphemes a minute
+        let mut elapsed = 0.0;
+        while opponent.finish_time.is_none() && elapsed < 120.0 {
+            elapsed += 0.1;
+            opponent.advance(0.1, elapsed, 250, &mut rng);
+        }
+        let finish_time = opponent.finish_time.unwrap();
+        assert!((50.0..70.0).contains(&finish_time), "{finish_time}");
+        assert_eq!(opponent.position, 250.0);
+
+        // Finished racers stay put
+        opponent.advance(1.0, elapsed + 1.0, 250, &mut rng);
+        assert_eq!(opponent.finish_time, Some(finish_time));
+    }
+
+    #[test]
+    fn test_placement() {
+        let mut fast = Opponent::new(OpponentProfile::Pro);
+        fast.finish_time = Some(30.0);
+        let mut slow = Opponent::new(OpponentProfile::Casual);
+        slow.finish_time = Some(80.0);
+        let unfinished = Opponent::new(OpponentProfile::Beginner);
+        let opponents = [fast, slow, unfinished];
+
+        assert_eq!(placement(&opponents, 20.0), 1);
+        assert_eq!(placement(&opponents, 45.0), 2);
+        assert_eq!(placement(&opponents, 90.0), 3);
+    }
+}
@@ -1,19 +1,80 @@
+use gettextrs::gettext;
 use gtk::gdk;
+use gtk::gio;
 use gtk::glib;
 use gtk::graphene;
 use gtk::pango;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
 use i18n_format::i18n_fmt;
-use rand::seq::SliceRandom;
+use libadwaita as adw;
+use libadwaita::prelude::*;
 use rand::Rng;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-#[derive(Clone)]
-pub(crate) struct ScrollingText {
-    text: String,
-    x: f64,
+use crate::game_engine::{ArcadeGame, Difficulty, DifficultyCurve, Entity, GameState, GameWidgets};
+use crate::game_setup::{self, GameSetup};
+use crate::high_scores::Game;
+use crate::pause::Pausable;
+use crate::pause_overlay::{self, PauseOverlay};
+use crate::sounds::{self, Sound};
+use crate::text_generation::WordList;
+
+// Where words scroll out of sight on the left
+const LANE_END: f64 = -200.0;
+
+// How fast words scroll, in pixels per second, and how often they appear
+fn difficulty_curve(difficulty: Difficulty) -> DifficultyCurve {
+    match difficulty {
+        Difficulty::Easy => DifficultyCurve {
+            start_speed: 30.0,
+            speed_step: 5.0,
+            points_per_level: 10,
+            spawn_interval: 2.5,
+            spawn_interval_step: 0.1,
+            min_spawn_interval: 1.5,
+            max_objects: 6,
+        },
+        Difficulty::Normal => DifficultyCurve {
+            start_speed: 40.0,
+            speed_step: 10.0,
+            points_per_level: 10,
+            spawn_interval: 2.0,
+            spawn_interval_step: 0.1,
+            min_spawn_interval: 1.0,
+            max_objects: 8,
+        },
+        Difficulty::Hard => DifficultyCurve {
+            start_speed: 60.0,
+            speed_step: 15.0,
+            points_per_level: 10,
+            spawn_interval: 1.5,
+            spawn_interval_step: 0.1,
+            min_spawn_interval: 0.7,
+            max_objects: 12,
+        },
+    }
+}
+
+// Words move along their lane, so only `x` changes
+pub(crate) type ScrollingText = Entity<String>;
+
+// Lane and position of the word closest to the end of its lane that starts with `c`,
+// among the ones already scrolled into sight
+fn find_target(texts: &[Vec<ScrollingText>], c: char, width: f64) -> Option<(usize, usize)> {
+    texts
+        .iter()
+        .enumerate()
+        .flat_map(|(lane, lane_texts)| {
+            lane_texts
+                .iter()
+                .enumerate()
+                .map(move |(pos, text)| (lane, pos, text))
+        })
+        .filter(|(_, _, text)| text.x < width && text.value.starts_with(c))
+        .min_by(|(_, _, a), (_, _, b)| a.x.total_cmp(&b.x))
+        .map(|(lane, pos, _)| (lane, pos))
 }
 
 mod lane_widget {
@@ -82,8 +143,8 @@ mod lane_widget {
 
             if let Ok(all_texts) = self.texts.borrow().try_borrow() {
                 for text in &all_texts[lane_index] {
-                    if text.x < width as f64 && text.x > -200.0 {
-                        layout.set_text(&text.text);
+                    if text.x < width as f64 && text.x > LANE_END {
+                        layout.set_text(&text.value);
                         snapshot.save();
                         snapshot.translate(&graphene::Point::new(text.x as f32, 30.0));
                         snapshot.append_layout(&layout, &text_color);
@@ -134,7 +195,7 @@ impl LaneWidget {
 mod imp {
     use super::*;
 
-    #[derive(gtk::CompositeTemplate)]
+    #[derive(Default, gtk::CompositeTemplate)]
     #[template(resource = "/io/github/nacho/mecalin/ui/scrolling_lanes_game.ui")]
     pub struct ScrollingLanesGame {
         #[template_child]
@@ -144,6 +205,8 @@ mod imp {
         #[template_child]
         pub level_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub status_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub results_box: TemplateChild<gtk::Box>,
         #[template_child]
         pub results_score_label: TemplateChild<gtk::Label>,
@@ -151,49 +214,25 @@ mod imp {
         pub results_level_label: TemplateChild<gtk::Label>,
         #[template_child]
         pub restart_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub setup_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub new_record_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub pause_overlay: TemplateChild<PauseOverlay>,
+        #[template_child]
+        pub game_setup: TemplateChild<GameSetup>,
 
         pub lanes: Rc<RefCell<Vec<LaneWidget>>>,
         pub(crate) lane_texts: Rc<RefCell<Vec<Vec<ScrollingText>>>>,
         pub current_lane: Rc<RefCell<usize>>,
-        pub score: RefCell<u32>,
-        pub difficulty: RefCell<u32>,
-        pub speed: RefCell<f64>,
-        pub game_over: RefCell<bool>,
-        pub game_loop_running: RefCell<bool>,
+        // Lane whose first word is being typed, when auto-targeting
+        pub target: RefCell<Option<usize>>,
+        pub state: GameState,
         pub lanes_container: RefCell<Option<gtk::Box>>,
-        pub word_list: RefCell<Vec<String>>,
-    }
-
-    impl Default for ScrollingLanesGame {
-        fn default() -> Self {
-            use std::str::FromStr;
-
-            let lang_code = crate::utils::language_from_locale();
-            let language = crate::text_generation::Language::from_str(lang_code)
-                .unwrap_or(crate::text_generation::Language::English);
-            let text = crate::text_generation::simple(language);
-            let word_list: Vec<String> = text.split_whitespace().map(|s| s.to_string()).collect();
-
-            Self {
-                game_area: Default::default(),
-                score_label: Default::default(),
-                level_label: Default::default(),
-                results_box: Default::default(),
-                results_score_label: Default::default(),
-                results_level_label: Default::default(),
-                restart_button: Default::default(),
-                lanes: Default::default(),
-                lane_texts: Default::default(),
-                current_lane: Default::default(),
-                score: Default::default(),
-                difficulty: Default::default(),
-                speed: Default::default(),
-                game_over: Default::default(),
-                game_loop_running: Default::default(),
-                lanes_container: Default::default(),
-                word_list: RefCell::new(word_list),
-            }
-        }
+        pub auto_target: RefCell<bool>,
+        // Loaded when a game starts rather than with the window
+        pub word_list: RefCell<WordList>,
     }
 
     #[glib::object_subclass]
@@ -203,6 +242,8 @@ mod imp {
         type ParentType = gtk::Box;
 
         fn class_init(klass: &mut Self::Class) {
+            PauseOverlay::ensure_type();
+            GameSetup::ensure_type();
             klass.bind_template();
         }
 
@@ -235,44 +276,27 @@ impl ScrollingLanesGame {
     fn setup_game(&self) {
         let imp = self.imp();
 
-        // Create 4 lanes
-        let lanes_container = gtk::Box::new(gtk::Orientation::Vertical, 2);
-        lanes_container.set_vexpand(true);
-        lanes_container.set_hexpand(true);
-
-        // Query colors
-        let temp_widget = gtk::Label::new(None);
-        temp_widget.add_css_class("lane-background");
-        let bg_color = temp_widget.color();
-        temp_widget.remove_css_class("lane-background");
-
-        temp_widget.add_css_class("lane-current");
-        let current_color = temp_widget.color();
-        temp_widget.remove_css_class("lane-current");
-
-        temp_widget.add_css_class("lane-text");
-        let text_color = temp_widget.color();
-
-        let mut lanes = Vec::new();
-        let lane_texts = vec![Vec::new(), Vec::new(), Vec::new(), Vec::new()];
-        imp.lane_texts.replace(lane_texts);
-
-        for i in 0..4 {
-            let lane = LaneWidget::new(i, imp.current_lane.clone(), imp.lane_texts.clone());
-            lane.set_colors(bg_color, current_color, text_color);
-            lanes_container.append(&lane);
-            lanes.push(lane);
-        }
-
-        imp.game_area.append(&lanes_container);
-        imp.lanes.replace(lanes);
-        imp.lanes_container.replace(Some(lanes_container));
+        self.setup_options();
 
         // Setup restart button
         let obj = self.downgrade();
         imp.restart_button.connect_clicked(move |_| {
             if let Some(obj) = obj.upgrade() {
-                obj.restart_game();
+                obj.start_game();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.setup_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.reset();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.game_setup.connect_start(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.start_game();
             }
         });
 
@@ -287,6 +311,14 @@ impl ScrollingLanesGame {
         });
         self.add_controller(key_controller);
 
+        let obj = self.downgrade();
+        imp.pause_overlay.connect_resume(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.resume();
+            }
+        });
+        pause_overlay::add_escape_controller(self);
+
         self.set_can_focus(true);
         self.set_focusable(true);
 
@@ -297,103 +329,85 @@ impl ScrollingLanesGame {
                 obj.grab_focus();
             }
         });
-
-        // Start game loop
-        self.start_game_loop();
     }
 
-    fn start_game_loop(&self) {
+    // Lane count, language and targeting, chosen along with the mode
+    fn setup_options(&self) {
         let imp = self.imp();
-
-        if *imp.game_loop_running.borrow() {
-            return;
-        }
-        *imp.game_loop_running.borrow_mut() = true;
-
-        // Update game
-        let obj = self.downgrade();
-        glib::timeout_add_local(std::time::Duration::from_millis(50), move || {
-            if let Some(obj) = obj.upgrade() {
-                if *obj.imp().game_over.borrow() {
-                    *obj.imp().game_loop_running.borrow_mut() = false;
-                    return glib::ControlFlow::Break;
-                }
-                obj.update_game();
-                glib::ControlFlow::Continue
-            } else {
-                glib::ControlFlow::Break
-            }
-        });
-
-        // Spawn new texts
-        let obj = self.downgrade();
-        glib::timeout_add_local(std::time::Duration::from_millis(2000), move || {
-            if let Some(obj) = obj.upgrade() {
-                if *obj.imp().game_over.borrow() {
-                    return glib::ControlFlow::Break;
-                }
-                obj.spawn_text();
-                glib::ControlFlow::Continue
-            } else {
-                glib::ControlFlow::Break
-            }
-        });
+        let settings = gio::Settings::new("io.github.nacho.mecalin");
+
+        let lanes_row = adw::SpinRow::with_range(2.0, 6.0, 1.0);
+        lanes_row.set_title(&gettext("Lanes"));
+        settings.bind("lanes-count", &lanes_row, "value").build();
+        imp.game_setup.add_row(&lanes_row);
+
+        imp.game_setup.add_language_row();
+
+        let auto_target_row = adw::SwitchRow::new();
+        auto_target_row.set_title(&gettext("Auto-Target"));
+        auto_target_row.set_subtitle(&gettext(
+            "Type the first letter of any word to aim at it, instead of switching lanes with the arrow keys",
+        ));
+        settings
+            .bind("lanes-auto-target", &auto_target_row, "active")
+            .build();
+        imp.game_setup.add_row(&auto_target_row);
     }
 
-    fn spawn_text(&self) {
+    // Replaces the lanes when their number changed since the last game
+    fn build_lanes(&self, count: usize) {
         let imp = self.imp();
-        let mut rng = rand::thread_rng();
-
-        let lane_index = rng.gen_range(0..4);
-        let word = imp
-            .word_list
-            .borrow()
-            .choose(&mut rng)
-            .expect("word list contains at least 1 word")
-            .clone();
+        if imp.lanes.borrow().len() == count {
+            return;
+        }
 
-        if let Some(lane) = imp.lanes.borrow().get(lane_index) {
-            let width = lane.width() as f64;
-            imp.lane_texts.borrow_mut()[lane_index].push(ScrollingText {
-                text: word,
-                x: width,
-            });
+        if let Some(old) = imp.lanes_container.take() {
+            imp.game_area.remove(&old);
         }
-    }
 
-    fn update_game(&self) {
-        let imp = self.imp();
-        let speed = *imp.speed.borrow();
+        let lanes_container = gtk::Box::new(gtk::Orientation::Vertical, 2);
+        lanes_container.set_vexpand(true);
+        lanes_container.set_hexpand(true);
+        lanes_container.set_visible(false);
 
-        let mut texts = imp.lane_texts.borrow_mut();
-        let lanes = imp.lanes.borrow();
+        // Query colors
+        let temp_widget = gtk::Label::new(None);
+        temp_widget.add_css_class("lane-background");
+        let bg_color = temp_widget.color();
+        temp_widget.remove_css_class("lane-background");
 
-        for (lane_index, lane_texts) in texts.iter_mut().enumerate() {
-            for text in lane_texts.iter_mut() {
-                text.x -= speed;
-            }
+        temp_widget.add_css_class("lane-current");
+        let current_color = temp_widget.color();
+        temp_widget.remove_css_class("lane-current");
 
-            // Check for game over
-            if lane_texts.iter().any(|t| t.x < -200.0) {
-                *imp.game_over.borrow_mut() = true;
-                drop(texts);
-                drop(lanes);
-                self.show_game_over();
-                return;
-            }
+        temp_widget.add_css_class("lane-text");
+        let text_color = temp_widget.color();
 
-            // Queue redraw
-            if let Some(lane) = lanes.get(lane_index) {
-                lane.queue_draw();
-            }
+        let mut lanes = Vec::new();
+        imp.lane_texts.replace(vec![Vec::new(); count]);
+
+        for i in 0..count {
+            let lane = LaneWidget::new(i, imp.current_lane.clone(), imp.lane_texts.clone());
+            lane.set_colors(bg_color, current_color, text_color);
+            lanes_container.append(&lane);
+            lanes.push(lane);
         }
+
+        imp.game_area.append(&lanes_container);
+        imp.lanes.replace(lanes);
+        imp.lanes_container.replace(Some(lanes_container));
     }
 
     fn handle_key_press(&self, key: gtk::gdk::Key) {
         let imp = self.imp();
+        if *imp.state.paused.borrow() || *imp.state.game_over.borrow() {
+            return;
+        }
         let key_name = key.name();
+        // Lanes are picked by typing when auto-targeting
+        let auto_target = *imp.auto_target.borrow();
 
-        if key_name.as_deref() == Some("Up") {
+        if !auto_target && key_name.as_deref() == Some("Up") {
             {
                 let mut current = imp.current_lane.borrow_mut();
                 if *current > 0 {
@@ -404,10 +418,11 @@ impl ScrollingLanesGame {
             for lane in imp.lanes.borrow().iter() {
                 lane.queue_draw();
             }
-        } else if key_name.as_deref() == Some("Down") {
+        } else if !auto_target && key_name.as_deref() == Some("Down") {
             {
+                let last_lane = imp.lanes.borrow().len().saturating_sub(1);
                 let mut current = imp.current_lane.borrow_mut();
-                if *current < 3 {
+                if *current < last_lane {
                     *current += 1;
                 }
             }
@@ -427,6 +442,10 @@ impl ScrollingLanesGame {
 
     fn handle_typing(&self, c: char) {
         let imp = self.imp();
+        if *imp.auto_target.borrow() {
+            self.handle_targeted_typing(c);
+            return;
+        }
         let current_lane = *imp.current_lane.borrow();
 
         let (found, score_changed) = {
@@ -434,14 +453,15 @@ impl ScrollingLanesGame {
 
             if let Some(lane_texts) = texts.get_mut(current_lane) {
                 // Find leftmost text that starts with this character
-                if let Some(pos) = lane_texts.iter().position(|t| t.text.starts_with(c)) {
+                if let Some(pos) = lane_texts.iter().position(|t| t.value.starts_with(c)) {
                     // Remove first character from the text
-                    let text = &mut lane_texts[pos].text;
+                    let text = &mut lane_texts[pos].value;
                     text.remove(0);
 
                     // If text is now empty, remove it completely
                     if text.is_empty() {
                         lane_texts.remove(pos);
+                        sounds::play(Sound::Destroyed);
                     }
 
                     (true, true)
@@ -454,90 +474,223 @@ impl ScrollingLanesGame {
         };
 
         if score_changed {
-            let mut score = imp.score.borrow_mut();
-            if found {
-                *score += 1;
-                let score_text = i18n_fmt! { i18n_fmt("Score: {}", *score) };
-                imp.score_label.set_text(&score_text);
-
-                if (*score).is_multiple_of(10) {
-                    let mut difficulty = imp.difficulty.borrow_mut();
-                    *difficulty += 1;
-                    let level_text = i18n_fmt! { i18n_fmt("Level: {}", *difficulty) };
-                    imp.level_label.set_text(&level_text);
-
-                    let mut speed = imp.speed.borrow_mut();
-                    *speed += 0.5;
-                }
-            } else if *score > 0 {
-                *score -= 1;
-                let score_text = i18n_fmt! { i18n_fmt("Score: {}", *score) };
-                imp.score_label.set_text(&score_text);
-            }
+            self.score_key(found);
         }
     }
 
-    fn show_game_over(&self) {
+    // Typing the first letter of a word locks onto it until it's finished, wherever it is
+    fn handle_targeted_typing(&self, c: char) {
         let imp = self.imp();
-        *imp.game_over.borrow_mut() = true;
+        let width = imp
+            .lanes
+            .borrow()
+            .first()
+            .map_or(0.0, |lane| lane.width() as f64);
 
-        if let Some(lanes) = imp.lanes_container.borrow().as_ref() {
-            lanes.set_visible(false);
-        }
+        let found = {
+            let mut texts = imp.lane_texts.borrow_mut();
+            let mut target = imp.target.borrow_mut();
+
+            // The targeted word is kept first in its lane
+            if target.is_none() {
+                if let Some((lane, pos)) = find_target(&texts, c, width) {
+                    let text = texts[lane].remove(pos);
+                    texts[lane].insert(0, text);
+                    *target = Some(lane);
+                    *imp.current_lane.borrow_mut() = lane;
+                }
+            }
+
+            match *target {
+                Some(lane) if texts[lane].first().is_some_and(|t| t.value.starts_with(c)) => {
+                    let text = &mut texts[lane][0].value;
+                    text.remove(0);
+                    if text.is_empty() {
+                        texts[lane].remove(0);
+                        *target = None;
+                        sounds::play(Sound::Destroyed);
+                    }
+                    true
+                }
+                _ => false,
+            }
+        };
 
-        imp.results_score_label
-            .set_text(&imp.score.borrow().to_string());
-        imp.results_level_label
-            .set_text(&imp.difficulty.borrow().to_string());
-        imp.results_box.set_visible(true);
+        self.score_key(found);
     }
 
-    fn restart_game(&self) {
+    fn score_key(&self, correct: bool) {
         let imp = self.imp();
+        imp.state.stats.borrow_mut().key_pressed(correct);
+
+        let mut progress = imp.state.progress.borrow_mut();
+        if correct {
+            // Words scroll faster and appear more often with every level
+            if progress.add_point() {
+                imp.state
+                    .spawner
+                    .borrow_mut()
+                    .set_interval(progress.spawn_interval());
+                sounds::play(Sound::LevelUp);
+            }
+        } else {
+            progress.lose_point();
+            sounds::play(Sound::Error);
+        }
+        drop(progress);
+        self.update_labels();
+    }
+}
+
+impl ArcadeGame for ScrollingLanesGame {
+    const GAME: Game = Game::ScrollingLanes;
 
-        imp.results_box.set_visible(false);
+    fn state(&self) -> &GameState {
+        &self.imp().state
+    }
 
-        if let Some(lanes) = imp.lanes_container.borrow().as_ref() {
-            lanes.set_visible(true);
+    fn widgets(&self) -> GameWidgets {
+        let imp = self.imp();
+        GameWidgets {
+            game_setup: imp.game_setup.get(),
+            pause_overlay: imp.pause_overlay.get(),
+            labels: vec![imp.score_label.get(), imp.level_label.get()],
+            status_label: imp.status_label.get(),
+            results_box: imp.results_box.get(),
+            results_score_label: imp.results_score_label.get(),
+            results_level_label: imp.results_level_label.get(),
+            new_record_label: imp.new_record_label.get(),
         }
+    }
 
-        self.grab_focus();
-        self.reset();
+    fn curve(difficulty: Difficulty) -> DifficultyCurve {
+        difficulty_curve(difficulty)
     }
 
-    pub fn reset(&self) {
+    fn prepare_game(&self) {
         let imp = self.imp();
-        imp.lane_texts
+        let settings = gio::Settings::new("io.github.nacho.mecalin");
+
+        self.build_lanes(settings.uint("lanes-count") as usize);
+        *imp.current_lane.borrow_mut() = 0;
+        *imp.target.borrow_mut() = None;
+        *imp.auto_target.borrow_mut() = settings.boolean("lanes-auto-target");
+        // The word list may have been changed in preferences since the last game
+        *imp.word_list.borrow_mut() = game_setup::game_words();
+
+        for lane in imp.lanes.borrow().iter() {
+            lane.queue_draw();
+        }
+    }
+
+    fn clear(&self) {
+        self.imp()
+            .lane_texts
             .borrow_mut()
             .iter_mut()
             .for_each(|v| v.clear());
-        *imp.current_lane.borrow_mut() = 0;
-        *imp.score.borrow_mut() = 0;
-        *imp.difficulty.borrow_mut() = 1;
-        *imp.speed.borrow_mut() = 2.0;
-        *imp.game_over.borrow_mut() = false;
+    }
 
-        // Hide game over overlay
-        imp.results_box.set_visible(false);
-        imp.game_area.set_visible(true);
+    fn object_count(&self) -> usize {
+        self.imp().lane_texts.borrow().iter().map(Vec::len).sum()
+    }
 
-        imp.score_label
-            .set_text(&i18n_fmt! { i18n_fmt("Score: {}", 0) });
-        imp.level_label
-            .set_text(&i18n_fmt! { i18n_fmt("Level: {}", 1) });
+    fn spawn(&self) {
+        let imp = self.imp();
+        let mut rng = rand::thread_rng();
+
+        let lane_count = imp.lanes.borrow().len();
+        if lane_count == 0 {
+            return;
+        }
+        let lane_index = rng.gen_range(0..lane_count);
+        let Some(word) = imp.word_list.borrow().choose(&mut rng).map(str::to_string) else {
+            return;
+        };
+
+        if let Some(lane) = imp.lanes.borrow().get(lane_index) {
+            let width = lane.width() as f64;
+            imp.lane_texts.borrow_mut()[lane_index].push(ScrollingText::new(word, width, 0.0));
+        }
+    }
+
+    // Words that scroll out of their lane are missed
+    fn advance(&self, dt: f64) -> usize {
+        let imp = self.imp();
+        let speed = imp.state.progress.borrow().speed();
+        let missed = {
+            let mut texts = imp.lane_texts.borrow_mut();
+            let mut target = imp.target.borrow_mut();
+            let mut missed = 0;
+            for (lane, lane_texts) in texts.iter_mut().enumerate() {
+                for text in lane_texts.iter_mut() {
+                    text.advance(-speed, 0.0, dt);
+                }
+                // A targeted word that got away frees the aim
+                if *target == Some(lane) && lane_texts.first().is_some_and(|t| t.x < LANE_END) {
+                    *target = None;
+                }
+                let count = lane_texts.len();
+                lane_texts.retain(|t| t.x >= LANE_END);
+                missed += count - lane_texts.len();
+            }
+            missed
+        };
 
         for lane in imp.lanes.borrow().iter() {
             lane.queue_draw();
         }
+        missed
+    }
 
-        // Ensure focus
-        glib::idle_add_local_once({
-            let obj = self.clone();
-            move || {
-                obj.grab_focus();
-            }
-        });
+    fn set_game_visible(&self, visible: bool) {
+        if let Some(lanes) = self.imp().lanes_container.borrow().as_ref() {
+            lanes.set_visible(visible);
+        }
+        if visible {
+            self.grab_focus();
+        }
+    }
+
+    fn update_labels(&self) {
+        let imp = self.imp();
+        let progress = imp.state.progress.borrow();
+        imp.score_label
+            .set_text(&i18n_fmt! { i18n_fmt("Score: {}", progress.score) });
+        imp.level_label
+            .set_text(&i18n_fmt! { i18n_fmt("Level: {}", progress.level) });
+
+        let status = progress.status_text();
+        imp.status_label.set_visible(status.is_some());
+        imp.status_label
+            .set_text(status.as_deref().unwrap_or_default());
+    }
+
+    fn score_variant(&self) -> Option<&'static str> {
+        self.imp().auto_target.borrow().then_some("auto-target")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        self.start_game_loop();
+    #[test]
+    fn test_find_target() {
+        let texts = vec![
+            vec![
+                ScrollingText::new("apple".to_string(), 300.0, 0.0),
+                ScrollingText::new("banana".to_string(), 500.0, 0.0),
+            ],
+            vec![ScrollingText::new("avocado".to_string(), 100.0, 0.0)],
+            vec![ScrollingText::new("berry".to_string(), 900.0, 0.0)],
+        ];
+
+        // The word closest to the end of its lane wins
+        assert_eq!(find_target(&texts, 'a', 800.0), Some((1, 0)));
+        assert_eq!(find_target(&texts, 'b', 800.0), Some((0, 1)));
+        // Words not scrolled into sight yet can't be targeted
+        assert_eq!(find_target(&texts, 'b', 400.0), None);
+        assert_eq!(find_target(&texts, 'c', 800.0), None);
     }
 }
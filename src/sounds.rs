@@ -0,0 +1,90 @@
+use gettextrs::gettext;
+use gtk::gio;
+use gtk::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use strum_macros::{Display as EnumDisplay, EnumIter};
+
+// Something that can be heard, each with its own sample and setting
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, EnumDisplay, EnumIter)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Sound {
+    Keystroke,
+    Error,
+    StepCompleted,
+    // A key or word typed away in a game
+    Destroyed,
+    LevelUp,
+    GameOver,
+}
+
+impl Sound {
+    pub fn ui_string(&self) -> String {
+        match self {
+            Sound::Keystroke => gettext("Keystrokes"),
+            Sound::Error => gettext("Mistakes"),
+            Sound::StepCompleted => gettext("Completed Steps"),
+            Sound::Destroyed => gettext("Keys and Words Hit"),
+            Sound::LevelUp => gettext("New Levels"),
+            Sound::GameOver => gettext("Game Over"),
+        }
+    }
+
+    // Whether this sound is played, when sounds are on
+    pub fn settings_key(&self) -> String {
+        format!("sound-{}", self)
+    }
+
+    fn resource_path(&self) -> String {
+        format!("/io/github/nacho/mecalin/sounds/{}.wav", self)
+    }
+}
+
+thread_local! {
+    // Loaded the first time they're played, then reused
+    static MEDIA_FILES: RefCell<HashMap<Sound, gtk::MediaFile>> = RefCell::new(HashMap::new());
+}
+
+// Plays a sound if it's enabled, starting it over when it's still playing
+pub fn play(sound: Sound) {
+    let settings = gio::Settings::new("io.github.nacho.mecalin");
+    if !settings.boolean("sound-feedback") || !settings.boolean(&sound.settings_key()) {
+        return;
+    }
+    let volume = settings.double("sound-volume");
+
+    MEDIA_FILES.with(|media_files| {
+        let mut media_files = media_files.borrow_mut();
+        let media_file = media_files
+            .entry(sound)
+            .or_insert_with(|| gtk::MediaFile::for_resource(&sound.resource_path()));
+
+        media_file.set_volume(volume);
+        if media_file.is_seekable() {
+            media_file.seek(0);
+        }
+        media_file.play();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_settings_keys() {
+        let keys: Vec<String> = Sound::iter().map(|sound| sound.settings_key()).collect();
+        assert_eq!(
+            keys,
+            [
+                "sound-keystroke",
+                "sound-error",
+                "sound-step-completed",
+                "sound-destroyed",
+                "sound-level-up",
+                "sound-game-over"
+            ]
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use gtk::{gio, glib};
+use std::cell::RefCell;
+use std::ffi::OsStr;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+// Something that reads text out loud. Only local engines are used, so nothing that is
+// dictated leaves the computer.
+pub trait SpeechBackend {
+    // Starts reading `text` in the language with code `lang_code`, cutting off anything
+    // still being read. A `rate` of 1 is the normal speed of the engine.
+    fn speak(&self, text: &str, lang_code: &str, rate: f64) -> Result<(), glib::Error>;
+
+    fn stop(&self);
+}
+
+// Engines in order of preference. speech-dispatcher comes first as it's already set up
+// with the voices chosen for the screen reader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter)]
+pub enum SpeechEngine {
+    SpeechDispatcher,
+    EspeakNg,
+}
+
+impl SpeechEngine {
+    fn program(&self) -> &'static str {
+        match self {
+            SpeechEngine::SpeechDispatcher => "spd-say",
+            SpeechEngine::EspeakNg => "espeak-ng",
+        }
+    }
+
+    // Only looks through PATH, so it's quick enough to check while building the view
+    pub fn is_installed(&self) -> bool {
+        glib::find_program_in_path(self.program()).is_some()
+    }
+
+    pub fn backend(&self) -> Box<dyn SpeechBackend> {
+        match self {
+            SpeechEngine::SpeechDispatcher => Box::new(SpeechDispatcher),
+            SpeechEngine::EspeakNg => Box::<EspeakNg>::default(),
+        }
+    }
+}
+
+// The preferred engine that is installed, if any
+pub fn default_backend() -> Option<Box<dyn SpeechBackend>> {
+    SpeechEngine::iter()
+        .find(SpeechEngine::is_installed)
+        .map(|engine| engine.backend())
+}
+
+fn spawn(argv: &[&OsStr]) -> Result<gio::Subprocess, glib::Error> {
+    gio::Subprocess::newv(
+        argv,
+        gio::SubprocessFlags::STDOUT_SILENCE | gio::SubprocessFlags::STDERR_SILENCE,
+    )
+}
+
+// Voices are named after the language only, without the region
+fn voice_language(lang_code: &str) -> &str {
+    lang_code.split('_').next().unwrap_or(lang_code)
+}
+
+// speech-dispatcher rates go from -100 to 100, with 0 being the normal speed
+fn speech_dispatcher_rate(rate: f64) -> i32 {
+    ((rate - 1.0) * 100.0).round().clamp(-100.0, 100.0) as i32
+}
+
+// espeak-ng counts words per minute, 175 by default
+fn espeak_ng_speed(rate: f64) -> u32 {
+    (175.0 * rate).round().clamp(80.0, 450.0) as u32
+}
+
+// Hands the text to the speech-dispatcher daemon, which keeps reading it on its own
+pub struct SpeechDispatcher;
+
+impl SpeechBackend for SpeechDispatcher {
+    fn speak(&self, text: &str, lang_code: &str, rate: f64) -> Result<(), glib::Error> {
+        // Cancelling in the same call, as a separate one could arrive after the text
+        let rate = speech_dispatcher_rate(rate).to_string();
+        spawn(&[
+            OsStr::new("spd-say"),
+            OsStr::new("--cancel"),
+            OsStr::new("--language"),
+            OsStr::new(voice_language(lang_code)),
+            OsStr::new("--rate"),
+            OsStr::new(&rate),
+            OsStr::new("--"),
+            OsStr::new(text),
+        ])?;
+        Ok(())
+    }
+
+    fn stop(&self) {
+        let _ = spawn(&[OsStr::new("spd-say"), OsStr::new("--cancel")]);
+    }
+}
+
+// Runs espeak-ng for each text, which reads it until it's done or killed
+#[derive(Default)]
+pub struct EspeakNg {
+    process: RefCell<Option<gio::Subprocess>>,
+}
+
+impl SpeechBackend for EspeakNg {
+    fn speak(&self, text: &str, lang_code: &str, rate: f64) -> Result<(), glib::Error> {
+        self.stop();
+
+        let speed = espeak_ng_speed(rate).to_string();
+        let process = spawn(&[
+            OsStr::new("espeak-ng"),
+            OsStr::new("-v"),
+            OsStr::new(voice_language(lang_code)),
+            OsStr::new("-s"),
+            OsStr::new(&speed),
+            OsStr::new("--"),
+            OsStr::new(text),
+        ])?;
+        self.process.replace(Some(process));
+        Ok(())
+    }
+
+    fn stop(&self) {
+        if let Some(process) = self.process.take() {
+            process.force_exit();
+        }
+    }
+}
+
+impl Drop for EspeakNg {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rates() {
+        assert_eq!(speech_dispatcher_rate(1.0), 0);
+        assert_eq!(speech_dispatcher_rate(0.6), -40);
+        assert_eq!(speech_dispatcher_rate(3.0), 100);
+        assert_eq!(espeak_ng_speed(1.0), 175);
+        assert_eq!(espeak_ng_speed(0.6), 105);
+        assert_eq!(espeak_ng_speed(0.1), 80);
+        assert_eq!(voice_language("de_CH"), "de");
+        assert_eq!(voice_language("es"), "es");
+    }
+}
@@ -41,6 +41,10 @@ mod imp {
         #[template_child]
         pub accuracy_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub rhythm_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub rhythm_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub test_info_box: TemplateChild<gtk::Box>,
         #[template_child]
         pub test_type_label: TemplateChild<gtk::Label>,
@@ -51,6 +55,10 @@ mod imp {
         #[template_child]
         pub language_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub attribution_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub seed_label: TemplateChild<gtk::Label>,
+        #[template_child]
         pub retry_button: TemplateChild<gtk::Button>,
 
         #[property(get, set)]
@@ -83,11 +91,15 @@ mod imp {
                 wpm_accuracy_box: Default::default(),
                 wpm_label: Default::default(),
                 accuracy_label: Default::default(),
+                rhythm_box: Default::default(),
+                rhythm_label: Default::default(),
                 test_info_box: Default::default(),
                 test_type_label: Default::default(),
                 duration_label: Default::default(),
                 language_box: Default::default(),
                 language_label: Default::default(),
+                attribution_label: Default::default(),
+                seed_label: Default::default(),
                 retry_button: Default::default(),
 
                 show_personal_best: Default::default(),
@@ -112,7 +124,12 @@ mod imp {
         fn signals() -> &'static [glib::subclass::Signal] {
             use std::sync::OnceLock;
             static SIGNALS: OnceLock<Vec<glib::subclass::Signal>> = OnceLock::new();
-            SIGNALS.get_or_init(|| vec![glib::subclass::Signal::builder("retry").build()])
+            SIGNALS.get_or_init(|| {
+                vec![
+                    glib::subclass::Signal::builder("retry").build(),
+                    glib::subclass::Signal::builder("retry-same-text").build(),
+                ]
+            })
         }
 
         fn constructed(&self) {
@@ -153,6 +170,11 @@ mod imp {
         fn on_retry_clicked(&self) {
             self.obj().emit_by_name::<()>("retry", &[]);
         }
+
+        #[template_callback]
+        fn on_retry_same_text_clicked(&self) {
+            self.obj().emit_by_name::<()>("retry-same-text", &[]);
+        }
     }
 }
 
@@ -168,6 +190,7 @@ impl SpeedTestResultsView {
             real_duration,
             wpm,
             accuracy,
+            rhythm,
             ..
         } = summary;
 
@@ -182,6 +205,15 @@ impl SpeedTestResultsView {
         imp.accuracy_label
             .set_label(&i18n_fmt! { i18n_fmt("{}%", display_accuracy) });
 
+        imp.rhythm_box.set_visible(rhythm.is_some());
+        if let Some(rhythm) = rhythm {
+            let display_rhythm = (rhythm * 100.).floor();
+            // Translators: The `{}` block will be replaced with the percentage number,
+            // do not translate it!
+            imp.rhythm_label
+                .set_label(&i18n_fmt! { i18n_fmt("{}%", display_rhythm) });
+        }
+
         imp.duration_label
             .set_label(&human_readable_duration(real_duration));
 
@@ -190,20 +222,66 @@ impl SpeedTestResultsView {
             TestConfig::Generated { difficulty, .. } => match difficulty {
                 GeneratedTestDifficulty::Simple => gettext("Simple"),
                 GeneratedTestDifficulty::Advanced => gettext("Advanced"),
+                GeneratedTestDifficulty::Quotes => gettext("Quotes"),
+                GeneratedTestDifficulty::Code(programming_language) => {
+                    let name = programming_language.get_message().unwrap();
+                    // Translators: The `{}` block will be replaced with the name of a
+                    // programming language, like "Rust". Do not translate it!
+                    i18n_fmt! { i18n_fmt("{} Code", name) }
+                }
+                GeneratedTestDifficulty::Custom => gettext("Custom Words"),
             },
         };
 
+        let test_type_string = match config {
+            TestConfig::Generated {
+                vocabulary: Some(count),
+                ..
+            } => {
+                // Translators: The first `{}` block will be replaced with the type of text,
+                // like "Simple", and the second with a number of words. Do not translate them!
+                i18n_fmt! { i18n_fmt("{}, {} Most Common Words", test_type_string, count) }
+            }
+            _ => test_type_string,
+        };
+
         imp.test_type_label.set_label(&test_type_string);
 
         match config {
-            TestConfig::Finite => imp.language_box.set_visible(false),
-            TestConfig::Generated { language, .. } => {
-                imp.language_box.set_visible(true);
+            TestConfig::Finite => {
+                imp.language_box.set_visible(false);
+                imp.seed_label.set_visible(false);
+            }
+            TestConfig::Generated {
+                language,
+                difficulty,
+                seed,
+                ..
+            } => {
+                // Code and custom words don't depend on the language of the text
+                imp.language_box.set_visible(!matches!(
+                    difficulty,
+                    GeneratedTestDifficulty::Code(_) | GeneratedTestDifficulty::Custom
+                ));
                 imp.language_label
                     .set_label(language.get_message().unwrap());
+
+                imp.seed_label.set_visible(true);
+                // Translators: The `{}` block will be replaced with the number identifying
+                // the generated text. Do not translate it!
+                imp.seed_label
+                    .set_label(&i18n_fmt! { i18n_fmt("Text seed: {}", seed) });
             }
         }
     }
+
+    // Quote tests credit the works their sentences come from
+    pub fn set_attribution(&self, attribution: Option<&str>) {
+        let imp = self.imp();
+        imp.attribution_label.set_visible(attribution.is_some());
+        imp.attribution_label
+            .set_label(attribution.unwrap_or_default());
+    }
 }
 
 pub fn human_readable_duration(duration: Duration) -> String {
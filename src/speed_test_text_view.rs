@@ -23,8 +23,10 @@ mod colors;
 mod input;
 mod scrolling;
 
+use crate::sounds::{self, Sound};
 use crate::text_utils::{
-    current_word, insert_replacements, validate_with_replacements, GraphemeState,
+    correct_prefix_len, current_word, insert_replacements, validate_with_replacements,
+    GraphemeState,
 };
 use glib::subclass::Signal;
 use gtk::glib;
@@ -76,6 +78,8 @@ mod imp {
         pub(super) caret_x_animation: OnceCell<adw::TimedAnimation>,
         pub(super) caret_y_animation: OnceCell<adw::TimedAnimation>,
         pub(super) caret_rgb: Cell<(f32, f32, f32)>,
+        // Graphemes that should be typed by now when pacing
+        pub(super) pace_position: Cell<Option<usize>>,
     }
 
     #[glib::object_subclass]
@@ -161,6 +165,11 @@ mod imp {
 
             let (caret_path, caret_stroke, caret_color) = self.caret_stroke_data();
             snapshot.append_stroke(&caret_path, &caret_stroke, &caret_color);
+
+            if let Some(bounds) = self.pace_marker_bounds() {
+                let (r, g, b) = self.caret_rgb.get();
+                snapshot.append_color(&gdk::RGBA::new(r, g, b, 0.4), &bounds);
+            }
         }
     }
 
@@ -201,6 +210,11 @@ mod imp {
                     .unwrap_or(GraphemeState::Unfinished);
 
                 let correct = last_grapheme_state != GraphemeState::Mistake;
+                sounds::play(if correct {
+                    Sound::Keystroke
+                } else {
+                    Sound::Error
+                });
                 let keystroke = (Instant::now(), correct);
                 self.keystrokes.borrow_mut().push(keystroke);
             } else if change == TextChange::Removal {
@@ -265,6 +279,15 @@ impl SpeedTestTextView {
             .count()
     }
 
+    // Typed graphemes up to the first mistake, which is how far along the text one really is
+    pub fn correct_grapheme_count(&self) -> usize {
+        let imp = self.imp();
+        correct_prefix_len(
+            imp.original_text.borrow().as_str(),
+            imp.typed_text.borrow().as_str(),
+        )
+    }
+
     pub fn last_grapheme_state(&self) -> GraphemeState {
         let imp = self.imp();
 
@@ -296,10 +319,19 @@ impl SpeedTestTextView {
         self.imp().keystrokes.borrow()
     }
 
+    // Underlines the character that should be typed now to keep up with the metronome
+    pub fn set_pace_position(&self, position: Option<usize>) {
+        let imp = self.imp();
+        if imp.pace_position.replace(position) != position {
+            self.queue_draw();
+        }
+    }
+
     pub fn reset(&self) {
         self.set_original_text("");
         self.set_typed_text("");
         self.set_running(false);
+        self.set_pace_position(None);
 
         let imp = self.imp();
         imp.scroll_animation().skip();
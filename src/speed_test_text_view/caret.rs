@@ -79,6 +79,36 @@ impl imp::SpeedTestTextView {
         (path, stroke, color)
     }
 
+    // Under the character at the pace position, if it's on one of the lines in view
+    pub(super) fn pace_marker_bounds(&self) -> Option<gtk::graphene::Rect> {
+        let position = self.pace_position.get()?;
+        let original = self.original_text.borrow();
+        let paced: String = original.graphemes(true).take(position).collect();
+        let (line, index) = line_offset_with_replacements(&original, &paced, 0);
+
+        let text_view = self.text_view.get();
+        let iter = text_view
+            .buffer()
+            .iter_at_line_index(line as i32, index as i32)?;
+        let location = text_view.iter_location(&iter);
+        let (x, y) = text_view.buffer_to_window_coords(
+            gtk::TextWindowType::Widget,
+            location.x(),
+            location.y() + location.height(),
+        );
+
+        if y <= 0 || y > self.obj().height() {
+            return None;
+        }
+
+        Some(gtk::graphene::Rect::new(
+            x as f32,
+            y as f32 - 3.,
+            location.width().max(8) as f32,
+            3.,
+        ))
+    }
+
     // Calculates where the caret currently should be, and runs an animation to get it there.
     // If `force` is true, the change will happen unconditionally and without an animation.
     pub(super) fn update_caret_position(&self, force: bool) {
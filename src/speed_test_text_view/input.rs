@@ -18,7 +18,10 @@
  */
 
 use super::*;
-use crate::text_utils::{end_alias, pop_grapheme_in_place, pop_word_in_place};
+use crate::text_utils::{
+    end_alias, indentation_to_skip, pop_grapheme_in_place, pop_word_in_place,
+    skipped_indentation_len,
+};
 
 impl imp::SpeedTestTextView {
     pub(super) fn setup_input_handling(&self) {
@@ -135,7 +138,12 @@ impl imp::SpeedTestTextView {
                         glib::signal::Propagation::Stop
                     }
                     (true, gdk::Key::BackSpace) => {
-                        imp.pop_typed_text(1);
+                        // Skipped indentation goes away together with the line break before it
+                        let skipped = skipped_indentation_len(
+                            &imp.original_text.borrow(),
+                            &imp.typed_text.borrow(),
+                        );
+                        imp.pop_typed_text(1 + skipped);
                         glib::signal::Propagation::Stop
                     }
                     (true, gdk::Key::Return) => {
@@ -145,6 +153,14 @@ impl imp::SpeedTestTextView {
                             .emit_by_name_with_values("commit", &["\n".into()]);
                         glib::signal::Propagation::Stop
                     }
+                    // Tab only moves the focus away unless the text has tabs to type
+                    (true, gdk::Key::Tab) if imp.original_text.borrow().contains('\t') => {
+                        controller
+                            .im_context()
+                            .expect("input controller has im context")
+                            .emit_by_name_with_values("commit", &["\t".into()]);
+                        glib::signal::Propagation::Stop
+                    }
                     _ => glib::signal::Propagation::Proceed,
                 }
             }
@@ -165,6 +181,13 @@ impl imp::SpeedTestTextView {
             self.typed_text.borrow_mut().push_str(&letter);
         }
 
+        let indentation =
+            indentation_to_skip(&self.original_text.borrow(), &self.typed_text.borrow())
+                .map(str::to_string);
+        if let Some(indentation) = indentation {
+            self.typed_text.borrow_mut().push_str(&indentation);
+        }
+
         self.typed_text_changed(TextChange::Addition);
     }
 
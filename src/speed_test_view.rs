@@ -6,18 +6,36 @@
  * SPDX-License-Identifier: GPL-3.0-or-later
  */
 
+use crate::code_snippets::{code, ProgrammingLanguage};
+use crate::custom_word_lists::{active_word_list, vocabulary_size};
+use crate::pacing::{paced_bpm, paced_position, Metronome};
+use crate::pause::{Pausable, PauseClock};
+use crate::pause_overlay::{self, PauseOverlay};
+use crate::quotes::quotes;
 use crate::speed_test_results_view::SpeedTestResultsView;
 use crate::speed_test_text_view::SpeedTestTextView;
-use crate::text_generation::{advanced, simple, Language};
+use crate::text_generation::{advanced, custom, random_seed, simple, Language};
 use crate::typing_test_utils::{GeneratedTestDifficulty, TestConfig, TestDuration, TestSummary};
 use gtk::glib;
 use gtk::prelude::*;
 use gtk::subclass::prelude::*;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::str::FromStr;
 use std::time::Instant;
 
+// Order of the entries in the text type dropdown
+const TEXT_TYPES: &[GeneratedTestDifficulty] = &[
+    GeneratedTestDifficulty::Simple,
+    GeneratedTestDifficulty::Advanced,
+    GeneratedTestDifficulty::Quotes,
+    GeneratedTestDifficulty::Code(ProgrammingLanguage::Rust),
+    GeneratedTestDifficulty::Code(ProgrammingLanguage::Python),
+    GeneratedTestDifficulty::Code(ProgrammingLanguage::JavaScript),
+    GeneratedTestDifficulty::Code(ProgrammingLanguage::Shell),
+    GeneratedTestDifficulty::Custom,
+];
+
 mod imp {
     use super::*;
 
@@ -36,10 +54,30 @@ mod imp {
         pub duration_dropdown: TemplateChild<gtk::DropDown>,
         #[template_child]
         pub settings_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub challenge_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub challenge_entry: TemplateChild<gtk::Entry>,
+        #[template_child]
+        pub copy_challenge_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub pause_overlay: TemplateChild<PauseOverlay>,
 
         pub start_time: Rc<RefCell<Option<Instant>>>,
         pub timer_source_id: Rc<RefCell<Option<glib::SourceId>>>,
+        pub pause_clock: Rc<RefCell<PauseClock>>,
         pub test_duration: Rc<RefCell<TestDuration>>,
+        pub seed: Cell<u64>,
+        pub vocabulary: Cell<Option<usize>>,
+        pub language: Cell<Language>,
+        pub attribution: RefCell<Option<String>>,
+        // Set while a challenge code updates the dropdowns, so they don't start a new test
+        pub loading_challenge: Cell<bool>,
+        // Tempo of the metronome for this test, if pacing
+        pub bpm: Cell<Option<u32>>,
+        pub metronome: Metronome,
+        // When the test was paused, so the rhythm leaves out the gaps around pauses
+        pub pauses: RefCell<Vec<Instant>>,
     }
 
     #[glib::object_subclass]
@@ -49,6 +87,7 @@ mod imp {
         type ParentType = gtk::Box;
 
         fn class_init(klass: &mut Self::Class) {
+            PauseOverlay::ensure_type();
             klass.bind_template();
         }
 
@@ -64,9 +103,22 @@ mod imp {
                 text_type_dropdown: Default::default(),
                 duration_dropdown: Default::default(),
                 settings_box: Default::default(),
+                challenge_button: Default::default(),
+                challenge_entry: Default::default(),
+                copy_challenge_button: Default::default(),
+                pause_overlay: Default::default(),
                 start_time: Rc::new(RefCell::new(None)),
                 timer_source_id: Rc::new(RefCell::new(None)),
+                pause_clock: Rc::new(RefCell::new(PauseClock::default())),
                 test_duration: Rc::new(RefCell::new(TestDuration::Sec30)),
+                seed: Cell::new(0),
+                vocabulary: Cell::new(None),
+                language: Cell::new(Language::English),
+                attribution: RefCell::new(None),
+                loading_challenge: Cell::new(false),
+                bpm: Cell::new(None),
+                metronome: Metronome::default(),
+                pauses: RefCell::new(Vec::new()),
             }
         }
     }
@@ -107,16 +159,48 @@ impl SpeedTestView {
             1 => TestDuration::Sec30,
             2 => TestDuration::Min1,
             3 => TestDuration::Min5,
+            4 => TestDuration::Min10,
             _ => TestDuration::Min1,
         }
     }
 
+    fn get_difficulty(&self) -> GeneratedTestDifficulty {
+        TEXT_TYPES
+            .get(self.imp().text_type_dropdown.selected() as usize)
+            .copied()
+            .unwrap_or(GeneratedTestDifficulty::Simple)
+    }
+
+    fn current_config(&self) -> TestConfig {
+        let imp = self.imp();
+        TestConfig::Generated {
+            difficulty: self.get_difficulty(),
+            language: imp.language.get(),
+            duration: *imp.test_duration.borrow(),
+            vocabulary: imp.vocabulary.get(),
+            seed: imp.seed.get(),
+        }
+    }
+
     fn reset_test(&self) {
+        let lang_code = crate::utils::language_from_locale();
+        let language = Language::from_str(lang_code).unwrap_or(Language::English);
+
+        self.start_test(language, vocabulary_size(), random_seed());
+    }
+
+    fn start_test(&self, language: Language, vocabulary: Option<usize>, seed: u64) {
         let imp = self.imp();
         if let Some(source_id) = imp.timer_source_id.borrow_mut().take() {
             source_id.remove();
         }
         *imp.start_time.borrow_mut() = None;
+        *imp.pause_clock.borrow_mut() = PauseClock::default();
+        imp.pause_overlay.set_visible(false);
+        imp.metronome.stop();
+        imp.pauses.borrow_mut().clear();
+        imp.bpm.set(paced_bpm());
+        imp.text_view.set_pace_position(None);
 
         let duration = self.get_duration();
         *imp.test_duration.borrow_mut() = duration;
@@ -124,15 +208,43 @@ impl SpeedTestView {
         imp.settings_box.set_visible(true);
         imp.timer_label.set_visible(false);
 
-        let lang_code = crate::utils::language_from_locale();
-        let language = Language::from_str(lang_code).unwrap_or(Language::English);
-
-        let text = if imp.text_type_dropdown.selected() == 0 {
-            simple(language)
-        } else {
-            advanced(language)
+        imp.language.set(language);
+        imp.seed.set(seed);
+        imp.attribution.replace(None);
+
+        // Quotes and code are always used whole
+        let difficulty = self.get_difficulty();
+        let vocabulary = vocabulary.filter(|_| {
+            !matches!(
+                difficulty,
+                GeneratedTestDifficulty::Quotes | GeneratedTestDifficulty::Code(_)
+            )
+        });
+        imp.vocabulary.set(vocabulary);
+
+        let text = match difficulty {
+            GeneratedTestDifficulty::Simple => simple(language, vocabulary, seed),
+            GeneratedTestDifficulty::Advanced => advanced(language, vocabulary, seed),
+            GeneratedTestDifficulty::Quotes => {
+                let quote_text = quotes(language, seed);
+                imp.attribution.replace(Some(quote_text.attribution()));
+                quote_text.text
+            }
+            GeneratedTestDifficulty::Code(programming_language) => code(programming_language, seed),
+            // Without a word list selected in preferences, the language's own words are used
+            GeneratedTestDifficulty::Custom => match active_word_list() {
+                Some(words) => custom(&words, vocabulary, seed),
+                None => simple(language, vocabulary, seed),
+            },
         };
 
+        let challenge_code = self.current_config().challenge_code();
+        imp.challenge_entry
+            .set_text(challenge_code.as_deref().unwrap_or_default());
+        imp.copy_challenge_button
+            .set_sensitive(challenge_code.is_some());
+        imp.challenge_entry.remove_css_class("error");
+
         imp.text_view.set_original_text(&text);
         imp.text_view.set_typed_text("");
         imp.text_view.set_running(false);
@@ -142,45 +254,86 @@ impl SpeedTestView {
         imp.results_view.set_visible(false);
     }
 
-    fn show_results(&self, start_instant: Instant) {
+    fn load_challenge_code(&self, code: &str) {
         let imp = self.imp();
 
-        let lang_code = crate::utils::language_from_locale();
-        let language = Language::from_str(lang_code).unwrap_or(Language::English);
-
-        let difficulty = if imp.text_type_dropdown.selected() == 0 {
-            GeneratedTestDifficulty::Simple
-        } else {
-            GeneratedTestDifficulty::Advanced
-        };
-
-        let config = TestConfig::Generated {
-            difficulty,
+        let Some(TestConfig::Generated {
             language,
-            duration: *imp.test_duration.borrow(),
+            difficulty,
+            duration,
+            vocabulary,
+            seed,
+        }) = TestConfig::from_challenge_code(code)
+        else {
+            imp.challenge_entry.add_css_class("error");
+            return;
         };
 
+        imp.loading_challenge.set(true);
+        let text_type_position = TEXT_TYPES.iter().position(|t| *t == difficulty);
+        imp.text_type_dropdown
+            .set_selected(text_type_position.unwrap_or(0) as u32);
+        imp.duration_dropdown.set_selected(match duration {
+            TestDuration::Sec15 => 0,
+            TestDuration::Sec30 => 1,
+            TestDuration::Min1 => 2,
+            TestDuration::Min5 => 3,
+            TestDuration::Min10 => 4,
+        });
+        imp.loading_challenge.set(false);
+
+        imp.challenge_button.popdown();
+        self.start_test(language, vocabulary, seed);
+    }
+
+    fn show_results(&self, start_instant: Instant) {
+        let imp = self.imp();
+        imp.metronome.stop();
+        imp.text_view.set_pace_position(None);
+
         let keystrokes = imp.text_view.keystrokes();
         let keystrokes_vec: Vec<_> = keystrokes.iter().copied().collect();
+        let pauses = imp.pauses.borrow();
 
         let summary = TestSummary::new(
             std::time::SystemTime::now(),
-            start_instant,
-            Instant::now(),
-            config,
+            imp.pause_clock.borrow().active_since(start_instant),
+            self.current_config(),
             &imp.text_view.original_text(),
             &imp.text_view.typed_text(),
             &keystrokes_vec,
+            imp.bpm.get().map(|bpm| (bpm, pauses.as_slice())),
         );
 
         imp.results_view.set_summary(summary);
+        imp.results_view
+            .set_attribution(imp.attribution.borrow().as_deref());
         imp.results_view.set_visible(true);
         imp.text_view.set_visible(false);
     }
 
+    // Moves the pacing marker to where a typist following the metronome would be
+    fn update_pace(&self, elapsed: std::time::Duration) {
+        let imp = self.imp();
+        if let Some(bpm) = imp.bpm.get() {
+            // The first keystroke started the test, right on the first beat
+            imp.text_view
+                .set_pace_position(Some(paced_position(elapsed, bpm) + 1));
+        }
+    }
+
     fn setup_signals(&self) {
         let imp = self.imp();
 
+        imp.pause_overlay.connect_resume(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                view.resume();
+            }
+        ));
+        pause_overlay::add_escape_controller(self);
+
         imp.results_view.connect_closure(
             "retry",
             false,
@@ -193,11 +346,26 @@ impl SpeedTestView {
             ),
         );
 
+        imp.results_view.connect_closure(
+            "retry-same-text",
+            false,
+            glib::closure_local!(
+                #[weak(rename_to = view)]
+                self,
+                move |_results_view: SpeedTestResultsView| {
+                    let imp = view.imp();
+                    view.start_test(imp.language.get(), imp.vocabulary.get(), imp.seed.get());
+                }
+            ),
+        );
+
         imp.text_type_dropdown.connect_selected_notify(glib::clone!(
             #[weak(rename_to = view)]
             self,
             move |_| {
-                view.reset_test();
+                if !view.imp().loading_challenge.get() {
+                    view.reset_test();
+                }
             }
         ));
 
@@ -205,7 +373,31 @@ impl SpeedTestView {
             #[weak(rename_to = view)]
             self,
             move |_| {
-                view.reset_test();
+                if !view.imp().loading_challenge.get() {
+                    view.reset_test();
+                }
+            }
+        ));
+
+        imp.challenge_entry.connect_activate(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |entry| {
+                view.load_challenge_code(&entry.text());
+            }
+        ));
+
+        imp.challenge_entry.connect_changed(|entry| {
+            entry.remove_css_class("error");
+        });
+
+        imp.copy_challenge_button.connect_clicked(glib::clone!(
+            #[weak(rename_to = view)]
+            self,
+            move |_| {
+                if let Some(code) = view.current_config().challenge_code() {
+                    view.clipboard().set_text(&code);
+                }
             }
         ));
 
@@ -226,8 +418,14 @@ impl SpeedTestView {
                         imp.settings_box.set_visible(false);
                         imp.timer_label.set_visible(true);
 
+                        if let Some(bpm) = imp.bpm.get() {
+                            imp.metronome.start(bpm);
+                            view.update_pace(std::time::Duration::ZERO);
+                        }
+
                         let timer_label = imp.timer_label.clone();
                         let start_time = imp.start_time.clone();
+                        let pause_clock = imp.pause_clock.clone();
                         let text_view_clone = text_view.clone();
                         let timer_source_id = imp.timer_source_id.clone();
                         let test_duration = imp.test_duration.clone();
@@ -235,9 +433,14 @@ impl SpeedTestView {
                         let source_id = glib::timeout_add_local(
                             std::time::Duration::from_millis(100),
                             move || {
+                                // The countdown stands still while paused
+                                if pause_clock.borrow().is_paused() {
+                                    return glib::ControlFlow::Continue;
+                                }
+
                                 let start_opt = *start_time.borrow();
                                 if let Some(start) = start_opt {
-                                    let elapsed = start.elapsed();
+                                    let elapsed = pause_clock.borrow().active_since(start);
                                     let duration_secs = test_duration.borrow().as_seconds();
                                     let remaining = duration_secs.saturating_sub(elapsed.as_secs());
 
@@ -257,6 +460,10 @@ impl SpeedTestView {
                                     let minutes = remaining / 60;
                                     let seconds = remaining % 60;
                                     timer_label.set_text(&format!("{}:{:02}", minutes, seconds));
+
+                                    if let Some(view) = view_weak.upgrade() {
+                                        view.update_pace(elapsed);
+                                    }
                                     glib::ControlFlow::Continue
                                 } else {
                                     *timer_source_id.borrow_mut() = None;
@@ -289,6 +496,50 @@ impl SpeedTestView {
     }
 }
 
+// Only a test that has started can be paused
+impl Pausable for SpeedTestView {
+    fn pause(&self) {
+        let imp = self.imp();
+        if !self.can_pause() || self.is_paused() {
+            return;
+        }
+
+        imp.pause_clock.borrow_mut().pause();
+        imp.pauses.borrow_mut().push(Instant::now());
+        imp.metronome.stop();
+        imp.text_view.set_running(false);
+        imp.text_view.set_accepts_input(false);
+        imp.text_view.set_visible(false);
+        imp.pause_overlay.show();
+    }
+
+    fn resume(&self) {
+        let imp = self.imp();
+        if !self.is_paused() {
+            return;
+        }
+
+        imp.pause_clock.borrow_mut().resume();
+        // The beat carries on from where it was, as counted from the first keystroke
+        if let (Some(bpm), Some(start)) = (imp.bpm.get(), *imp.start_time.borrow()) {
+            let elapsed = imp.pause_clock.borrow().active_since(start);
+            imp.metronome.start_at(bpm, elapsed);
+        }
+        imp.pause_overlay.set_visible(false);
+        imp.text_view.set_accepts_input(true);
+        imp.text_view.set_visible(true);
+        imp.text_view.grab_focus();
+    }
+
+    fn is_paused(&self) -> bool {
+        self.imp().pause_clock.borrow().is_paused()
+    }
+
+    fn can_pause(&self) -> bool {
+        self.imp().start_time.borrow().is_some()
+    }
+}
+
 impl Default for SpeedTestView {
     fn default() -> Self {
         Self::new()
@@ -18,8 +18,13 @@
  */
 
 use include_dir::{include_dir, Dir};
+use rand::distributions::WeightedIndex;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use rand::seq::index::sample;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, OnceLock};
 use strum_macros::{Display as EnumDisplay, EnumIter, EnumMessage, EnumString};
 use unicode_segmentation::UnicodeSegmentation;
 
@@ -154,22 +159,170 @@ const GENERIC_PUNCTUATION: &[Punctuation] = &[
 
 type Numerals = [&'static str; 10];
 
+// Vowels of the languages with courses, used to make up pronounceable words
+const VOWELS: &str = "aeiouyàáâãäåæèéêëìíîïòóôõöøùúûüýœ";
+// With fewer real words than this, restricted texts would repeat the same few words
+const MIN_RESTRICTED_WORDS: usize = 8;
+
 const WESTERN_ARABIC_NUMERALS: &Numerals = &["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
 const PERSIAN_NUMERALS: &Numerals = &["۰", "۱", "۲", "۳", "۴", "۵", "۶", "۷", "۸", "۹"];
 const DEVANAGARI_NUMERALS: &Numerals = &["०", "१", "२", "३", "४", "५", "६", "७", "८", "९"];
 const BANGLA_NUMERALS: &Numerals = &["০", "১", "২", "৩", "৪", "৫", "৬", "৭", "৮", "৯"];
 
-// Only lowercase letters, no punctuation or numbers
-pub fn simple(language: Language) -> String {
-    simple_generic(&language.to_string(), " ")
+// Word list files have one word per line, optionally followed by a tab and how often the
+// word is used, like "the\t23135851". Lists with frequencies are sampled with them as
+// weights, so common words come up as often as they do in real text. Lists without them
+// are expected to be sorted from most to least common already, and are weighted by rank
+// following Zipf's law, where the word at rank n is used about 1/n as often as the first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WordList {
+    words: Vec<String>,
+    frequencies: Option<Vec<f64>>,
+}
+
+impl WordList {
+    pub fn parse(contents: &str) -> Self {
+        Self::from_entries(
+            contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(split_frequency),
+        )
+    }
+
+    // Words without a frequency get the lowest one in the list, so a few forgotten
+    // numbers don't make words more common than the rest
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = (&'a str, Option<f64>)>) -> Self {
+        let entries: Vec<(&str, Option<f64>)> = entries.into_iter().collect();
+        let lowest = entries
+            .iter()
+            .filter_map(|(_, frequency)| *frequency)
+            .min_by(f64::total_cmp);
+
+        let Some(lowest) = lowest else {
+            return WordList {
+                words: entries.iter().map(|(word, _)| word.to_string()).collect(),
+                frequencies: None,
+            };
+        };
+
+        let mut entries: Vec<(&str, f64)> = entries
+            .into_iter()
+            .map(|(word, frequency)| (word, frequency.unwrap_or(lowest)))
+            .collect();
+        // Stable, so words used equally often keep the order of the file
+        entries.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        WordList {
+            words: entries.iter().map(|(word, _)| word.to_string()).collect(),
+            frequencies: Some(entries.iter().map(|(_, frequency)| *frequency).collect()),
+        }
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    pub fn frequencies(&self) -> Option<&[f64]> {
+        self.frequencies.as_deref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn filter(&self, keep: impl Fn(&str) -> bool) -> Self {
+        let kept: Vec<usize> = (0..self.words.len())
+            .filter(|i| keep(&self.words[*i]))
+            .collect();
+
+        WordList {
+            words: kept.iter().map(|i| self.words[*i].clone()).collect(),
+            frequencies: self
+                .frequencies
+                .as_ref()
+                .map(|frequencies| kept.iter().map(|i| frequencies[*i]).collect()),
+        }
+    }
+
+    // The `count` most common words, for practicing a smaller vocabulary first
+    pub fn top(&self, count: usize) -> Self {
+        let count = count.min(self.words.len());
+        WordList {
+            words: self.words[..count].to_vec(),
+            frequencies: self
+                .frequencies
+                .as_ref()
+                .map(|frequencies| frequencies[..count].to_vec()),
+        }
+    }
+
+    pub fn choose(&self, rng: &mut impl Rng) -> Option<&str> {
+        self.choose_with(self.weights().as_ref(), rng)
+    }
+
+    fn weights(&self) -> Option<WeightedIndex<f64>> {
+        match &self.frequencies {
+            Some(frequencies) => WeightedIndex::new(frequencies).ok(),
+            None => WeightedIndex::new((1..=self.words.len()).map(|rank| 1.0 / rank as f64)).ok(),
+        }
+    }
+
+    fn choose_with(
+        &self,
+        weights: Option<&WeightedIndex<f64>>,
+        rng: &mut impl Rng,
+    ) -> Option<&str> {
+        let word = match weights {
+            Some(weights) => self.words.get(weights.sample(rng)),
+            None => self.words.choose(rng),
+        };
+
+        word.map(String::as_str)
+    }
+}
+
+// Splits the frequency column off a word list line. Frequencies that aren't positive
+// numbers are ignored.
+pub fn split_frequency(line: &str) -> (&str, Option<f64>) {
+    match line.split_once('\t') {
+        Some((word, frequency)) => (
+            word,
+            frequency
+                .trim()
+                .parse()
+                .ok()
+                .filter(|frequency: &f64| frequency.is_finite() && *frequency > 0.0),
+        ),
+        None => (line, None),
+    }
+}
+
+// Seeds identify a generated text, so the same test can be retried or shared
+pub fn random_seed() -> u64 {
+    thread_rng().gen()
+}
+
+// Only lowercase letters, no punctuation or numbers. The vocabulary limits the text
+// to that many of the most common words of the language, or all of them if `None`.
+pub fn simple(language: Language, vocabulary: Option<usize>, seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    simple_generic(&mut rng, vocabulary, &language.to_string(), " ")
 }
 
 // Some capitalized letters, punctuation and numbers
-pub fn advanced(language: Language) -> String {
+pub fn advanced(language: Language, vocabulary: Option<usize>, seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
     match language {
         // Add special cases here if relevant
         // Arabic has its own set of punctuation and a couple of words with vowel markers
         Language::Arabic => advanced_generic(
+            &mut rng,
+            vocabulary,
             "ar_advanced",
             " ",
             &[
@@ -188,6 +341,8 @@ pub fn advanced(language: Language) -> String {
         // See <https://github.com/bragefuglseth/keypunch/issues/41> and
         // <https://github.com/bragefuglseth/keypunch/pull/80> if this ever comes up again
         Language::Bulgarian | Language::Romanian => advanced_generic(
+            &mut rng,
+            vocabulary,
             &language.to_string(),
             " ",
             &[
@@ -205,6 +360,8 @@ pub fn advanced(language: Language) -> String {
         // See <https://github.com/bragefuglseth/keypunch/issues/58>. The quotation is
         // slightly different from above.
         Language::Czech => advanced_generic(
+            &mut rng,
+            vocabulary,
             &language.to_string(),
             " ",
             &[
@@ -223,6 +380,8 @@ pub fn advanced(language: Language) -> String {
         // that allow for typing guillemet quotation marks easily.
         // See <https://github.com/bragefuglseth/keypunch/issues/47>
         Language::French => advanced_generic(
+            &mut rng,
+            vocabulary,
             &language.to_string(),
             " ",
             &[
@@ -238,6 +397,8 @@ pub fn advanced(language: Language) -> String {
             WESTERN_ARABIC_NUMERALS,
         ),
         Language::Greek => advanced_generic(
+            &mut rng,
+            vocabulary,
             &language.to_string(),
             " ",
             &[
@@ -254,6 +415,8 @@ pub fn advanced(language: Language) -> String {
         ),
         // Hindi & Nepali use Devanagari punctuation
         Language::Bangla | Language::Hindi | Language::Nepali => advanced_generic(
+            &mut rng,
+            vocabulary,
             &language.to_string(),
             " ",
             &[
@@ -274,6 +437,8 @@ pub fn advanced(language: Language) -> String {
         ),
         // Spanish has "wrapping" exclamation points and question marks
         Language::Spanish => advanced_generic(
+            &mut rng,
+            vocabulary,
             &language.to_string(),
             " ",
             &[
@@ -292,6 +457,8 @@ pub fn advanced(language: Language) -> String {
         // so there is a different word_list for advanced mode with more words.
         // extra words taken from: <https://github.com/monkeytypegame/monkeytype/blob/master/frontend/static/languages/portuguese_acentos_e_cedilha.json>
         Language::Portuguese => advanced_generic(
+            &mut rng,
+            vocabulary,
             "pt_advanced",
             " ",
             &[
@@ -311,6 +478,8 @@ pub fn advanced(language: Language) -> String {
         // but on the default Persian keyboard in GNOME,
         // the numbers are the same as Persian.
         Language::Persian => advanced_generic(
+            &mut rng,
+            vocabulary,
             "fa_advanced",
             " ",
             &[
@@ -326,6 +495,8 @@ pub fn advanced(language: Language) -> String {
             PERSIAN_NUMERALS,
         ),
         _ => advanced_generic(
+            &mut rng,
+            vocabulary,
             &language.to_string(),
             " ",
             GENERIC_PUNCTUATION,
@@ -334,24 +505,99 @@ pub fn advanced(language: Language) -> String {
     }
 }
 
+// Words typeable with only the allowed characters, like the keys learned so far in a
+// course. Real words of the language are used when there are enough of them, otherwise
+// pronounceable pseudo-words are made up from the allowed letters.
+pub fn restricted(
+    language: Language,
+    allowed: &HashSet<char>,
+    word_count: usize,
+    seed: u64,
+) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let word_list =
+        language_word_list(language).filter(|word| word.chars().all(|c| allowed.contains(&c)));
+
+    let mut generated: Vec<String> = Vec::new();
+    if word_list.len() >= MIN_RESTRICTED_WORDS {
+        let weights = word_list.weights();
+        while generated.len() < word_count {
+            let word = word_list
+                .choose_with(weights.as_ref(), &mut rng)
+                .expect("word list contains at least 1 word");
+            if generated.last().is_none_or(|last| last != word) {
+                generated.push(word.to_string());
+            }
+        }
+    } else {
+        let mut letters: Vec<char> = allowed
+            .iter()
+            .copied()
+            .filter(|c| c.is_alphabetic())
+            .collect();
+        // Without letters, words are made of whatever else can be typed, like "/;/."
+        if letters.is_empty() {
+            letters = allowed
+                .iter()
+                .copied()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+        }
+        // Sets have no order, so the seed alone wouldn't give the same words every time
+        letters.sort_unstable();
+
+        let (vowels, consonants): (Vec<char>, Vec<char>) = letters
+            .into_iter()
+            .partition(|c| c.to_lowercase().any(|c| VOWELS.contains(c)));
+        if vowels.is_empty() && consonants.is_empty() {
+            return String::new();
+        }
+
+        while generated.len() < word_count {
+            generated.push(pseudo_word(&vowels, &consonants, &mut rng));
+        }
+    }
+
+    generated.join(" ")
+}
+
+// Words from a user's own word list, used as they are
+pub fn custom(word_list: &WordList, vocabulary: Option<usize>, seed: u64) -> String {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    random_words(&with_vocabulary(word_list, vocabulary), &mut rng)
+        .into_iter()
+        .map(|s| s + " ")
+        .collect()
+}
+
+// The words the generated texts of a language are made of
+pub fn language_word_list(language: Language) -> Arc<WordList> {
+    embedded_word_list(&language.to_string())
+}
+
 // Should work for most languages
-fn simple_generic(lang_code: &str, spacing: &str) -> String {
-    let mut rng = thread_rng();
-    let generated = random_words_from_lang_code(lang_code, &mut rng);
+fn simple_generic(
+    rng: &mut impl Rng,
+    vocabulary: Option<usize>,
+    lang_code: &str,
+    spacing: &str,
+) -> String {
+    let generated = random_words_from_lang_code(lang_code, vocabulary, rng);
 
     generated.into_iter().map(|s| s + spacing).collect()
 }
 
 // Should work for most languages
 fn advanced_generic(
+    rng: &mut impl Rng,
+    vocabulary: Option<usize>,
     lang_code: &str,
     spacing: &str,
     punctuations: &[Punctuation],
     numerals: &Numerals,
 ) -> String {
-    let mut rng = thread_rng();
-
-    let mut generated = random_words_from_lang_code(lang_code, &mut rng);
+    let mut generated = random_words_from_lang_code(lang_code, vocabulary, rng);
 
     // The very first letter in the chunk should always be capitalized
     if let Some(word) = generated.get_mut(0) {
@@ -360,17 +606,17 @@ fn advanced_generic(
 
     // Swaps out some words with numbers
     let len = generated.len();
-    for i in sample(&mut rng, len, len / 20) {
+    for i in sample(rng, len, len / 20) {
         if let Some(word) = generated.get_mut(i) {
-            *word = random_number_weighted(numerals, &mut rng);
+            *word = random_number_weighted(numerals, rng);
         }
     }
 
     // Sample from the entire text except for the last word, since that gets punctuation
     // further down in the function
-    for i in sample(&mut rng, len - 2, len / 4) {
+    for i in sample(rng, len - 2, len / 4) {
         if let Some(word) = generated.get_mut(i) {
-            if let Ok(punctuation) = punctuations.choose_weighted(&mut rng, |p| p.weight) {
+            if let Ok(punctuation) = punctuations.choose_weighted(rng, |p| p.weight) {
                 *word = insert_punctuation(word, *punctuation);
 
                 if punctuation.ends_sentence {
@@ -383,11 +629,7 @@ fn advanced_generic(
     }
 
     // Insert random "sentence ending" punctuation on last word
-    if let Some(end_punctuation) = punctuations
-        .iter()
-        .filter(|p| p.ends_sentence)
-        .choose(&mut rng)
-    {
+    if let Some(end_punctuation) = punctuations.iter().filter(|p| p.ends_sentence).choose(rng) {
         if let Some(word) = generated.get_mut(len - 1) {
             *word = insert_punctuation(word, *end_punctuation);
         }
@@ -409,29 +651,63 @@ fn uppercase_first_letter(s: &str) -> String {
         .collect()
 }
 
-fn words_from_lang_code(lang_code: &str) -> Vec<&'static str> {
-    let s = EMBEDDED_WORD_LIST_DIR
-        .get_file(format!("{lang_code}.txt"))
-        .unwrap_or_else(|| panic!("word list for \"{}\" exists", lang_code))
-        .contents_utf8()
-        .expect("file has valid utf8 contents");
+// Parsed the first time a language is used and kept around, since the biggest
+// lists have thousands of words
+fn embedded_word_list(lang_code: &str) -> Arc<WordList> {
+    static WORD_LISTS: OnceLock<Mutex<HashMap<String, Arc<WordList>>>> = OnceLock::new();
+
+    let mut word_lists = WORD_LISTS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("word list cache isn't poisoned");
+
+    word_lists
+        .entry(lang_code.to_string())
+        .or_insert_with(|| {
+            let contents = EMBEDDED_WORD_LIST_DIR
+                .get_file(format!("{lang_code}.txt"))
+                .unwrap_or_else(|| panic!("word list for \"{}\" exists", lang_code))
+                .contents_utf8()
+                .expect("file has valid utf8 contents");
+
+            Arc::new(WordList::parse(contents))
+        })
+        .clone()
+}
+
+fn random_words_from_lang_code(
+    lang_code: &str,
+    vocabulary: Option<usize>,
+    rng: &mut impl Rng,
+) -> Vec<String> {
+    random_words(
+        &with_vocabulary(&embedded_word_list(lang_code), vocabulary),
+        rng,
+    )
+}
 
-    s.lines().filter(|line| !line.is_empty()).collect()
+fn with_vocabulary(word_list: &WordList, vocabulary: Option<usize>) -> Cow<'_, WordList> {
+    match vocabulary {
+        Some(count) => Cow::Owned(word_list.top(count)),
+        None => Cow::Borrowed(word_list),
+    }
 }
 
-fn random_words_from_lang_code(lang_code: &str, rng: &mut ThreadRng) -> Vec<String> {
-    let word_list = words_from_lang_code(lang_code);
+fn random_words(word_list: &WordList, rng: &mut impl Rng) -> Vec<String> {
+    let weights = word_list.weights();
 
     let mut generated: Vec<String> = Vec::new();
     while generated.iter().flat_map(|s| s.graphemes(true)).count() < CHUNK_GRAPHEME_COUNT {
         let new_word = word_list
-            .choose(rng)
+            .choose_with(weights.as_ref(), rng)
             .expect("word list contains at least 1 word");
 
-        let unique = if let Some(previous_words) = generated.last_chunk::<2>() {
-            previous_words.iter().all(|word| word != new_word)
-        } else {
-            true
+        // Custom word lists can be too short to avoid repeating the last two words
+        let unique = match generated.last_chunk::<2>() {
+            Some(previous_words) if word_list.len() > 2 => {
+                previous_words.iter().all(|word| word != new_word)
+            }
+            _ => true,
         };
 
         if unique {
@@ -442,6 +718,21 @@ fn random_words_from_lang_code(lang_code: &str, rng: &mut ThreadRng) -> Vec<Stri
     generated
 }
 
+// Made of one to three syllables, each a consonant and a vowel with an occasional
+// closing consonant, so they can be read out loud while typing
+fn pseudo_word(vowels: &[char], consonants: &[char], rng: &mut impl Rng) -> String {
+    let mut word = String::new();
+    for _ in 0..rng.gen_range(1..=3) {
+        word.extend(consonants.choose(rng));
+        word.extend(vowels.choose(rng));
+        if rng.gen_bool(0.3) {
+            word.extend(consonants.choose(rng));
+        }
+    }
+
+    word
+}
+
 fn insert_punctuation(word: &str, punctuation: Punctuation) -> String {
     match (punctuation.prefix, punctuation.suffix) {
         (Some(pre), Some(suf)) => format!("{pre}{word}{suf}"),
@@ -453,7 +744,7 @@ fn insert_punctuation(word: &str, punctuation: Punctuation) -> String {
 
 // Generates a number between 0 and 9999, with support for multiple numeral systems
 // and a bias towards smaller numbers
-fn random_number_weighted(numerals: &Numerals, rng: &mut ThreadRng) -> String {
+fn random_number_weighted(numerals: &Numerals, rng: &mut impl Rng) -> String {
     // The tuples consist of a number length and the proportion of numbers that should have
     // that amount of digits
     let number_length = *[(1, 0.4), (2, 0.3), (3, 0.2), (4, 0.1)]
@@ -470,3 +761,237 @@ fn random_number_weighted(numerals: &Numerals, rng: &mut ThreadRng) -> String {
 
     s
 }
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NumberDrill {
+    #[default]
+    Numbers,
+    Amounts,
+    Dates,
+}
+
+pub const NUMBER_DRILL_LINE_COUNT: usize = 10;
+const NUMBER_DRILL_ENTRIES_PER_LINE: usize = 4;
+
+// The numerals a language writes its numbers with
+fn numerals_for_language(language: Language) -> &'static Numerals {
+    match language {
+        Language::Persian => PERSIAN_NUMERALS,
+        Language::Bangla => BANGLA_NUMERALS,
+        Language::Hindi | Language::Nepali => DEVANAGARI_NUMERALS,
+        _ => WESTERN_ARABIC_NUMERALS,
+    }
+}
+
+// Lines of numbers, amounts or dates for data entry practice, mostly typed on the numpad
+pub fn number_drill(
+    language: Language,
+    drill: NumberDrill,
+    decimal_separator: &str,
+    seed: u64,
+) -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let numerals = numerals_for_language(language);
+
+    (0..NUMBER_DRILL_LINE_COUNT)
+        .map(|_| {
+            (0..NUMBER_DRILL_ENTRIES_PER_LINE)
+                .map(|_| match drill {
+                    NumberDrill::Numbers => random_number_weighted(numerals, &mut rng),
+                    NumberDrill::Amounts => random_amount(numerals, decimal_separator, &mut rng),
+                    NumberDrill::Dates => random_date(numerals, &mut rng),
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+// A price-like amount with two decimals, like 129.95
+fn random_amount(numerals: &Numerals, decimal_separator: &str, rng: &mut impl Rng) -> String {
+    let units = random_number_weighted(numerals, rng);
+    let cents: String = (0..2).map(|_| *numerals.choose(rng).unwrap()).collect();
+
+    format!("{units}{decimal_separator}{cents}")
+}
+
+// A valid day/month/year date, using the slash that the numpad has a key for
+fn random_date(numerals: &Numerals, rng: &mut impl Rng) -> String {
+    let year = rng.gen_range(1950..2050);
+    let month = rng.gen_range(1..=12);
+    let days_in_month = match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    let day = rng.gen_range(1..=days_in_month);
+
+    format!("{day:02}/{month:02}/{year}")
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(digit) => numerals[digit as usize],
+            None => "/",
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_words(text: &str, count: usize) -> String {
+        text.split(' ').take(count).collect::<Vec<_>>().join(" ")
+    }
+
+    #[test]
+    fn test_same_seed_generates_same_text() {
+        assert_eq!(
+            simple(Language::French, None, 1234),
+            simple(Language::French, None, 1234)
+        );
+        assert_eq!(
+            advanced(Language::French, None, 1234),
+            advanced(Language::French, None, 1234)
+        );
+        assert_ne!(
+            simple(Language::French, None, 1234),
+            simple(Language::French, None, 4321)
+        );
+    }
+
+    #[test]
+    fn test_simple_golden_output() {
+        assert_eq!(
+            first_words(&simple(Language::English, None, 42), 8),
+            "for I you to the man after the"
+        );
+    }
+
+    #[test]
+    fn test_advanced_golden_output() {
+        assert_eq!(
+            first_words(&advanced(Language::Spanish, None, 7), 8),
+            "Como \"que\" este ir su que como cómo"
+        );
+    }
+
+    #[test]
+    fn test_custom_words() {
+        // Lists shorter than the repetition window still fill a whole chunk
+        let words = WordList::parse("Kubernetes\nkubectl apply\n");
+        let text = custom(&words, None, 5);
+
+        assert_eq!(text, custom(&words, None, 5));
+        assert!(text.contains("Kubernetes"));
+        assert!(text.graphemes(true).count() >= CHUNK_GRAPHEME_COUNT);
+    }
+
+    #[test]
+    fn test_word_list_frequencies() {
+        let words = WordList::parse("rare\t1\nthe\t1000\nunknown\nbroken\t-4\nof\t500\n");
+        assert_eq!(words.words(), ["the", "of", "rare", "unknown", "broken"]);
+        assert_eq!(
+            words.frequencies(),
+            Some(&[1000.0, 500.0, 1.0, 1.0, 1.0][..])
+        );
+        assert_eq!(words.top(2).words(), ["the", "of"]);
+
+        // Common words should come up far more often than rare ones
+        let rare_words: String = (0..10).map(|i| format!("rare{i}\t1\n")).collect();
+        let words = WordList::parse(&format!("the\t1000\nof\t500\n{rare_words}"));
+        let text = custom(&words, None, 9);
+        let count = |word: &str| text.split(' ').filter(|w| *w == word).count();
+        assert!(count("the") > count("rare0") * 10, "{text}");
+    }
+
+    #[test]
+    fn test_rank_weights() {
+        // The bundled lists have no frequencies, but the most common words still come up
+        // far more often than the least common ones
+        let words = language_word_list(Language::English);
+        assert!(words.frequencies().is_none());
+        let mut rng = StdRng::seed_from_u64(1);
+        let samples: Vec<&str> = (0..10_000)
+            .map(|_| words.choose(&mut rng).unwrap())
+            .collect();
+        let count = |word: &str| samples.iter().filter(|w| **w == word).count();
+
+        let first = count(&words.words()[0]);
+        let last = count(&words.words()[words.len() - 1]);
+        assert!(first > last * 20, "{first} {last}");
+        assert!(first > 10_000 / words.len() * 5, "{first}");
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let text = simple(Language::English, Some(10), 3);
+        let top_words = language_word_list(Language::English).top(10);
+        assert!(text
+            .split_whitespace()
+            .all(|word| top_words.words().iter().any(|w| w == word)));
+
+        // A vocabulary larger than the list is the whole list
+        assert_eq!(
+            simple(Language::English, Some(usize::MAX), 42),
+            simple(Language::English, None, 42)
+        );
+    }
+
+    #[test]
+    fn test_restricted() {
+        let allowed: HashSet<char> = "asdfghjkl;eiru".chars().collect();
+        let text = restricted(Language::English, &allowed, 20, 4);
+        assert_eq!(text, restricted(Language::English, &allowed, 20, 4));
+        assert_eq!(text.split(' ').count(), 20);
+        assert!(
+            text.chars().all(|c| c == ' ' || allowed.contains(&c)),
+            "{text}"
+        );
+
+        // Real words run out early in a course, so the text is made up instead
+        let allowed: HashSet<char> = "fj".chars().collect();
+        let text = restricted(Language::English, &allowed, 5, 4);
+        assert_eq!(text.split(' ').count(), 5);
+        assert!(
+            text.chars().all(|c| c == ' ' || allowed.contains(&c)),
+            "{text}"
+        );
+
+        assert_eq!(restricted(Language::English, &HashSet::new(), 5, 4), "");
+    }
+
+    #[test]
+    fn test_number_drill() {
+        let lines = number_drill(Language::English, NumberDrill::Amounts, ",", 3);
+        assert_eq!(lines.len(), NUMBER_DRILL_LINE_COUNT);
+        assert_eq!(
+            lines,
+            number_drill(Language::English, NumberDrill::Amounts, ",", 3)
+        );
+
+        for amount in lines.iter().flat_map(|line| line.split(' ')) {
+            let (units, cents) = amount.split_once(',').unwrap();
+            assert!(!units.is_empty() && units.chars().all(|c| c.is_ascii_digit()));
+            assert_eq!(cents.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_number_drill_dates() {
+        for line in number_drill(Language::English, NumberDrill::Dates, ".", 11) {
+            for date in line.split(' ') {
+                let parts: Vec<u32> = date.split('/').map(|p| p.parse().unwrap()).collect();
+                assert!((1..=31).contains(&parts[0]), "{date}");
+                assert!((1..=12).contains(&parts[1]), "{date}");
+                assert!((1950..2050).contains(&parts[2]), "{date}");
+            }
+        }
+
+        // Scripts with their own digits use them for dates too
+        let persian = number_drill(Language::Persian, NumberDrill::Dates, ".", 11);
+        assert!(persian[0]
+            .chars()
+            .all(|c| c == '/' || c == ' ' || PERSIAN_NUMERALS.contains(&c.to_string().as_str())));
+    }
+}
@@ -24,6 +24,7 @@ use unicode_segmentation::UnicodeSegmentation;
 // String replacements for when text is displayed in the text view
 const REPLACEMENTS: &[(&str, &str)] = &[
     ("\n", "↲\n"), // Visually indicate enter
+    ("\t", "⇥\t"), // Visually indicate tab
 ];
 
 // Accepted alternate ways to type out certain characters
@@ -256,6 +257,40 @@ pub fn line_offset_with_replacements(
     (line_num, byte_offset)
 }
 
+// Leading whitespace of the next line in the original text, if the typed text has just
+// correctly finished a line break. Code indentation is skipped over instead of typed out.
+pub fn indentation_to_skip<'a>(original: &'a str, typed: &str) -> Option<&'a str> {
+    if !typed.ends_with('\n') {
+        return None;
+    }
+
+    let typed_graphemes = typed.graphemes(true).count();
+    let (next_line_idx, newline) = original.grapheme_indices(true).nth(typed_graphemes - 1)?;
+    if newline != "\n" {
+        return None;
+    }
+
+    let next_line = &original[next_line_idx + newline.len()..];
+    let indentation_len = next_line.len() - next_line.trim_start_matches([' ', '\t']).len();
+
+    (indentation_len > 0).then(|| &next_line[..indentation_len])
+}
+
+// Grapheme count of indentation at the end of the typed text that was skipped over by
+// `indentation_to_skip()`, so it can be removed together with the line break before it
+pub fn skipped_indentation_len(original: &str, typed: &str) -> usize {
+    let Some(line_start) = typed.rfind('\n').map(|i| i + 1) else {
+        return 0;
+    };
+
+    let indentation = &typed[line_start..];
+    if indentation_to_skip(original, &typed[..line_start]) == Some(indentation) {
+        indentation.graphemes(true).count()
+    } else {
+        0
+    }
+}
+
 pub fn pop_grapheme_in_place(s: &mut String, graphemes: usize) {
     for _ in 0..graphemes {
         let last_chars = s.graphemes(true).next_back().unwrap_or("").chars().count();
@@ -295,13 +330,220 @@ pub fn current_word(original: &str, typed_grapheme_count: usize) -> usize {
         .count()
 }
 
+// How many graphemes were typed right from the start, up to the first mistake
+pub fn correct_prefix_len(original: &str, typed: &str) -> usize {
+    zip(original.graphemes(true), typed.graphemes(true))
+        .take_while(|(og, tg)| og == tg)
+        .count()
+}
+
 pub fn calculate_wpm(duration: Duration, original: &str, typed: &str) -> f64 {
     let minutes = duration.as_secs_f64() / 60.;
 
+    // Indentation after a correct line break was skipped over by `indentation_to_skip()`
+    // rather than typed, so it doesn't count
+    let mut skipping_indentation = false;
     let correct_graphemes = zip(original.graphemes(true), typed.graphemes(true))
-        .filter(|(og, tg)| og == tg)
+        .filter(|(og, tg)| {
+            let correct = og == tg;
+            let skipped = skipping_indentation && correct && matches!(*og, " " | "\t");
+            skipping_indentation = skipped || (correct && *og == "\n");
+            correct && !skipped
+        })
         .count();
     let words = correct_graphemes as f64 / 5.;
 
     words / minutes
 }
+
+// Splits text into phrases short enough to be typed after hearing them once, ending them
+// at punctuation when there's a pause to be heard
+pub fn dictation_phrases(text: &str, max_words: usize) -> Vec<String> {
+    let mut phrases = Vec::new();
+    let mut phrase: Vec<&str> = Vec::new();
+
+    for word in text.split_whitespace() {
+        phrase.push(word);
+        let ends_clause = word.ends_with(['.', ',', ';', ':', '!', '?']);
+        if phrase.len() >= max_words || (ends_clause && phrase.len() >= max_words / 2) {
+            phrases.push(phrase.join(" "));
+            phrase.clear();
+        }
+    }
+    if !phrase.is_empty() {
+        phrases.push(phrase.join(" "));
+    }
+
+    phrases
+}
+
+// How a transcription compares to the original, word by word
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TranscriptionScore {
+    pub correct: usize,
+    pub wrong: usize,
+    pub missed: usize,
+    pub extra: usize,
+}
+
+impl TranscriptionScore {
+    // One minus the word error rate, without going below zero when there are extra words
+    pub fn accuracy(&self) -> f64 {
+        let original_words = self.correct + self.wrong + self.missed;
+        if original_words == 0 {
+            return 0.;
+        }
+
+        let errors = self.wrong + self.missed + self.extra;
+        (1. - errors as f64 / original_words as f64).max(0.)
+    }
+}
+
+// Case and the punctuation around words can't be heard, so they don't count
+fn transcription_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+// Aligns the words of both texts with the fewest edits, then counts what each edit was
+pub fn transcription_score(original: &str, typed: &str) -> TranscriptionScore {
+    let original = transcription_words(original);
+    let typed = transcription_words(typed);
+
+    let mut distances = vec![vec![0; typed.len() + 1]; original.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=original.len() {
+        for j in 1..=typed.len() {
+            let substitution =
+                distances[i - 1][j - 1] + usize::from(original[i - 1] != typed[j - 1]);
+            distances[i][j] = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+        }
+    }
+
+    let mut score = TranscriptionScore::default();
+    let (mut i, mut j) = (original.len(), typed.len());
+    while i > 0 || j > 0 {
+        let same = i > 0 && j > 0 && original[i - 1] == typed[j - 1];
+        if i > 0 && j > 0 && distances[i][j] == distances[i - 1][j - 1] + usize::from(!same) {
+            if same {
+                score.correct += 1;
+            } else {
+                score.wrong += 1;
+            }
+            i -= 1;
+            j -= 1;
+        } else if i > 0 && distances[i][j] == distances[i - 1][j] + 1 {
+            score.missed += 1;
+            i -= 1;
+        } else {
+            score.extra += 1;
+            j -= 1;
+        }
+    }
+
+    score
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CODE: &str = "fn main() {\n    if true {\n\t\tloop {}\n    }\n}";
+
+    #[test]
+    fn test_indentation_to_skip() {
+        assert_eq!(indentation_to_skip(CODE, "fn main() {\n"), Some("    "));
+        assert_eq!(
+            indentation_to_skip(CODE, "fn main() {\n    if true {\n"),
+            Some("\t\t")
+        );
+        assert_eq!(indentation_to_skip(CODE, "fn main() {"), None);
+        // A line break typed in the wrong place doesn't skip anything
+        assert_eq!(indentation_to_skip(CODE, "fn main()\n"), None);
+        assert_eq!(
+            indentation_to_skip(CODE, "fn main() {\n    if true {\n\t\tloop {}\n    }\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_skipped_indentation_len() {
+        assert_eq!(skipped_indentation_len(CODE, "fn main() {\n    "), 4);
+        assert_eq!(skipped_indentation_len(CODE, "fn main() {\n  "), 0);
+        assert_eq!(skipped_indentation_len(CODE, "fn main() {\n    i"), 0);
+        assert_eq!(skipped_indentation_len(CODE, "fn main() {"), 0);
+    }
+
+    #[test]
+    fn test_calculate_wpm() {
+        let minute = Duration::from_secs(60);
+        assert_eq!(calculate_wpm(minute, "the cat", "the cat"), 7. / 5.);
+        assert_eq!(calculate_wpm(minute, "the cat", "thx cat"), 6. / 5.);
+
+        // Only "fn main() {", the line breaks and "if true {" were typed
+        let typed = "fn main() {\n    if true {\n\t\t";
+        assert_eq!(calculate_wpm(minute, CODE, typed), 22. / 5.);
+        // Indentation typed after a wrong line break wasn't skipped, so it counts
+        assert_eq!(calculate_wpm(minute, "a\n  b", "a   b"), 4. / 5.);
+    }
+
+    #[test]
+    fn test_correct_prefix_len() {
+        assert_eq!(correct_prefix_len("the cat", "the cat"), 7);
+        assert_eq!(correct_prefix_len("the cat", "the"), 3);
+        assert_eq!(correct_prefix_len("the cat", "thx cat"), 2);
+        assert_eq!(correct_prefix_len("the cat", "asdfghjkl"), 0);
+        assert_eq!(correct_prefix_len("", "abc"), 0);
+    }
+
+    #[test]
+    fn test_tab_replacement() {
+        assert_eq!(insert_replacements("a\tb\n"), "a⇥\tb↲\n");
+    }
+
+    #[test]
+    fn test_dictation_phrases() {
+        assert_eq!(
+            dictation_phrases("One two three. Four five six seven eight nine ten.", 4),
+            ["One two three.", "Four five six seven", "eight nine ten."]
+        );
+        assert!(dictation_phrases(" ", 4).is_empty());
+    }
+
+    #[test]
+    fn test_transcription_score() {
+        let score = transcription_score("The quick, brown fox.", "the quick brwn fox jumps");
+        assert_eq!(
+            score,
+            TranscriptionScore {
+                correct: 3,
+                wrong: 1,
+                missed: 0,
+                extra: 1,
+            }
+        );
+        assert_eq!(score.accuracy(), 0.5);
+
+        let score = transcription_score("one two three", "one three");
+        assert_eq!((score.correct, score.missed), (2, 1));
+
+        assert_eq!(transcription_score("One, two!", "one two").accuracy(), 1.);
+        assert_eq!(transcription_score("one two", "").accuracy(), 0.);
+        assert_eq!(
+            transcription_score("one", "one two three four").accuracy(),
+            0.
+        );
+    }
+}
@@ -3,7 +3,10 @@ use gtk::subclass::prelude::*;
 use gtk::{glib, pango};
 use libadwaita as adw;
 use libadwaita::subclass::prelude::*;
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
+use std::time::Instant;
+
+use crate::sounds::{self, Sound};
 
 mod imp {
     use super::*;
@@ -18,6 +21,8 @@ mod imp {
         #[template_child]
         pub repetition_label: TemplateChild<gtk::Label>,
         pub cursor_position: Cell<i32>,
+        // When each key of the current attempt was typed, mistakes included
+        pub keystrokes: RefCell<Vec<Instant>>,
     }
 
     #[glib::object_subclass]
@@ -120,6 +125,8 @@ impl imp::TypingRow {
 
                     // Check if the new text would match target text
                     if !target_str.starts_with(typed_str) && !typed_str.is_empty() {
+                        imp.keystrokes.borrow_mut().push(Instant::now());
+
                         // Show error animation
                         typing_row.show_error();
 
@@ -152,6 +159,10 @@ impl imp::TypingRow {
                     }
 
                     let cursor_pos = typed_str.chars().count() as i32;
+                    if cursor_pos > imp.cursor_position.get() {
+                        imp.keystrokes.borrow_mut().push(Instant::now());
+                        sounds::play(Sound::Keystroke);
+                    }
                     imp.cursor_position.set(cursor_pos);
                     typing_row.queue_draw();
 
@@ -252,11 +263,30 @@ impl TypingRow {
         let imp = self.imp();
         imp.target_label.set_text(text);
         imp.cursor_position.set(0);
+        // Read out when the input gets the focus, as the label isn't focusable
+        imp.text_input
+            .update_property(&[gtk::accessible::Property::Label(text)]);
+        imp.keystrokes.borrow_mut().clear();
         self.queue_draw();
     }
 
     pub fn clear(&self) {
-        self.imp().text_input.buffer().set_text("");
+        let imp = self.imp();
+        imp.text_input.buffer().set_text("");
+        imp.keystrokes.borrow_mut().clear();
+    }
+
+    // The character to type after what has been typed so far
+    pub fn next_char(&self) -> Option<char> {
+        let imp = self.imp();
+        imp.target_label
+            .text()
+            .chars()
+            .nth(imp.cursor_position.get() as usize)
+    }
+
+    pub fn keystrokes(&self) -> Vec<Instant> {
+        self.imp().keystrokes.borrow().clone()
     }
 
     pub fn set_repetition_text(&self, text: &str) {
@@ -265,6 +295,7 @@ impl TypingRow {
 
     fn show_error(&self) {
         self.add_css_class("typing-error");
+        sounds::play(Sound::Error);
 
         glib::timeout_add_local_once(std::time::Duration::from_millis(400), {
             let typing_row = self.clone();
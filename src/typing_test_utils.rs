@@ -17,19 +17,26 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use crate::text_generation::Language;
+use crate::code_snippets::ProgrammingLanguage;
+use crate::pacing::rhythm_score;
+use crate::text_generation::{random_seed, Language};
 use crate::text_utils::calculate_wpm;
 use gettextrs::gettext;
 use gtk::gio;
 use gtk::prelude::*;
 use std::str::FromStr;
 use std::time::{Duration, Instant, SystemTime};
+use strum::IntoEnumIterator;
 use strum_macros::{Display as EnumDisplay, EnumIter, EnumString};
 
 #[derive(Clone, Copy, PartialEq, EnumString, EnumDisplay)]
 pub enum GeneratedTestDifficulty {
     Simple,
     Advanced,
+    Quotes,
+    Code(ProgrammingLanguage),
+    // Words from the custom word list selected in preferences
+    Custom,
 }
 
 impl GeneratedTestDifficulty {
@@ -37,7 +44,35 @@ impl GeneratedTestDifficulty {
         match s {
             "simple" => Some(GeneratedTestDifficulty::Simple),
             "advanced" => Some(GeneratedTestDifficulty::Advanced),
-            _ => None,
+            "quotes" => Some(GeneratedTestDifficulty::Quotes),
+            "custom" => Some(GeneratedTestDifficulty::Custom),
+            _ => s
+                .strip_prefix("code-")
+                .and_then(|code| ProgrammingLanguage::from_str(code).ok())
+                .map(GeneratedTestDifficulty::Code),
+        }
+    }
+
+    // Short form used in challenge codes, e.g. "a" for advanced or "py" for Python code.
+    // Custom word lists only exist on one computer, so they can't be shared.
+    fn challenge_string(&self) -> Option<String> {
+        match self {
+            GeneratedTestDifficulty::Simple => Some("s".to_string()),
+            GeneratedTestDifficulty::Advanced => Some("a".to_string()),
+            GeneratedTestDifficulty::Quotes => Some("q".to_string()),
+            GeneratedTestDifficulty::Code(language) => Some(language.to_string()),
+            GeneratedTestDifficulty::Custom => None,
+        }
+    }
+
+    fn from_challenge_string(s: &str) -> Option<Self> {
+        match s {
+            "s" => Some(GeneratedTestDifficulty::Simple),
+            "a" => Some(GeneratedTestDifficulty::Advanced),
+            "q" => Some(GeneratedTestDifficulty::Quotes),
+            _ => ProgrammingLanguage::from_str(s)
+                .ok()
+                .map(GeneratedTestDifficulty::Code),
         }
     }
 }
@@ -49,22 +84,101 @@ pub enum TestConfig {
         language: Language,
         difficulty: GeneratedTestDifficulty,
         duration: TestDuration,
+        // How many of the most common words the text is made of, `None` for all of them
+        vocabulary: Option<usize>,
+        seed: u64,
     },
 }
 
 impl TestConfig {
     pub fn from_settings(settings: &gio::Settings) -> Self {
         match settings.string("session-type").as_str() {
-            difficulty_string @ ("Simple" | "Advanced") => TestConfig::Generated {
+            difficulty_string @ ("Simple" | "Advanced" | "Quotes") => TestConfig::Generated {
                 language: Language::from_str(&settings.string("text-language"))
                     .unwrap_or(Language::English),
                 difficulty: GeneratedTestDifficulty::from_str(difficulty_string).unwrap(),
                 duration: TestDuration::from_str(&settings.string("session-duration")).unwrap(),
+                vocabulary: None,
+                seed: random_seed(),
             },
             "Custom" => TestConfig::Finite,
             _ => panic!("invalid settings value for `session-type` key"),
         }
     }
+
+    // Challenge codes identify a generated test so it can be shared and typed again
+    // by someone else. They look like "fr-a30-1b5gqf7ny2kzl": the language code, the
+    // short difficulty followed by the duration in seconds, and the seed in base 36.
+    // A limited vocabulary is added after the duration, like "fr-a30w500-1b5gqf7ny2kzl".
+    pub fn challenge_code(&self) -> Option<String> {
+        match self {
+            TestConfig::Finite => None,
+            TestConfig::Generated {
+                language,
+                difficulty,
+                duration,
+                vocabulary,
+                seed,
+            } => Some(format!(
+                "{language}-{}{}{}-{}",
+                difficulty.challenge_string()?,
+                duration.as_seconds(),
+                vocabulary
+                    .map(|count| format!("w{count}"))
+                    .unwrap_or_default(),
+                to_base36(*seed)
+            )),
+        }
+    }
+
+    pub fn from_challenge_code(code: &str) -> Option<Self> {
+        let code = code.trim().to_lowercase();
+        let mut parts = code.split('-');
+        let (Some(language), Some(settings), Some(seed), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+
+        // The code is lowercased above, so language codes like "de_CH" are compared the same way
+        let language = Language::iter().find(|l| l.to_string().to_lowercase() == language)?;
+
+        let digits_start = settings.find(|c: char| c.is_ascii_digit())?;
+        let difficulty = GeneratedTestDifficulty::from_challenge_string(&settings[..digits_start])?;
+        let (seconds, vocabulary) = match settings[digits_start..].split_once('w') {
+            Some((seconds, vocabulary)) => {
+                let count: usize = vocabulary.parse().ok().filter(|count| *count > 0)?;
+                (seconds, Some(count))
+            }
+            None => (&settings[digits_start..], None),
+        };
+        let seconds: u64 = seconds.parse().ok()?;
+        let duration = TestDuration::iter().find(|d| d.as_seconds() == seconds)?;
+
+        Some(TestConfig::Generated {
+            language,
+            difficulty,
+            duration,
+            vocabulary,
+            seed: u64::from_str_radix(seed, 36).ok()?,
+        })
+    }
+}
+
+fn to_base36(mut n: u64) -> String {
+    const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+    let mut digits = Vec::new();
+    loop {
+        digits.push(DIGITS[(n % 36) as usize]);
+        n /= 36;
+        if n == 0 {
+            break;
+        }
+    }
+    digits.reverse();
+
+    String::from_utf8(digits).expect("base 36 digits are ASCII")
 }
 
 #[derive(Copy, Clone, Default, PartialEq, EnumString, EnumDisplay, EnumIter)]
@@ -116,21 +230,25 @@ pub struct TestSummary {
     pub wpm: f64,
     pub start_timestamp: SystemTime,
     pub accuracy: f64,
+    // How evenly keystrokes followed the metronome, when pacing
+    pub rhythm: Option<f64>,
 }
 
 impl TestSummary {
     pub fn new(
         start_timestamp: SystemTime,
-        start_instant: Instant,
-        end_instant: Instant,
+        // Time spent typing, without the pauses
+        real_duration: Duration,
         config: TestConfig,
         original: &str,
         typed: &str,
         keystrokes: &[(Instant, bool)],
+        // Tempo of the metronome, if there was one, and when the test was paused
+        pacing: Option<(u32, &[Instant])>,
     ) -> Self {
-        let real_duration = end_instant.duration_since(start_instant);
         let correct_keystrokes = keystrokes.iter().filter(|(_, correct)| *correct).count();
         let total_keystrokes = keystrokes.len();
+        let times: Vec<Instant> = keystrokes.iter().map(|(time, _)| *time).collect();
 
         TestSummary {
             config,
@@ -138,6 +256,89 @@ impl TestSummary {
             wpm: calculate_wpm(real_duration, original, typed),
             start_timestamp,
             accuracy: correct_keystrokes as f64 / total_keystrokes as f64,
+            rhythm: pacing.and_then(|(bpm, pauses)| rhythm_score(&times, pauses, bpm)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_code_round_trip() {
+        let config = TestConfig::Generated {
+            language: Language::SwissGerman,
+            difficulty: GeneratedTestDifficulty::Advanced,
+            duration: TestDuration::Min5,
+            vocabulary: None,
+            seed: u64::MAX,
+        };
+
+        let code = config.challenge_code().unwrap();
+        assert_eq!(code, "de_CH-a300-3w5e11264sgsf");
+        assert!(TestConfig::from_challenge_code(&code) == Some(config));
+        let code_config = TestConfig::Generated {
+            language: Language::English,
+            difficulty: GeneratedTestDifficulty::Code(ProgrammingLanguage::Shell),
+            duration: TestDuration::Sec15,
+            vocabulary: None,
+            seed: 0,
+        };
+        assert_eq!(code_config.challenge_code().unwrap(), "en-sh15-0");
+        assert!(TestConfig::from_challenge_code("en-sh15-0") == Some(code_config));
+        assert!(
+            TestConfig::from_challenge_code(&format!("  {}\n", code.to_uppercase()))
+                == Some(config)
+        );
+
+        let vocabulary_config = TestConfig::Generated {
+            language: Language::English,
+            difficulty: GeneratedTestDifficulty::Simple,
+            duration: TestDuration::Min1,
+            vocabulary: Some(500),
+            seed: 35,
+        };
+        assert_eq!(vocabulary_config.challenge_code().unwrap(), "en-s60w500-z");
+        assert!(TestConfig::from_challenge_code("en-s60w500-z") == Some(vocabulary_config));
+
+        // The longest tests are encoded in seconds like the others
+        let long_config = TestConfig::Generated {
+            language: Language::English,
+            difficulty: GeneratedTestDifficulty::Simple,
+            duration: TestDuration::Min10,
+            vocabulary: None,
+            seed: 35,
+        };
+        assert_eq!(long_config.challenge_code().unwrap(), "en-s600-z");
+        assert!(TestConfig::from_challenge_code("en-s600-z") == Some(long_config));
+    }
+
+    #[test]
+    fn test_invalid_challenge_codes() {
+        assert!(TestConfig::Finite.challenge_code().is_none());
+        let custom = TestConfig::Generated {
+            language: Language::English,
+            difficulty: GeneratedTestDifficulty::Custom,
+            duration: TestDuration::Sec30,
+            vocabulary: None,
+            seed: 1,
+        };
+        assert!(custom.challenge_code().is_none());
+        for code in [
+            "",
+            "fr",
+            "fr-a30",
+            "xx-a30-1",
+            "fr-x30-1",
+            "fr-30-1",
+            "fr-a31-1",
+            "fr-a30-!",
+            "fr-a30-1-2",
+            "fr-a30w-1",
+            "fr-a30w0-1",
+        ] {
+            assert!(TestConfig::from_challenge_code(code).is_none(), "{code}");
         }
     }
 }
@@ -8,12 +8,20 @@ use libadwaita::subclass::prelude::*;
 
 use crate::config;
 use crate::course::Lesson;
+use crate::dictation_view::DictationView;
 use crate::falling_keys_game::FallingKeysGame;
+use crate::game_engine::ArcadeGame;
+use crate::lan_race_game::LanRaceGame;
+use crate::leaderboard_view::LeaderboardView;
 use crate::lesson_view::LessonView;
+use crate::number_drill_view::NumberDrillView;
+use crate::pause::Pausable;
 use crate::preferences_view::PreferencesView;
+use crate::race_game::RaceGame;
 use crate::scrolling_lanes_game::ScrollingLanesGame;
 use crate::speed_test_view::SpeedTestView;
 use crate::typing_row::TypingRow;
+use crate::word_defense_game::WordDefenseGame;
 
 mod imp {
     use super::*;
@@ -34,10 +42,22 @@ mod imp {
         #[template_child]
         pub speed_test_row: TemplateChild<adw::ActionRow>,
         #[template_child]
+        pub number_drill_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub dictation_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
         pub falling_keys_row: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub scrolling_lanes_row: TemplateChild<adw::ActionRow>,
         #[template_child]
+        pub word_defense_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub race_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub lan_race_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub leaderboard_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
         pub preferences_row: TemplateChild<adw::ActionRow>,
         #[template_child]
         pub about_row: TemplateChild<adw::ActionRow>,
@@ -109,10 +129,12 @@ impl MecalinWindow {
         imp.window_title.set_title(&gettext("Falling Keys"));
         imp.window_title.set_subtitle("");
 
-        // Reset game when showing
+        // Reset game when showing, unless it was paused to be picked up again
         if let Some(game) = imp.main_stack.child_by_name("game") {
             if let Ok(game) = game.downcast::<FallingKeysGame>() {
-                game.reset();
+                if !game.is_paused() {
+                    game.reset();
+                }
             }
         }
     }
@@ -124,14 +146,105 @@ impl MecalinWindow {
         imp.window_title.set_title(&gettext("Scrolling Lanes"));
         imp.window_title.set_subtitle("");
 
-        // Reset game when showing
+        // Reset game when showing, unless it was paused to be picked up again
         if let Some(game) = imp.main_stack.child_by_name("lanes_game") {
             if let Ok(game) = game.downcast::<ScrollingLanesGame>() {
+                if !game.is_paused() {
+                    game.reset();
+                }
+            }
+        }
+    }
+
+    pub fn show_defense_game(&self) {
+        let imp = self.imp();
+
+        // Create word defense game if it doesn't exist
+        if imp.main_stack.child_by_name("defense_game").is_none() {
+            let game = WordDefenseGame::new();
+            imp.main_stack.add_named(&game, Some("defense_game"));
+        }
+
+        imp.main_stack.set_visible_child_name("defense_game");
+        imp.back_button.set_visible(true);
+        imp.window_title.set_title(&gettext("Word Defense"));
+        imp.window_title.set_subtitle("");
+
+        // Reset game when showing, unless it was paused to be picked up again
+        if let Some(game) = imp.main_stack.child_by_name("defense_game") {
+            if let Ok(game) = game.downcast::<WordDefenseGame>() {
+                if !game.is_paused() {
+                    game.reset();
+                }
+            }
+        }
+    }
+
+    pub fn show_race_game(&self) {
+        let imp = self.imp();
+
+        // Create race game if it doesn't exist
+        if imp.main_stack.child_by_name("race_game").is_none() {
+            let game = RaceGame::new();
+            imp.main_stack.add_named(&game, Some("race_game"));
+        }
+
+        imp.main_stack.set_visible_child_name("race_game");
+        imp.back_button.set_visible(true);
+        imp.window_title.set_title(&gettext("Typing Race"));
+        imp.window_title.set_subtitle("");
+
+        if let Some(game) = imp.main_stack.child_by_name("race_game") {
+            if let Ok(game) = game.downcast::<RaceGame>() {
+                if !game.is_paused() {
+                    game.reset();
+                }
+            }
+        }
+    }
+
+    pub fn show_lan_race_game(&self) {
+        let imp = self.imp();
+
+        // Create network race if it doesn't exist
+        if imp.main_stack.child_by_name("lan_race_game").is_none() {
+            let game = LanRaceGame::new();
+            imp.main_stack.add_named(&game, Some("lan_race_game"));
+        }
+
+        imp.main_stack.set_visible_child_name("lan_race_game");
+        imp.back_button.set_visible(true);
+        imp.window_title.set_title(&gettext("Network Race"));
+        imp.window_title.set_subtitle("");
+
+        if let Some(game) = imp.main_stack.child_by_name("lan_race_game") {
+            if let Ok(game) = game.downcast::<LanRaceGame>() {
                 game.reset();
             }
         }
     }
 
+    pub fn show_leaderboard(&self) {
+        let imp = self.imp();
+
+        // Create leaderboard view if it doesn't exist
+        if imp.main_stack.child_by_name("leaderboard").is_none() {
+            let leaderboard = LeaderboardView::new();
+            imp.main_stack.add_named(&leaderboard, Some("leaderboard"));
+        }
+
+        if let Some(leaderboard) = imp.main_stack.child_by_name("leaderboard") {
+            if let Ok(leaderboard) = leaderboard.downcast::<LeaderboardView>() {
+                leaderboard.refresh();
+            }
+        }
+
+        imp.main_stack.set_visible_child_name("leaderboard");
+        imp.back_button.set_visible(true);
+        imp.window_title.set_title(&gettext("High Scores"));
+        imp.window_title.set_subtitle("");
+    }
+
     pub fn show_speed_test(&self) {
         let imp = self.imp();
 
@@ -147,12 +260,66 @@ impl MecalinWindow {
         imp.window_title.set_subtitle("");
     }
 
+    pub fn show_number_drill(&self) {
+        let imp = self.imp();
+
+        // Create number drill view if it doesn't exist
+        if imp.main_stack.child_by_name("number_drill").is_none() {
+            let number_drill = NumberDrillView::new();
+            imp.main_stack
+                .add_named(&number_drill, Some("number_drill"));
+        }
+
+        imp.main_stack.set_visible_child_name("number_drill");
+        imp.back_button.set_visible(true);
+        imp.window_title.set_title(&gettext("Numeric Keypad"));
+        imp.window_title.set_subtitle("");
+
+        if let Some(number_drill) = imp.main_stack.child_by_name("number_drill") {
+            number_drill.grab_focus();
+        }
+    }
+
+    pub fn show_dictation(&self) {
+        let imp = self.imp();
+
+        // Create dictation view if it doesn't exist
+        if imp.main_stack.child_by_name("dictation").is_none() {
+            let dictation = DictationView::new();
+            imp.main_stack.add_named(&dictation, Some("dictation"));
+        }
+
+        imp.main_stack.set_visible_child_name("dictation");
+        imp.back_button.set_visible(true);
+        imp.window_title.set_title(&gettext("Dictation"));
+        imp.window_title.set_subtitle("");
+
+        if let Some(dictation) = imp.main_stack.child_by_name("dictation") {
+            if let Ok(dictation) = dictation.downcast::<DictationView>() {
+                dictation.start();
+                dictation.grab_focus();
+            }
+        }
+    }
+
     pub fn go_back(&self) {
         let imp = self.imp();
         let current_page = imp.main_stack.visible_child_name();
 
-        if let Some("lessons" | "game" | "lanes_game" | "speed_test" | "preferences") =
-            current_page.as_deref()
+        // Games and tests wait for the player to come back
+        self.pause_visible_page();
+
+        // Others can't wait for us in a network race, so we leave it
+        if let Some(game) = imp.main_stack.visible_child() {
+            if let Some(game) = game.downcast_ref::<LanRaceGame>() {
+                game.reset();
+            }
+        }
+
+        if let Some(
+            "lessons" | "game" | "lanes_game" | "defense_game" | "race_game" | "lan_race_game"
+            | "leaderboard" | "speed_test" | "number_drill" | "dictation" | "preferences",
+        ) = current_page.as_deref()
         {
             imp.main_stack.set_visible_child_name("main_menu");
             imp.back_button.set_visible(false);
@@ -161,6 +328,25 @@ impl MecalinWindow {
         }
     }
 
+    // Stops the game or test on screen, if there's one running
+    fn pause_visible_page(&self) {
+        let Some(page) = self.imp().main_stack.visible_child() else {
+            return;
+        };
+
+        if let Some(game) = page.downcast_ref::<FallingKeysGame>() {
+            game.pause();
+        } else if let Some(game) = page.downcast_ref::<ScrollingLanesGame>() {
+            game.pause();
+        } else if let Some(game) = page.downcast_ref::<WordDefenseGame>() {
+            game.pause();
+        } else if let Some(game) = page.downcast_ref::<RaceGame>() {
+            game.pause();
+        } else if let Some(speed_test) = page.downcast_ref::<SpeedTestView>() {
+            speed_test.pause();
+        }
+    }
+
     pub fn show_about(&self) {
         let about = adw::AboutDialog::builder()
             .application_name("Mecalin")
@@ -324,6 +510,34 @@ impl imp::MecalinWindow {
             }
         });
 
+        let window = self.obj().downgrade();
+        self.word_defense_row.connect_activated(move |_| {
+            if let Some(window) = window.upgrade() {
+                window.show_defense_game();
+            }
+        });
+
+        let window = self.obj().downgrade();
+        self.race_row.connect_activated(move |_| {
+            if let Some(window) = window.upgrade() {
+                window.show_race_game();
+            }
+        });
+
+        let window = self.obj().downgrade();
+        self.lan_race_row.connect_activated(move |_| {
+            if let Some(window) = window.upgrade() {
+                window.show_lan_race_game();
+            }
+        });
+
+        let window = self.obj().downgrade();
+        self.leaderboard_row.connect_activated(move |_| {
+            if let Some(window) = window.upgrade() {
+                window.show_leaderboard();
+            }
+        });
+
         let window = self.obj().downgrade();
         self.speed_test_row.connect_activated(move |_| {
             if let Some(window) = window.upgrade() {
@@ -331,6 +545,20 @@ impl imp::MecalinWindow {
             }
         });
 
+        let window = self.obj().downgrade();
+        self.number_drill_row.connect_activated(move |_| {
+            if let Some(window) = window.upgrade() {
+                window.show_number_drill();
+            }
+        });
+
+        let window = self.obj().downgrade();
+        self.dictation_row.connect_activated(move |_| {
+            if let Some(window) = window.upgrade() {
+                window.show_dictation();
+            }
+        });
+
         let window = self.obj().downgrade();
         self.about_row.connect_activated(move |_| {
             if let Some(window) = window.upgrade() {
@@ -345,6 +573,13 @@ impl imp::MecalinWindow {
             }
         });
 
+        // Nobody is typing while another window has the focus
+        self.obj().connect_is_active_notify(|window| {
+            if !window.is_active() {
+                window.pause_visible_page();
+            }
+        });
+
         let window = self.obj().downgrade();
         self.back_button.connect_clicked(move |_| {
             if let Some(window) = window.upgrade() {
@@ -0,0 +1,798 @@
+use gtk::gdk;
+use gtk::glib;
+use gtk::graphene;
+use gtk::pango;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+use i18n_format::i18n_fmt;
+use rand::Rng;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::game_engine::{ArcadeGame, Difficulty, DifficultyCurve, Entity, GameState, GameWidgets};
+use crate::game_setup::{self, GameSetup};
+use crate::high_scores::Game;
+use crate::pause::Pausable;
+use crate::pause_overlay::{self, PauseOverlay};
+use crate::sounds::{self, Sound};
+use crate::text_generation::WordList;
+
+// Height of the base at the bottom of the play area, in pixels
+const BASE_HEIGHT: f64 = 12.0;
+// Every third wave ends with a boss sentence
+const BOSS_WAVE_INTERVAL: u32 = 3;
+const BOSS_WORDS: usize = 5;
+// Bosses are long, so they approach at half the speed of words
+const BOSS_SPEED_FACTOR: f64 = 0.5;
+// Chance of a word carrying a power-up
+const POWER_UP_CHANCE: f64 = 0.1;
+const SLOW_TIME_SECONDS: f64 = 5.0;
+const SLOW_TIME_FACTOR: f64 = 0.5;
+
+const WORD_FONT: &str = "Sans 20";
+const BOSS_FONT: &str = "Sans Bold 24";
+
+// How fast words approach, in pixels per second, and how often they appear, by wave
+fn difficulty_curve(difficulty: Difficulty) -> DifficultyCurve {
+    match difficulty {
+        Difficulty::Easy => DifficultyCurve {
+            start_speed: 20.0,
+            speed_step: 4.0,
+            points_per_level: 10,
+            spawn_interval: 2.5,
+            spawn_interval_step: 0.15,
+            min_spawn_interval: 1.2,
+            max_objects: 6,
+        },
+        Difficulty::Normal => DifficultyCurve {
+            start_speed: 30.0,
+            speed_step: 6.0,
+            points_per_level: 10,
+            spawn_interval: 2.0,
+            spawn_interval_step: 0.15,
+            min_spawn_interval: 0.9,
+            max_objects: 8,
+        },
+        Difficulty::Hard => DifficultyCurve {
+            start_speed: 40.0,
+            speed_step: 8.0,
+            points_per_level: 10,
+            spawn_interval: 1.5,
+            spawn_interval_step: 0.1,
+            min_spawn_interval: 0.6,
+            max_objects: 10,
+        },
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    // Words approach at half the speed for a while
+    SlowTime,
+    // Every word on screen is destroyed, but not the boss
+    ClearScreen,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvaderKind {
+    Word,
+    Boss,
+    PowerUp(PowerUp),
+}
+
+// A word or sentence approaching the base, typed from its first character on
+#[derive(Clone, Debug, PartialEq)]
+pub struct Invader {
+    pub text: String,
+    pub kind: InvaderKind,
+    // Characters typed so far
+    pub typed: usize,
+}
+
+impl Invader {
+    pub fn new(text: String, kind: InvaderKind) -> Self {
+        Self {
+            text,
+            kind,
+            typed: 0,
+        }
+    }
+
+    pub fn next_char(&self) -> Option<char> {
+        self.text.chars().nth(self.typed)
+    }
+
+    // Returns whether `c` was the next character
+    pub fn type_char(&mut self, c: char) -> bool {
+        if self.next_char() == Some(c) {
+            self.typed += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        self.next_char().is_none()
+    }
+
+    // The text split into the typed and the pending part
+    pub fn split(&self) -> (&str, &str) {
+        let index = self
+            .text
+            .char_indices()
+            .nth(self.typed)
+            .map_or(self.text.len(), |(index, _)| index);
+        self.text.split_at(index)
+    }
+}
+
+pub(crate) type DefenseInvader = Entity<Invader>;
+
+// The word being typed, or else the one closest to the base that starts with `c`
+fn find_target(invaders: &[DefenseInvader], c: char) -> Option<usize> {
+    invaders
+        .iter()
+        .position(|invader| invader.value.typed > 0)
+        .or_else(|| {
+            invaders
+                .iter()
+                .enumerate()
+                .filter(|(_, invader)| invader.value.next_char() == Some(c))
+                .max_by(|(_, a), (_, b)| a.y.total_cmp(&b.y))
+                .map(|(index, _)| index)
+        })
+}
+
+// Words of a wave still to come, and the boss closing it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Wave {
+    pub number: u32,
+    words_left: u32,
+    boss_pending: bool,
+}
+
+impl Wave {
+    pub fn new(number: u32) -> Self {
+        Self {
+            number,
+            words_left: 4 + 2 * number,
+            boss_pending: number.is_multiple_of(BOSS_WAVE_INTERVAL),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        Self::new(self.number + 1)
+    }
+
+    // What comes next, the boss after all the words
+    pub fn spawn(&mut self) -> Option<InvaderKind> {
+        if self.words_left > 0 {
+            self.words_left -= 1;
+            Some(InvaderKind::Word)
+        } else if self.boss_pending {
+            self.boss_pending = false;
+            Some(InvaderKind::Boss)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_cleared(&self, invaders_left: usize) -> bool {
+        self.words_left == 0 && !self.boss_pending && invaders_left == 0
+    }
+}
+
+#[derive(Clone, Copy)]
+struct DefenseColors {
+    word: gdk::RGBA,
+    typed: gdk::RGBA,
+    power_up: gdk::RGBA,
+    boss: gdk::RGBA,
+    base: gdk::RGBA,
+}
+
+impl Default for DefenseColors {
+    fn default() -> Self {
+        Self {
+            word: gdk::RGBA::BLACK,
+            typed: gdk::RGBA::BLACK,
+            power_up: gdk::RGBA::BLACK,
+            boss: gdk::RGBA::BLACK,
+            base: gdk::RGBA::BLACK,
+        }
+    }
+}
+
+mod defense_widget {
+    use super::*;
+
+    #[derive(Default)]
+    pub struct DefenseWidget {
+        pub(super) invaders: RefCell<Rc<RefCell<Vec<DefenseInvader>>>>,
+        pub(super) colors: RefCell<DefenseColors>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for DefenseWidget {
+        const NAME: &'static str = "DefenseWidget";
+        type Type = super::DefenseWidget;
+        type ParentType = gtk::Widget;
+    }
+
+    impl ObjectImpl for DefenseWidget {}
+
+    impl WidgetImpl for DefenseWidget {
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+            let width = widget.width() as f32;
+            let height = widget.height() as f32;
+            let colors = *self.colors.borrow();
+
+            // The base words must not reach
+            let base =
+                graphene::Rect::new(0.0, height - BASE_HEIGHT as f32, width, BASE_HEIGHT as f32);
+            snapshot.append_color(&colors.base, &base);
+
+            let layout = pango::Layout::new(&widget.pango_context());
+            let word_font = pango::FontDescription::from_string(WORD_FONT);
+            let boss_font = pango::FontDescription::from_string(BOSS_FONT);
+
+            for invader in self.invaders.borrow().borrow().iter() {
+                let (font, color) = match invader.value.kind {
+                    InvaderKind::Word => (&word_font, &colors.word),
+                    InvaderKind::Boss => (&boss_font, &colors.boss),
+                    InvaderKind::PowerUp(_) => (&word_font, &colors.power_up),
+                };
+                layout.set_font_description(Some(font));
+
+                // The typed part in the accent color, followed by the rest
+                let (typed, pending) = invader.value.split();
+                snapshot.save();
+                snapshot.translate(&graphene::Point::new(invader.x as f32, invader.y as f32));
+                layout.set_text(typed);
+                snapshot.append_layout(&layout, &colors.typed);
+                let typed_width = layout.pixel_size().0 as f32;
+                snapshot.translate(&graphene::Point::new(typed_width, 0.0));
+                layout.set_text(pending);
+                snapshot.append_layout(&layout, color);
+                snapshot.restore();
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    pub struct DefenseWidget(ObjectSubclass<defense_widget::DefenseWidget>)
+        @extends gtk::Widget;
+}
+
+impl DefenseWidget {
+    pub(crate) fn new(invaders: Rc<RefCell<Vec<DefenseInvader>>>) -> Self {
+        let widget: Self = glib::Object::new();
+        widget.imp().invaders.replace(invaders);
+        widget.set_vexpand(true);
+        widget.set_hexpand(true);
+        widget
+    }
+
+    fn set_colors(&self, colors: DefenseColors) {
+        *self.imp().colors.borrow_mut() = colors;
+    }
+
+    // Width of a text in pixels, with the font it's drawn with
+    fn text_width(&self, text: &str, kind: InvaderKind) -> f64 {
+        let font = match kind {
+            InvaderKind::Boss => BOSS_FONT,
+            InvaderKind::Word | InvaderKind::PowerUp(_) => WORD_FONT,
+        };
+        let layout = pango::Layout::new(&self.pango_context());
+        layout.set_font_description(Some(&pango::FontDescription::from_string(font)));
+        layout.set_text(text);
+        layout.pixel_size().0 as f64
+    }
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, gtk::CompositeTemplate)]
+    #[template(resource = "/io/github/nacho/mecalin/ui/word_defense_game.ui")]
+    pub struct WordDefenseGame {
+        #[template_child]
+        pub game_area: TemplateChild<gtk::Overlay>,
+        #[template_child]
+        pub score_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub wave_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub status_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub power_up_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub results_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub results_score_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub results_wave_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub restart_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub setup_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub new_record_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub pause_overlay: TemplateChild<PauseOverlay>,
+        #[template_child]
+        pub game_setup: TemplateChild<GameSetup>,
+
+        pub defense_widget: RefCell<Option<DefenseWidget>>,
+        pub keyboard_widget: RefCell<Option<crate::keyboard_widget::KeyboardWidget>>,
+        pub(crate) invaders: Rc<RefCell<Vec<DefenseInvader>>>,
+        pub state: GameState,
+        pub wave: RefCell<Wave>,
+        // Seconds left of the slow time power-up
+        pub slow_time_left: RefCell<f64>,
+        // Loaded when a game starts rather than with the window
+        pub word_list: RefCell<WordList>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for WordDefenseGame {
+        const NAME: &'static str = "WordDefenseGame";
+        type Type = super::WordDefenseGame;
+        type ParentType = gtk::Box;
+
+        fn class_init(klass: &mut Self::Class) {
+            PauseOverlay::ensure_type();
+            GameSetup::ensure_type();
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &glib::subclass::InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for WordDefenseGame {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().setup_game();
+        }
+    }
+    impl WidgetImpl for WordDefenseGame {}
+    impl BoxImpl for WordDefenseGame {}
+}
+
+glib::wrapper! {
+    pub struct WordDefenseGame(ObjectSubclass<imp::WordDefenseGame>)
+        @extends gtk::Box, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget, gtk::Orientable;
+}
+
+impl WordDefenseGame {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn setup_game(&self) {
+        let imp = self.imp();
+
+        // The keyboard hints the next key of the word being typed
+        let keyboard = crate::keyboard_widget::KeyboardWidget::new();
+        imp.game_area.add_overlay(&keyboard);
+        keyboard.set_halign(gtk::Align::Center);
+        keyboard.set_valign(gtk::Align::End);
+        keyboard.set_margin_bottom(20);
+        imp.keyboard_widget.replace(Some(keyboard));
+
+        // Query colors from CSS
+        let temp_widget = gtk::Label::new(None);
+        let css_color = |class: &str| {
+            temp_widget.add_css_class(class);
+            let color = temp_widget.color();
+            temp_widget.remove_css_class(class);
+            color
+        };
+        let colors = DefenseColors {
+            word: css_color("defense-word"),
+            typed: css_color("defense-typed"),
+            power_up: css_color("defense-power-up"),
+            boss: css_color("defense-boss"),
+            base: css_color("defense-base"),
+        };
+
+        let defense_widget = DefenseWidget::new(imp.invaders.clone());
+        defense_widget.set_colors(colors);
+        defense_widget.set_can_focus(true);
+        defense_widget.set_focusable(true);
+
+        imp.game_area.add_overlay(&defense_widget);
+        imp.defense_widget.replace(Some(defense_widget.clone()));
+
+        // Setup keyboard input
+        let key_controller = gtk::EventControllerKey::new();
+        let obj = self.downgrade();
+        key_controller.connect_key_pressed(move |_, key, _, _| {
+            if let Some(obj) = obj.upgrade() {
+                if let Some(c) = key.to_unicode() {
+                    obj.handle_key_press(c);
+                }
+            }
+            glib::Propagation::Stop
+        });
+        defense_widget.add_controller(key_controller);
+
+        imp.game_setup.add_language_row();
+
+        let obj = self.downgrade();
+        imp.restart_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.start_game();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.setup_button.connect_clicked(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.reset();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.game_setup.connect_start(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.start_game();
+            }
+        });
+
+        let obj = self.downgrade();
+        imp.pause_overlay.connect_resume(move |_| {
+            if let Some(obj) = obj.upgrade() {
+                obj.resume();
+            }
+        });
+        pause_overlay::add_escape_controller(self);
+    }
+
+    fn spawn_invader(&self, kind: InvaderKind) {
+        let imp = self.imp();
+        let mut rng = rand::thread_rng();
+        let Some(defense_widget) = imp.defense_widget.borrow().clone() else {
+            return;
+        };
+
+        let word_list = imp.word_list.borrow();
+        let (text, kind) = match kind {
+            InvaderKind::Boss => {
+                let words: Vec<&str> = (0..BOSS_WORDS)
+                    .filter_map(|_| word_list.choose(&mut rng))
+                    .collect();
+                (words.join(" "), kind)
+            }
+            _ => {
+                let kind = if rng.gen_bool(POWER_UP_CHANCE) {
+                    let power_up = if rng.gen_bool(0.5) {
+                        PowerUp::SlowTime
+                    } else {
+                        PowerUp::ClearScreen
+                    };
+                    InvaderKind::PowerUp(power_up)
+                } else {
+                    kind
+                };
+                let text = word_list.choose(&mut rng).unwrap_or_default().to_string();
+                (text, kind)
+            }
+        };
+        if text.is_empty() {
+            return;
+        }
+
+        let width = defense_widget.width() as f64;
+        let text_width = defense_widget.text_width(&text, kind);
+        let max_x = (width - text_width - 20.0).max(20.0);
+        let x = if max_x > 20.0 {
+            rng.gen_range(20.0..max_x)
+        } else {
+            20.0
+        };
+
+        imp.invaders
+            .borrow_mut()
+            .push(DefenseInvader::new(Invader::new(text, kind), x, 0.0));
+    }
+
+    // Typing the first character of a word locks onto it until it's destroyed
+    fn handle_key_press(&self, c: char) {
+        let imp = self.imp();
+        if *imp.state.paused.borrow() || *imp.state.game_over.borrow() {
+            return;
+        }
+
+        let destroyed = {
+            let mut invaders = imp.invaders.borrow_mut();
+            let target = find_target(&invaders, c);
+            let correct = target.is_some_and(|index| invaders[index].value.type_char(c));
+            imp.state.stats.borrow_mut().key_pressed(correct);
+
+            let mut progress = imp.state.progress.borrow_mut();
+            if correct {
+                progress.add_point();
+            } else {
+                progress.lose_point();
+                sounds::play(Sound::Error);
+            }
+
+            target
+                .filter(|&index| invaders[index].value.is_destroyed())
+                .map(|index| invaders.remove(index).value)
+        };
+
+        if destroyed.is_some() {
+            sounds::play(Sound::Destroyed);
+        }
+        if let Some(InvaderKind::PowerUp(power_up)) = destroyed.map(|invader| invader.kind) {
+            self.apply_power_up(power_up);
+        }
+
+        self.update_hint();
+        self.update_labels();
+        if let Some(defense_widget) = imp.defense_widget.borrow().as_ref() {
+            defense_widget.queue_draw();
+        }
+    }
+
+    fn apply_power_up(&self, power_up: PowerUp) {
+        let imp = self.imp();
+        match power_up {
+            PowerUp::SlowTime => {
+                imp.slow_time_left.replace(SLOW_TIME_SECONDS);
+            }
+            PowerUp::ClearScreen => {
+                // Each word cleared is worth a point
+                let mut invaders = imp.invaders.borrow_mut();
+                let count = invaders.len();
+                invaders.retain(|invader| invader.value.kind == InvaderKind::Boss);
+                let mut progress = imp.state.progress.borrow_mut();
+                for _ in 0..count - invaders.len() {
+                    progress.add_point();
+                }
+            }
+        }
+    }
+
+    // Highlights the next key of the word being typed
+    fn update_hint(&self) {
+        let imp = self.imp();
+        let next_char = imp
+            .invaders
+            .borrow()
+            .iter()
+            .find(|invader| invader.value.typed > 0)
+            .and_then(|invader| invader.value.next_char());
+        if let Some(keyboard) = imp.keyboard_widget.borrow().as_ref() {
+            keyboard.set_current_key(next_char);
+        }
+    }
+}
+
+impl ArcadeGame for WordDefenseGame {
+    const GAME: Game = Game::WordDefense;
+
+    fn state(&self) -> &GameState {
+        &self.imp().state
+    }
+
+    fn widgets(&self) -> GameWidgets {
+        let imp = self.imp();
+        GameWidgets {
+            game_setup: imp.game_setup.get(),
+            pause_overlay: imp.pause_overlay.get(),
+            labels: vec![imp.score_label.get(), imp.wave_label.get()],
+            status_label: imp.status_label.get(),
+            results_box: imp.results_box.get(),
+            results_score_label: imp.results_score_label.get(),
+            results_level_label: imp.results_wave_label.get(),
+            new_record_label: imp.new_record_label.get(),
+        }
+    }
+
+    fn curve(difficulty: Difficulty) -> DifficultyCurve {
+        difficulty_curve(difficulty)
+    }
+
+    fn prepare_game(&self) {
+        let imp = self.imp();
+        *imp.wave.borrow_mut() = Wave::new(1);
+        *imp.slow_time_left.borrow_mut() = 0.0;
+        // The word list may have been changed in preferences since the last game
+        *imp.word_list.borrow_mut() = game_setup::game_words();
+        self.update_hint();
+
+        if let Some(defense_widget) = imp.defense_widget.borrow().as_ref() {
+            defense_widget.queue_draw();
+        }
+    }
+
+    fn clear(&self) {
+        self.imp().invaders.borrow_mut().clear();
+    }
+
+    fn object_count(&self) -> usize {
+        self.imp().invaders.borrow().len()
+    }
+
+    fn spawn(&self) {
+        let kind = self.imp().wave.borrow_mut().spawn();
+        if let Some(kind) = kind {
+            self.spawn_invader(kind);
+        }
+    }
+
+    // Words that reach the base are missed
+    fn advance(&self, dt: f64) -> usize {
+        let imp = self.imp();
+        let time_factor = {
+            let mut slow_time_left = imp.slow_time_left.borrow_mut();
+            let slowed = *slow_time_left > 0.0;
+            *slow_time_left = (*slow_time_left - dt).max(0.0);
+            if slowed {
+                SLOW_TIME_FACTOR
+            } else {
+                1.0
+            }
+        };
+        imp.power_up_label
+            .set_visible(*imp.slow_time_left.borrow() > 0.0);
+
+        // The next wave starts once every word of this one is gone
+        if imp.wave.borrow().is_cleared(imp.invaders.borrow().len()) {
+            let wave = imp.wave.borrow().next();
+            imp.wave.replace(wave);
+            let interval = imp
+                .state
+                .progress
+                .borrow()
+                .curve
+                .spawn_interval(wave.number);
+            imp.state.spawner.borrow_mut().set_interval(interval);
+            sounds::play(Sound::LevelUp);
+        }
+
+        let speed = imp
+            .state
+            .progress
+            .borrow()
+            .curve
+            .speed(imp.wave.borrow().number)
+            * time_factor;
+        let Some(defense_widget) = imp.defense_widget.borrow().clone() else {
+            return 0;
+        };
+        let base_y = defense_widget.height() as f64 - BASE_HEIGHT;
+        let missed = {
+            let mut invaders = imp.invaders.borrow_mut();
+            for invader in invaders.iter_mut() {
+                let speed = match invader.value.kind {
+                    InvaderKind::Boss => speed * BOSS_SPEED_FACTOR,
+                    InvaderKind::Word | InvaderKind::PowerUp(_) => speed,
+                };
+                invader.advance(0.0, speed, dt);
+            }
+            let count = invaders.len();
+            // Words are drawn from their top, so they hit the base about a line early
+            invaders.retain(|invader| invader.y + 30.0 < base_y);
+            count - invaders.len()
+        };
+
+        if missed > 0 {
+            self.update_hint();
+        }
+        defense_widget.queue_draw();
+        missed
+    }
+
+    fn set_game_visible(&self, visible: bool) {
+        let imp = self.imp();
+        if let Some(defense_widget) = imp.defense_widget.borrow().as_ref() {
+            defense_widget.set_visible(visible);
+            if visible {
+                defense_widget.grab_focus();
+            }
+        }
+        if let Some(keyboard) = imp.keyboard_widget.borrow().as_ref() {
+            keyboard.set_visible(visible);
+        }
+        // A running slow-down shows again with the next frame
+        if !visible {
+            imp.power_up_label.set_visible(false);
+        }
+    }
+
+    fn update_labels(&self) {
+        let imp = self.imp();
+        let progress = imp.state.progress.borrow();
+        imp.score_label
+            .set_text(&i18n_fmt! { i18n_fmt("Score: {}", progress.score) });
+        let wave = imp.wave.borrow().number;
+        imp.wave_label
+            .set_text(&i18n_fmt! { i18n_fmt("Wave: {}", wave) });
+
+        let status = progress.status_text();
+        imp.status_label.set_visible(status.is_some());
+        imp.status_label
+            .set_text(status.as_deref().unwrap_or_default());
+    }
+
+    // The wave reached takes the place of the level
+    fn level(&self) -> u32 {
+        self.imp().wave.borrow().number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invader_typing() {
+        let mut invader = Invader::new("añil".to_string(), InvaderKind::Word);
+        assert!(!invader.type_char('n'));
+        assert!(invader.type_char('a'));
+        assert!(invader.type_char('ñ'));
+        assert_eq!(invader.split(), ("añ", "il"));
+        assert!(invader.type_char('i'));
+        assert!(!invader.is_destroyed());
+        assert!(invader.type_char('l'));
+        assert!(invader.is_destroyed());
+        assert_eq!(invader.split(), ("añil", ""));
+    }
+
+    #[test]
+    fn test_find_target() {
+        let mut invaders = vec![
+            DefenseInvader::new(
+                Invader::new("cat".to_string(), InvaderKind::Word),
+                0.0,
+                50.0,
+            ),
+            DefenseInvader::new(
+                Invader::new("cow".to_string(), InvaderKind::Word),
+                0.0,
+                80.0,
+            ),
+            DefenseInvader::new(
+                Invader::new("dog".to_string(), InvaderKind::Word),
+                0.0,
+                90.0,
+            ),
+        ];
+
+        // The word closest to the base wins
+        assert_eq!(find_target(&invaders, 'c'), Some(1));
+        assert_eq!(find_target(&invaders, 'x'), None);
+
+        // Once a word is started, it stays the target
+        invaders[0].value.type_char('c');
+        assert_eq!(find_target(&invaders, 'd'), Some(0));
+    }
+
+    #[test]
+    fn test_waves() {
+        let mut wave = Wave::new(1);
+        for _ in 0..6 {
+            assert_eq!(wave.spawn(), Some(InvaderKind::Word));
+        }
+        assert_eq!(wave.spawn(), None);
+        assert!(!wave.is_cleared(1));
+        assert!(wave.is_cleared(0));
+
+        // Every few waves end with a boss
+        let mut boss_wave = Wave::new(BOSS_WAVE_INTERVAL);
+        let spawns: Vec<_> = std::iter::from_fn(|| boss_wave.spawn()).collect();
+        assert_eq!(spawns.len() as u32, 5 + 2 * BOSS_WAVE_INTERVAL);
+        assert_eq!(spawns.last(), Some(&InvaderKind::Boss));
+        assert_eq!(wave.next().number, 2);
+    }
+}